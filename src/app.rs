@@ -1,22 +1,55 @@
 use std::fmt::format;
-use std::fs::File;
+use std::fs::{self, File};
 use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::time::{Duration, Instant};
 // 
 mod mooncell;
-use mooncell::Mooncell;
+use mooncell::{Mooncell, FileType, FileUnit, ConflictPlan, CreateKind, FilterMode, Resolution};
 mod fileview;
 use fileview::Fileview;
+mod socketview;
+use socketview::Socketview;
+mod holderview;
+use holderview::Holderview;
+mod diskview;
+use diskview::Diskview;
+mod inventoryview;
+use inventoryview::Inventoryview;
+mod jobview;
+use jobview::Jobview;
+mod conflictview;
+use conflictview::Conflictview;
+mod trashview;
+use trashview::{Trashview, TrashPending};
+mod lineinput;
+use lineinput::LineInput;
+mod renameview;
+use renameview::Renameview;
+mod createview;
+use createview::Createview;
+mod permissionview;
+use permissionview::Permissionview;
+mod finderview;
+use finderview::{Finderview, FINDER_LIMIT};
+mod grepview;
+use grepview::Grepview;
+mod usageview;
+use usageview::{Usageview, UsagePending};
+mod treemapview;
+mod placesview;
+use placesview::{Placesview, PLACES_LIMIT};
 // rataui
 use color_eyre::{eyre, owo_colors::OwoColorize, Result};
-use crossterm::{cursor::Show, event::{self, Event, KeyCode, KeyEvent, KeyEventKind}, terminal};
+use crossterm::{cursor::Show, event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers}, terminal};
 use ratatui::{
     buffer::Buffer,
     layout::{self, Constraint, Direction, Flex, Layout, Position, Rect},
     style::{Color, Modifier, Style, Stylize},
     symbols::border,
     text::{Line, Span, Text},
-    widgets::{Bar, BarChart, BarGroup, Block, BorderType, Borders, Gauge, List, ListItem, ListState, Paragraph, Sparkline, Widget},
+    widgets::{Bar, BarChart, BarGroup, Block, BorderType, Borders, Gauge, List, ListItem, ListState, Paragraph, Sparkline, Tabs, Widget},
     DefaultTerminal, Frame,
 };
 
@@ -48,6 +81,37 @@ enum DisplayModel {
     Top,
     FileManage,
     FileView,
+    Socket,
+    Disk,
+    Inventory,
+    Jobs,
+    Trash,
+    Usage,
+}
+
+// 文件管理模式下的弹出窗口
+enum FmPopup {
+    Null,
+    Holder(Holderview),
+    Conflict(Conflictview),
+    Rename(Renameview),
+    Create(Createview),
+    Permission(Permissionview),
+    Finder(Finderview),
+    Grep(Grepview),
+    Places(Placesview),
+}
+
+// 文件管理模式下在列表里直接输入
+enum FmInput {
+    Null,
+    Rename(LineInput),    // 重命名光标所在的文件
+    Filter(LineInput, FilterMode),    // 边输入边过滤当前文件夹
+    PersistentFilter(LineInput, FilterMode),    // 设置一直生效的过滤
+    Path(LineInput),    // 在标题上编辑路径
+    SetBookmark,    // 等待按下书签的字符
+    JumpBookmark,
+    ConfirmDelete,    // 等待确认永久删除，除了y以外的按键都取消
 }
 
 pub struct App {
@@ -55,9 +119,23 @@ pub struct App {
     mooncell: Mooncell,
     model: DisplayModel,
     file_view: Fileview,
+    socket_view: Socketview,
+    disk_view: Diskview,
+    inventory_view: Inventoryview,
+    job_view: Jobview,
+    trash_view: Trashview,
+    usage_view: Usageview,
+    fm_popup: FmPopup,
+    fm_input: FmInput,
+    editor_pending: Option<Vec<PathBuf>>,    // 等待交给编辑器重命名的文件，需要在run里暂停界面
 
     list_state: ListState,    // 文件管理列表的转中状态
+    other_list_state: ListState,    // 双栏时另一栏的列表状态，切换时和list_state交换
+    tab_list_states: Vec<(ListState, ListState)>,    // 每个标签页的列表状态，当前标签页的在上面两个字段里
     file_manage_tips: String,    // 用于显示文件管理状态的提示
+    fm_errors: Vec<String>,    // 最近一次文件操作的错误
+    fm_dirty: bool,    // 文件列表需要刷新
+    exit_pending: bool,    // 还有后台任务时，需要再按一次退出
     input_history: Vec<String>,    // 显示cpu占用历史
     last_enter_time: Option<Instant>,    // 实现双击enter检测
 }
@@ -68,13 +146,27 @@ impl App {
 
         Self {
             list_state: state,
+            other_list_state: ListState::default(),
+            tab_list_states: vec![(ListState::default(), ListState::default())],
             last_enter_time: None,
             model: DisplayModel::Top, 
             user_input: String::new(),
             input_history: Vec::new(),
             mooncell: Mooncell::new(),
             file_view: Fileview::new(),
+            socket_view: Socketview::new(),
+            disk_view: Diskview::new(),
+            inventory_view: Inventoryview::new(),
+            job_view: Jobview::new(),
+            trash_view: Trashview::new(),
+            usage_view: Usageview::new(),
+            fm_popup: FmPopup::Null,
+            fm_input: FmInput::Null,
+            editor_pending: None,
             file_manage_tips: String::new(),
+            fm_errors: Vec::new(),
+            fm_dirty: true,
+            exit_pending: false,
         }
     }
 
@@ -85,10 +177,46 @@ impl App {
             // 绘制&捕获事件
             terminal.draw(|frame| self.draw(frame))?;
             let _ = self.handle_events();
+            if let Some(paths) = self.editor_pending.take() {
+                self.fm_edit_names(terminal, paths);
+            }
+
+            // 后台任务结束后提示并刷新文件列表
+            for job in self.mooncell.jobs_poll() {
+                self.file_manage_tips = format!("job #{} {} {}: {}/{} item(s)", job.id, job.kind.to_str(), job.state.to_str(), job.done, job.source_count);
+                if job.skipped > 0 {
+                    self.file_manage_tips.push_str(&format!(", {} skipped", job.skipped));
+                }
+                if let Some(last) = self.mooncell.fm_last_undo() {
+                    self.file_manage_tips.push_str(&format!("    undo {}[u]", last));
+                }
+                self.fm_errors = job.errors
+                    .iter()
+                    .map(|(path, e)| format!("{}: {}", path.to_string_lossy(), e))
+                    .collect();
+                self.fm_dirty = true;
+                if let DisplayModel::Trash = self.model {
+                    self.mooncell.trash_refresh();
+                }
+            }
             
             match self.model {
                 DisplayModel::FileManage => {
-                    self.mooncell.refresh_file_list();
+                    // 有按键或者每隔一段时间刷新一次
+                    if self.fm_dirty || count >= 10 {
+                        count = 0;
+                        self.fm_dirty = false;
+                        self.mooncell.refresh_file_list();
+                    } else {
+                        count += 1;
+                    }
+                    // 后台还在查找时更新结果
+                    self.fm_finder_refresh();
+                    self.fm_grep_refresh();
+                    if let FmPopup::Holder(view) = &mut self.fm_popup
+                        && view.take_refresh() {
+                        view.set_holders(self.mooncell.find_holders(view.get_path()));
+                    }
                 },
                 DisplayModel::Top => {
                     // 刷新数据
@@ -107,6 +235,24 @@ impl App {
                         Err(_) => { }
                     }
                 }
+                DisplayModel::Socket => {
+                    if count == 10 {
+                        count = 0;
+                        self.mooncell.socket_refresh();
+                    } else {
+                        count += 1;
+                    }
+                }
+                DisplayModel::Disk => {
+                    if count == 10 {
+                        count = 0;
+                        self.mooncell.disk_refresh();
+                    } else {
+                        count += 1;
+                    }
+                }
+                DisplayModel::Usage => self.mooncell.usage_poll(),
+                DisplayModel::Inventory | DisplayModel::Jobs | DisplayModel::Trash => {}
             }
         }
         Ok(())
@@ -121,6 +267,7 @@ impl App {
                 let layout_all = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints(vec![
+                        Constraint::Length(1),    // 标签页
                         Constraint::Fill(1),
                         Constraint::Length(2),
                     ])
@@ -131,34 +278,108 @@ impl App {
                         Constraint::Percentage(70),
                         Constraint::Fill(1),
                     ])
-                    .split(layout_all[0]);
+                    .split(layout_all[1]);
                 let file_message = Layout::default()
                     .direction(Direction::Vertical)
                     .constraints(vec![
                         Constraint::Length(1),    // 分割
-                        Constraint::Length(1),    // 名字
-                        Constraint::Length(1),    // 类型
-                        Constraint::Length(1),    // 占用空间
-                        Constraint::Length(2),    // 分割
-                        Constraint::Fill(0),    // 选中的文件名
+                        Constraint::Percentage(60),    // 光标所在文件的详细信息
+                        Constraint::Fill(1),    // 选中的文件名
+                        Constraint::Length(1),    // 后台任务
                         Constraint::Length(1),    // 进行文件操作的提示
                     ])
                     .split(layout_filemanage[1]);
                 
                 // tips
-                let tips_str = String::from("switch to top[tab]    exit[esc]\r\nReturn to the previous directory[backspace]    Enter folder[enter]");
+                let tips_str = format!("{}    two panes[|]    exit[esc]    filter[/]    persistent filter[F]    rename[r]    bulk rename[R]    rename in editor[E]    new[n]    permissions[p]    find[f]    grep[g]    bookmark[m]    go to bookmark[']    recent[z]    edit path[ctrl-l]    disk usage[U]    treemap[M]    sort[s/S/D]    hidden[.]\r\nnew tab[ctrl-t]    close tab[ctrl-w]    switch tab[ [ ] ]    Return to the previous directory[backspace]    Enter folder[enter]    processes using it[o]    trash[T]",
+                    if self.mooncell.fm_is_dual() { "switch pane[tab]    copy/move to other pane[c/x]" } else { "switch to top[tab]" });
                 let tips_p = Paragraph::new(tips_str.clone())
                         .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(tips_p, layout_all[1]);
+                frame.render_widget(tips_p, layout_all[2]);
 
-                // 文件列表
-                let tree_str_list = self.mooncell.fm_create_name_list();
+                // 标签页
+                let tab_titles: Vec<String> = self.mooncell.fm_tab_names()
+                    .iter()
+                    .enumerate()
+                    .map(|(i, name)| format!("{}:{}", i + 1, name))
+                    .collect();
+                let tabs = Tabs::new(tab_titles)
+                    .select(self.mooncell.fm_tab_index())
+                    .highlight_style(Style::default().bg(Color::LightBlue).fg(Color::Black).add_modifier(Modifier::BOLD));
+                frame.render_widget(tabs, layout_all[0]);
+
+                // 双栏时列表区域左右分开
+                let (list_area, other_area) = if self.mooncell.fm_is_dual() {
+                    let layout_panes = Layout::default()
+                        .direction(Direction::Horizontal)
+                        .constraints(vec![
+                            Constraint::Fill(1),
+                            Constraint::Fill(1),
+                        ])
+                        .split(layout_filemanage[0]);
+                    if self.mooncell.fm_is_left_active() {
+                        (layout_panes[0], Some(layout_panes[1]))
+                    } else {
+                        (layout_panes[1], Some(layout_panes[0]))
+                    }
+                } else {
+                    (layout_filemanage[0], None)
+                };
+
+                // 文件列表，高亮过滤匹配的字符，重命名时光标所在的一行换成输入框
+                let file_list = self.mooncell.get_file_list().unwrap_or_default();
+                let mut tree_str_list: Vec<Line> = self.mooncell.fm_create_name_list()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, display)| match file_list.get(i) {
+                        Some(file) => {
+                            // 按类型着色，有图标时显示在前面
+                            let style = self.mooncell.fm_type_style(file.file_type);
+                            let mut line = self.fm_highlight_name(&display, &file.name)
+                                .style(Style::default().fg(self.fm_type_color(file.file_type)));
+                            if !style.icon.is_empty() {
+                                line.spans.insert(0, Span::raw(format!("{} ", style.icon)));
+                            }
+                            line
+                        }
+                        None => Line::from(display),
+                    })
+                    .collect();
+                if let (FmInput::Rename(input), Some(pos)) = (&self.fm_input, self.list_state.selected())
+                    && let Some(line) = tree_str_list.get_mut(pos) {
+                    *line = input.to_line("rename: ");
+                }
                 let path_str = match self.mooncell.get_path_str() {
                     Some(str) => str.to_string(),
                     None => "...".to_string(),
                 };
+                let path_line = match &self.fm_input {
+                    FmInput::Path(input) => input.to_line("path: "),
+                    _ => Line::from(format!("{}{}", path_str, self.mooncell.fm_filter_description())),
+                };
+                let mut file_tree_block = Block::bordered()
+                    .title(path_line)
+                    .title_bottom(Line::from(format!("[{}]", self.mooncell.fm_sort_description())).right_aligned());
+                match &self.fm_input {
+                    FmInput::Filter(input, mode) => {
+                        file_tree_block = file_tree_block.title_bottom(input.to_line(&format!("{} /", mode.to_str())).left_aligned());
+                    }
+                    FmInput::PersistentFilter(input, mode) => {
+                        file_tree_block = file_tree_block.title_bottom(input.to_line(&format!("only {}: ", mode.to_str())).left_aligned());
+                    }
+                    FmInput::SetBookmark => {
+                        file_tree_block = file_tree_block.title_bottom(Line::from("bookmark this folder as: ").left_aligned());
+                    }
+                    FmInput::JumpBookmark => {
+                        file_tree_block = file_tree_block.title_bottom(Line::from("jump to bookmark: ").left_aligned());
+                    }
+                    _ => {}
+                }
+                if other_area.is_some() {
+                    file_tree_block = file_tree_block.border_style(Style::default().fg(Color::LightBlue));
+                }
                 let file_tree_list = List::new(tree_str_list)
-                    .block(Block::bordered().title(path_str))
+                    .block(file_tree_block)
                     .highlight_style(
                         Style::default()
                             .bg(Color::LightBlue)
@@ -166,56 +387,67 @@ impl App {
                             .add_modifier(Modifier::BOLD),
                     )
                     .highlight_symbol(">> ");
-                frame.render_stateful_widget(file_tree_list, layout_filemanage[0], &mut self.list_state);
+                frame.render_stateful_widget(file_tree_list, list_area, &mut self.list_state);
+
+                // 另一栏
+                if let Some(area) = other_area {
+                    let other_path_str = self.mooncell.fm_other_path_str().unwrap_or("...").to_string();
+                    let other_list = List::new(self.mooncell.fm_other_name_list())
+                        .block(Block::bordered().title(other_path_str).border_style(Style::default().fg(Color::DarkGray)))
+                        .highlight_style(Style::default().bg(Color::DarkGray))
+                        .highlight_symbol(">> ");
+                    frame.render_stateful_widget(other_list, area, &mut self.other_list_state);
+                }
                 
-                // 文件名、文件类型、文件大小
-                let mut str_file_name = String::new();
-                let mut str_file_type = String::new();
-                let mut str_file_size = String::new();
-                if let Some(file_list_pos) = self.list_state.selected() {
-                    match self.mooncell.get_file_list() {
-                        Some(list) => {
-                            if let Some(file_select) = list.get(file_list_pos) {
-                                str_file_name = file_select.name.clone();
-                                str_file_type = Mooncell::filetype_to_string(&file_select.file_type);
-                                if file_select.occupy < 1024.0 {
-                                    str_file_size = Mooncell::float_to_string(file_select.occupy as f32) + &"KB".to_string();
-                                } else if file_select.occupy < 1048576.0 {
-                                    str_file_size = Mooncell::float_to_string((file_select.occupy / 1024.0) as f32) + &"MB".to_string();
-                                } else {
-                                    str_file_size = Mooncell::float_to_string((file_select.occupy / 1024.0 / 1024.0) as f32) + &"GB".to_string();
-                                }
-                            }
-                        }
-                        None => {}
-                    }
+                // 光标所在文件的详细信息
+                let metadata_lines: Vec<String> = match self.fm_get_highlight() {
+                    Some(file) => self.mooncell.fm_metadata_lines(&file),
+                    None => vec![String::from("get file metadata error")],
+                };
+                let metadata_p = Paragraph::new(metadata_lines.join("\n"))
+                    .block(Block::new().borders(Borders::BOTTOM))
+                    .wrap(ratatui::widgets::Wrap { trim: false });
+                frame.render_widget(metadata_p, file_message[1]);
 
+                // 选中文件信息，没有选中文件时显示最近一次操作的错误
+                let select_str = self.mooncell.create_select_str();
+                if select_str.is_empty() && !self.fm_errors.is_empty() {
+                    let errors_p = Paragraph::new(self.fm_errors.join("\n"))
+                        .style(Style::default().fg(Color::Red))
+                        .wrap(ratatui::widgets::Wrap { trim: false });
+                    frame.render_widget(errors_p, file_message[2]);
                 } else {
-                    str_file_name = String::from("get name error");
-                    str_file_type = String::from("get type error");
-                    str_file_size = String::from("get file occupy error");
+                    let select_file_name_p = Paragraph::new(select_str)
+                        .alignment(ratatui::layout::Alignment::Center);
+                    frame.render_widget(select_file_name_p, file_message[2]);
                 }
-                let file_name_p = Paragraph::new(str_file_name.clone())
-                    .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(file_name_p, file_message[1]);
 
-                let file_type_p = Paragraph::new(str_file_type.clone())
-                    .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(file_type_p, file_message[2]);
-                
-                let file_size_p = Paragraph::new(str_file_size.clone())
-                    .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(file_size_p, file_message[3]);
-
-                // 选中文件信息
-                let select_file_name_p = Paragraph::new(self.mooncell.create_select_str())
-                    .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(select_file_name_p, file_message[5]);
+                // 后台任务
+                let running = self.mooncell.jobs_running_count();
+                if running > 0 {
+                    let jobs_p = Paragraph::new(format!("{} job(s) running [j]", running))
+                        .alignment(ratatui::layout::Alignment::Center)
+                        .style(Style::default().fg(Color::Yellow));
+                    frame.render_widget(jobs_p, file_message[3]);
+                }
 
                 // 提示str
                 let tips_p = Paragraph::new(self.file_manage_tips.clone())
                     .alignment(ratatui::layout::Alignment::Center);
-                frame.render_widget(tips_p, file_message[6]);
+                frame.render_widget(tips_p, file_message[4]);
+
+                // 弹出窗口
+                match &mut self.fm_popup {
+                    FmPopup::Holder(view) => view.draw(frame, popup_area(frame.area(), 80, 60)),
+                    FmPopup::Conflict(view) => view.draw(frame, popup_area(frame.area(), 70, 50)),
+                    FmPopup::Rename(view) => view.draw(frame, popup_area(frame.area(), 80, 70)),
+                    FmPopup::Create(view) => view.draw(frame, popup_area(frame.area(), 60, 50)),
+                    FmPopup::Permission(view) => view.draw(frame, popup_area(frame.area(), 90, 80)),
+                    FmPopup::Finder(view) => view.draw(frame, popup_area(frame.area(), 80, 80), self.mooncell.fm_finder_status()),
+                    FmPopup::Grep(view) => view.draw(frame, popup_area(frame.area(), 90, 85), self.mooncell.fm_grep_status()),
+                    FmPopup::Places(view) => view.draw(frame, popup_area(frame.area(), 70, 70)),
+                    FmPopup::Null => {}
+                }
             }
             
             // ************************** 资源管理模式 ************************** //
@@ -290,7 +522,7 @@ impl App {
                 frame.render_widget(logo_p, logo_systeam[0]);
 
                 // tips
-                let tips_str = String::from("switch to filemanage[tab]    exit[esc]");
+                let tips_str = String::from("switch to filemanage[tab]    sockets[F2]    disks[F3]    hardware[F4]    exit[esc]");
                 let tips_p = Paragraph::new(tips_str.clone())
                         .alignment(ratatui::layout::Alignment::Center);
                 frame.render_widget(tips_p, layout_top[2]);
@@ -388,6 +620,48 @@ impl App {
             DisplayModel::FileView => {
                 self.file_view.draw(frame);
             }
+
+            // ************************** 网络连接模式 ************************** //
+            DisplayModel::Socket => {
+                self.socket_view.draw(frame, self.mooncell.get_sockets());
+            }
+
+            // ************************** 硬盘列表模式 ************************** //
+            DisplayModel::Disk => {
+                self.disk_view.draw(frame, &self.mooncell.get_disks());
+            }
+
+            // ************************** 硬件清单模式 ************************** //
+            DisplayModel::Inventory => {
+                self.inventory_view.draw(frame, &self.mooncell.get_inventory_text());
+            }
+
+            // ************************** 后台任务模式 ************************** //
+            DisplayModel::Jobs => {
+                self.job_view.draw(frame, &self.mooncell.get_jobs());
+            }
+
+            // ************************** 回收站模式 ************************** //
+            DisplayModel::Trash => {
+                self.trash_view.draw(frame, self.mooncell.get_trash_items());
+            }
+
+            // ************************** 磁盘占用模式 ************************** //
+            DisplayModel::Usage => {
+                let entries = self.mooncell.usage_entries();
+                let type_colors: Vec<(FileType, Color)> = FileType::ALL
+                    .iter()
+                    .map(|file_type| (*file_type, self.fm_type_color(*file_type)))
+                    .collect();
+                self.usage_view.draw(
+                    frame,
+                    self.mooncell.usage_get_root(),
+                    self.mooncell.usage_progress(),
+                    self.mooncell.usage_current(),
+                    &entries,
+                    &type_colors,
+                );
+            }
         }
     }
 
@@ -405,21 +679,83 @@ impl App {
                 };
             }
             DisplayModel::FileManage => {
+                // 不能阻塞，后台任务的进度需要刷新
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_fm(key_event);
+                            self.fm_dirty = true;
+                        }
+                        _ => {}
+                    };
+                }
+            }
+            DisplayModel::FileView => {
                 match event::read()? {
                     Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                        self.handle_key_event_fm(key_event);
+                        self.handle_key_event_fv(key_event);
                     }
                     _ => {}
                 };
             }
-            DisplayModel::FileView => {
+            DisplayModel::Socket => {
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_socket(key_event);
+                        }
+                        _ => {}
+                    };
+                };
+            }
+            DisplayModel::Disk => {
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_disk(key_event);
+                        }
+                        _ => {}
+                    };
+                };
+            }
+            DisplayModel::Inventory => {
                 match event::read()? {
                     Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
-                        self.handle_key_event_fv(key_event);
+                        self.handle_key_event_inventory(key_event);
                     }
                     _ => {}
                 };
             }
+            DisplayModel::Jobs => {
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_jobs(key_event);
+                        }
+                        _ => {}
+                    };
+                }
+            }
+            DisplayModel::Trash => {
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_trash(key_event);
+                        }
+                        _ => {}
+                    };
+                }
+            }
+            DisplayModel::Usage => {
+                if event::poll(Duration::from_millis(100))? {
+                    match event::read()? {
+                        Event::Key(key_event) if key_event.kind == KeyEventKind::Press => {
+                            self.handle_key_event_usage(key_event);
+                        }
+                        _ => {}
+                    };
+                }
+            }
         }
         Ok(())
     }
@@ -442,19 +778,91 @@ impl App {
                 KeyCode::Tab => {
                     self.model = DisplayModel::FileManage;
                 }
+                KeyCode::F(2) => {
+                    self.mooncell.socket_refresh();
+                    self.model = DisplayModel::Socket;
+                }
+                KeyCode::F(3) => {
+                    self.mooncell.disk_refresh();
+                    self.model = DisplayModel::Disk;
+                }
+                KeyCode::F(4) => {
+                    self.mooncell.inventory_refresh();
+                    self.inventory_view.reset();
+                    self.model = DisplayModel::Inventory;
+                }
                 _ => {}
             }
         }
     }
     fn handle_key_event_fm(&mut self, key_event: KeyEvent) {
+        if !matches!(self.fm_popup, FmPopup::Null) {
+            self.handle_key_event_fm_popup(key_event);
+            return;
+        }
+        if !matches!(self.fm_input, FmInput::Null) {
+            self.handle_key_event_fm_input(key_event);
+            return;
+        }
+
         match key_event.code {
-            KeyCode::Esc => self.exit(),
+            KeyCode::Esc => {
+                // 有过滤时先清除过滤
+                if self.mooncell.fm_has_filter() {
+                    self.mooncell.fm_clear_filter();
+                    self.fm_refresh_filtered();
+                } else {
+                    self.exit();
+                }
+            }
             KeyCode::Up => self.file_list_previous(),
             KeyCode::Down => self.file_list_next(),
             KeyCode::Tab => {
-                self.model = DisplayModel::Top;
+                if self.mooncell.fm_switch_pane() {
+                    std::mem::swap(&mut self.list_state, &mut self.other_list_state);
+                } else {
+                    self.model = DisplayModel::Top;
+                }
                 self.file_manage_tips.clear();
             }
+            KeyCode::Char('t') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.fm_swap_tab_states();
+                self.mooncell.fm_tab_new();
+                let mut state = ListState::default();
+                state.select_first();
+                self.tab_list_states.insert(self.mooncell.fm_tab_index(), (state, ListState::default()));
+                self.fm_swap_tab_states();
+            }
+            KeyCode::Char('w') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                let index = self.mooncell.fm_tab_index();
+                if self.mooncell.fm_tab_close() {
+                    self.tab_list_states.remove(index);
+                    self.fm_swap_tab_states();
+                } else {
+                    self.file_manage_tips = String::from("can`t close the last tab");
+                }
+            }
+            KeyCode::Char('[') | KeyCode::Char(']') => {
+                let count = self.mooncell.fm_tab_count();
+                let index = self.mooncell.fm_tab_index();
+                let index = if key_event.code == KeyCode::Char(']') { (index + 1) % count } else { (index + count - 1) % count };
+                self.fm_swap_tab_states();
+                self.mooncell.fm_tab_select(index);
+                self.fm_swap_tab_states();
+            }
+            KeyCode::Char('|') => {
+                // 关闭时保留当前操作的一栏
+                self.mooncell.fm_switch_dual();
+                self.other_list_state.select_first();
+            }
+            KeyCode::Char('/') => {
+                self.mooncell.fm_clear_filter();
+                self.fm_input = FmInput::Filter(LineInput::new(""), FilterMode::Fuzzy);
+            }
+            KeyCode::Char('F') => {
+                let (pattern, mode) = self.mooncell.fm_get_persistent_filter().unwrap_or((String::new(), FilterMode::Glob));
+                self.fm_input = FmInput::PersistentFilter(LineInput::new(&pattern), mode);
+            }
             KeyCode::Backspace => { let _ = self.mooncell.back_upper_layer(); }
             /*使用一个列表存储选中的文件，改变目录后清除
              * 回车第一下选中，双击——进入文件夹、预览文件(待开发)
@@ -513,14 +921,836 @@ impl App {
                 // 更新最后按 Enter 的时间
                 self.last_enter_time = Some(now);
             }
-            KeyCode::Delete => self.mooncell.fm_del_ready(),
-            KeyCode::Char('c') => self.mooncell.fm_copy_ready(),
-            KeyCode::Char('x') => self.mooncell.fm_move_ready(),
-            KeyCode::Char('v') => self.mooncell.fm_perform_operations(),
+            // Delete放进回收站，Shift+Delete确认后永久删除
+            KeyCode::Delete if key_event.modifiers.contains(KeyModifiers::SHIFT) => {
+                self.mooncell.fm_del_ready();
+                match self.mooncell.fm_get_operation_count() {
+                    0 => self.file_manage_tips = String::from("nothing selected"),
+                    count => {
+                        self.fm_input = FmInput::ConfirmDelete;
+                        self.file_manage_tips = format!("delete {} item(s) permanently? [y/n]", count);
+                    }
+                }
+            }
+            KeyCode::Delete => {
+                self.mooncell.fm_trash_ready();
+                self.fm_ready_tips();
+            }
+            // 双栏时直接复制/移动到另一栏，没有选中的文件时使用光标所在的文件
+            KeyCode::Char('c') | KeyCode::Char('x') if self.mooncell.fm_is_dual() => {
+                if self.mooncell.fm_get_select().is_empty()
+                    && let Some(file) = self.fm_get_highlight() {
+                    self.mooncell.select_push(file);
+                }
+                if key_event.code == KeyCode::Char('c') {
+                    self.mooncell.fm_copy_ready();
+                } else {
+                    self.mooncell.fm_move_ready();
+                }
+                self.mooncell.fm_target_other();
+                self.fm_paste();
+            }
+            KeyCode::Char('c') => {
+                self.mooncell.fm_copy_ready();
+                self.fm_ready_tips();
+            }
+            KeyCode::Char('x') => {
+                self.mooncell.fm_move_ready();
+                self.fm_ready_tips();
+            }
+            KeyCode::Char('v') => self.fm_paste(),
+            KeyCode::Char('j') => self.model = DisplayModel::Jobs,
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                match self.mooncell.fm_redo() {
+                    Ok(message) => self.file_manage_tips = message,
+                    Err(e) => self.file_manage_tips = e.to_string(),
+                }
+            }
+            KeyCode::Char('u') => {
+                match self.mooncell.fm_undo() {
+                    Ok(message) => self.file_manage_tips = message,
+                    Err(e) => self.file_manage_tips = e.to_string(),
+                }
+            }
+            KeyCode::Char('T') => {
+                self.mooncell.trash_refresh();
+                self.trash_view.set_message(String::new());
+                self.model = DisplayModel::Trash;
+            }
+            KeyCode::Char('U') | KeyCode::Char('M') => {
+                self.mooncell.usage_start(self.usage_view.is_cross_fs());
+                self.usage_view.set_message(String::new());
+                self.usage_view.set_treemap(key_event.code == KeyCode::Char('M'));
+                self.usage_view.select(0);
+                self.model = DisplayModel::Usage;
+            }
+            KeyCode::Char('o') => {
+                if let Some(file) = self.fm_get_highlight() {
+                    let holders = self.mooncell.find_holders(&file.path);
+                    self.fm_popup = FmPopup::Holder(Holderview::new(file.path.clone(), holders));
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(file) = self.fm_get_highlight() {
+                    // 光标放在后缀前面
+                    let mut input = LineInput::new(&file.name);
+                    if !file.path.is_dir()
+                        && let Some(pos) = file.name.rfind('.')
+                        && pos > 0 {
+                        input.set_cursor(file.name[..pos].chars().count());
+                    }
+                    self.fm_input = FmInput::Rename(input);
+                }
+            }
+            KeyCode::Char('R') => {
+                let paths = self.fm_target_paths();
+                if !paths.is_empty() {
+                    let mut view = Renameview::new(paths);
+                    view.set_previews(self.mooncell.fm_rename_preview(view.get_paths(), &view.get_rule()));
+                    self.fm_popup = FmPopup::Rename(view);
+                }
+            }
+            KeyCode::Char('p') => {
+                let files = self.fm_targets();
+                if !files.is_empty() {
+                    self.fm_popup = FmPopup::Permission(Permissionview::new(files, &self.mooncell));
+                }
+            }
+            KeyCode::Char('s') => self.mooncell.fm_cycle_sort(),
+            KeyCode::Char('S') => self.mooncell.fm_reverse_sort(),
+            KeyCode::Char('D') => self.mooncell.fm_switch_dirs_first(),
+            KeyCode::Char('.') => self.mooncell.fm_switch_hidden(),
+            KeyCode::Char('f') => {
+                let root = self.mooncell.fm_finder_start();
+                self.fm_popup = FmPopup::Finder(Finderview::new(root));
+                self.fm_finder_refresh();
+            }
+            KeyCode::Char('g') => self.fm_popup = FmPopup::Grep(Grepview::new()),
+            KeyCode::Char('l') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                let path_str = self.mooncell.get_path_str().unwrap_or("").to_string();
+                self.fm_input = FmInput::Path(LineInput::new(&path_str));
+            }
+            KeyCode::Char('m') => self.fm_input = FmInput::SetBookmark,
+            KeyCode::Char('\'') => self.fm_input = FmInput::JumpBookmark,
+            KeyCode::Char('z') => {
+                self.fm_popup = FmPopup::Places(Placesview::new());
+                self.fm_places_refresh();
+            }
+            KeyCode::Char('n') => {
+                let highlight = self.fm_get_highlight().map(|file| file.path);
+                self.fm_popup = FmPopup::Create(Createview::new(highlight, self.mooncell.fm_templates()));
+            }
+            KeyCode::Char('E') => {
+                let paths = self.fm_target_paths();
+                if !paths.is_empty() {
+                    self.editor_pending = Some(paths);
+                }
+            }
             
             _ => {}
         }
     }
+
+    /*
+     * @概述        文件管理模式下，弹出窗口打开时的按键处理
+     */
+    fn handle_key_event_fm_popup(&mut self, key_event: KeyEvent) {
+        match &mut self.fm_popup {
+            FmPopup::Holder(view) => {
+                view.set_message(String::new());
+                // 等待确认发送信号
+                if view.is_pending() {
+                    let signal = view.take_pending_signal();
+                    if let (KeyCode::Char('y'), Some(signal), Some(holder)) = (key_event.code, signal, view.get_selected()) {
+                        match self.mooncell.send_signal(holder.pid, signal) {
+                            Ok(_) => view.set_message(format!("SIG{} sent to {}", signal, holder.pid)),
+                            Err(e) => view.set_message(format!("SIG{} failed: {}", signal, e.to_string())),
+                        }
+                        // 等进程处理完信号再刷新
+                        view.refresh_later();
+                    }
+                    return;
+                }
+
+                match key_event.code {
+                    KeyCode::Esc | KeyCode::Backspace => self.fm_popup = FmPopup::Null,
+                    KeyCode::Up => view.previous(),
+                    KeyCode::Down => view.next(),
+                    KeyCode::Char('t') => view.ready_signal("TERM"),
+                    KeyCode::Char('k') => view.ready_signal("KILL"),
+                    KeyCode::Char('r') => view.set_holders(self.mooncell.find_holders(view.get_path())),
+                    KeyCode::Char('w') => {
+                        if let Some(holder) = view.get_selected() {
+                            self.fm_popup = FmPopup::Null;
+                            self.jump_to_process_cwd(holder.pid);
+                        }
+                    }
+                    KeyCode::Char('e') => {
+                        if let Some(holder) = view.get_selected() {
+                            self.fm_popup = FmPopup::Null;
+                            self.jump_to_process_exe(holder.pid);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            FmPopup::Conflict(view) => {
+                match key_event.code {
+                    KeyCode::Esc | KeyCode::Backspace => {
+                        self.fm_popup = FmPopup::Null;
+                        self.file_manage_tips = String::from("paste cancelled, nothing was changed");
+                        return;
+                    }
+                    KeyCode::Char('o') => view.resolve(Resolution::Overwrite, &self.mooncell),
+                    KeyCode::Char('s') => view.resolve(Resolution::Skip, &self.mooncell),
+                    KeyCode::Char('k') => view.resolve(Resolution::KeepBoth, &self.mooncell),
+                    KeyCode::Char('n') => view.resolve(Resolution::OverwriteIfNewer, &self.mooncell),
+                    KeyCode::Char('m') => view.resolve(Resolution::Merge, &self.mooncell),
+                    KeyCode::Char('a') => view.switch_apply_all(),
+                    _ => {}
+                }
+                if view.is_finished() {
+                    let plan = view.take_plan();
+                    self.fm_popup = FmPopup::Null;
+                    self.fm_perform_operations(plan);
+                }
+            }
+            FmPopup::Rename(view) => {
+                let mut rule_changed = false;
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.fm_popup = FmPopup::Null;
+                        self.file_manage_tips = String::from("rename cancelled, nothing was changed");
+                        return;
+                    }
+                    KeyCode::Enter => {
+                        if view.problem_count() > 0 {
+                            view.set_error(format!("{} conflict(s), fix them before renaming", view.problem_count()));
+                            return;
+                        }
+                        match self.mooncell.fm_rename_apply(view.get_previews()) {
+                            Ok(count) => self.file_manage_tips = format!("{} item(s) renamed    undo[u]", count),
+                            Err(e) => self.file_manage_tips = e.to_string(),
+                        }
+                        self.mooncell.clear_select();
+                        self.fm_popup = FmPopup::Null;
+                        return;
+                    }
+                    KeyCode::Up => view.previous(),
+                    KeyCode::Down => view.next(),
+                    KeyCode::Tab if !view.is_from_editor() => view.switch_focus(),
+                    KeyCode::F(2) if !view.is_from_editor() => {
+                        view.switch_regex();
+                        rule_changed = true;
+                    }
+                    KeyCode::F(3) if !view.is_from_editor() => {
+                        view.next_case();
+                        rule_changed = true;
+                    }
+                    _ => rule_changed = view.handle_key(key_event),
+                }
+                if rule_changed {
+                    view.set_previews(self.mooncell.fm_rename_preview(view.get_paths(), &view.get_rule()));
+                }
+            }
+            FmPopup::Create(view) => {
+                if view.is_naming() {
+                    match key_event.code {
+                        KeyCode::Esc => view.cancel_naming(),
+                        KeyCode::Enter => {
+                            let name = view.get_name();
+                            let kind = view.get_kind();
+                            self.fm_popup = FmPopup::Null;
+                            if let Some(kind) = kind {
+                                self.fm_create(&kind, &name);
+                            }
+                        }
+                        _ => { view.handle_key(key_event); }
+                    }
+                    return;
+                }
+                match key_event.code {
+                    KeyCode::Esc | KeyCode::Backspace => self.fm_popup = FmPopup::Null,
+                    KeyCode::Up => view.previous(),
+                    KeyCode::Down => view.next(),
+                    KeyCode::Enter => view.choose(),
+                    KeyCode::Char('f') => view.choose_kind("file"),
+                    KeyCode::Char('d') => view.choose_kind("folder"),
+                    KeyCode::Char('l') => view.choose_kind("symlink"),
+                    KeyCode::Char('h') => view.choose_kind("hardlink"),
+                    _ => {}
+                }
+            }
+            FmPopup::Permission(view) => {
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.fm_popup = FmPopup::Null;
+                        self.file_manage_tips = String::from("permissions unchanged");
+                    }
+                    KeyCode::Enter if !view.has_error() => {
+                        let (done, errors) = self.mooncell.fm_permission_apply(view.get_previews());
+                        self.file_manage_tips = format!("permissions changed on {} item(s), {} failed", done, errors.len());
+                        self.fm_errors = errors
+                            .iter()
+                            .map(|(path, e)| format!("{}: {}", path.to_string_lossy(), e))
+                            .collect();
+                        self.mooncell.clear_select();
+                        self.fm_popup = FmPopup::Null;
+                    }
+                    KeyCode::Enter => {}
+                    _ => view.handle_key(key_event, &self.mooncell),
+                }
+            }
+            FmPopup::Finder(view) => {
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.fm_popup = FmPopup::Null;
+                        self.mooncell.fm_finder_stop();
+                    }
+                    KeyCode::Up => view.previous(),
+                    KeyCode::Down => view.next(),
+                    KeyCode::Enter => {
+                        // 进入所在的文件夹并选中
+                        if let Some(path) = view.get_selected().and_then(|found| self.mooncell.fm_finder_path(found))
+                            && let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                            let name = name.to_string_lossy().to_string();
+                            self.fm_popup = FmPopup::Null;
+                            self.mooncell.fm_finder_stop();
+                            self.fm_jump(parent, Some(&name));
+                        }
+                    }
+                    KeyCode::Char('o') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                        // 文件夹直接进入，文件在fileview中打开
+                        if let Some(found) = view.get_selected()
+                            && let Some(path) = self.mooncell.fm_finder_path(found) {
+                            let is_dir = found.is_dir;
+                            self.fm_popup = FmPopup::Null;
+                            self.mooncell.fm_finder_stop();
+                            if is_dir {
+                                self.fm_jump(&path, None);
+                            } else if let Some(parent) = path.parent()
+                                && let Some(str) = path.to_str() {
+                                let name = path.file_name().map(|name| name.to_string_lossy().to_string());
+                                self.fm_jump(parent, name.as_deref());
+                                self.file_view.set_path(str);
+                                self.model = DisplayModel::FileView;
+                            }
+                        }
+                    }
+                    _ => {
+                        if view.handle_key(key_event) {
+                            self.fm_finder_refresh();
+                        }
+                    }
+                }
+            }
+            FmPopup::Places(view) => {
+                match key_event.code {
+                    KeyCode::Esc => self.fm_popup = FmPopup::Null,
+                    KeyCode::Up => view.previous(),
+                    KeyCode::Down => view.next(),
+                    KeyCode::Enter => {
+                        if let Some((_, path)) = view.get_selected() {
+                            let path = path.clone();
+                            self.fm_popup = FmPopup::Null;
+                            self.fm_jump(&path, None);
+                        }
+                    }
+                    KeyCode::Delete => {
+                        match view.get_selected() {
+                            Some((Some(key), _)) => self.mooncell.fm_bookmark_remove(*key),
+                            Some((None, path)) => self.mooncell.fm_forget_dir(&path.clone()),
+                            None => {}
+                        }
+                        self.fm_places_refresh();
+                    }
+                    _ => {
+                        if view.handle_key(key_event) {
+                            self.fm_places_refresh();
+                        }
+                    }
+                }
+            }
+            FmPopup::Grep(view) => {
+                let (_, _, done) = self.mooncell.fm_grep_status();
+                match key_event.code {
+                    KeyCode::Esc if !done => self.mooncell.fm_grep_cancel(),
+                    KeyCode::Esc => {
+                        self.fm_popup = FmPopup::Null;
+                        self.mooncell.fm_grep_close();
+                    }
+                    KeyCode::Tab => view.switch_focus(),
+                    KeyCode::F(2) => view.switch_regex(),
+                    KeyCode::F(3) => view.switch_case(),
+                    KeyCode::F(4) => view.switch_binary(),
+                    KeyCode::Up if view.is_results_focus() => view.previous(),
+                    KeyCode::Down if view.is_results_focus() => view.next(),
+                    KeyCode::Enter if view.is_results_focus() => {
+                        // 在fileview中打开并滚动到匹配的行，返回后结果还在
+                        if let Some(result) = view.get_selected()
+                            && let Some(str) = result.path.to_str() {
+                            if let Ok(size) = crossterm::terminal::size() {
+                                self.file_view.refresh_termainal_size(size);
+                            }
+                            self.file_view.set_path(str);
+                            self.file_view.jump_to(result.line, result.start, result.end);
+                            self.model = DisplayModel::FileView;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        self.mooncell.fm_grep_close();
+                        match self.mooncell.fm_grep_start(view.get_options()) {
+                            Ok(_) => view.start(),
+                            Err(e) => view.set_error(e.to_string()),
+                        }
+                    }
+                    _ => { view.handle_key(key_event); }
+                }
+            }
+            FmPopup::Null => {}
+        }
+    }
+
+    /*
+     * @概述        取出内容查找的新结果
+     */
+    fn fm_grep_refresh(&mut self) {
+        if let FmPopup::Grep(view) = &mut self.fm_popup {
+            let results = self.mooncell
+                .fm_grep_results(view.get_count())
+                .into_iter()
+                .map(|result| (self.mooncell.fm_grep_relative(&result.path), result))
+                .collect();
+            view.push_results(results);
+        }
+    }
+
+    /*
+     * @概述        查找窗口的输入或者找到的条目数变化后重新匹配
+     */
+    fn fm_finder_refresh(&mut self) {
+        if let FmPopup::Finder(view) = &mut self.fm_popup {
+            let (count, _) = self.mooncell.fm_finder_status();
+            if view.need_search(count) {
+                let matches = self.mooncell.fm_finder_search(view.get_query(), FINDER_LIMIT);
+                view.set_matches(matches, count);
+            }
+        }
+    }
+
+    /*
+     * @概述        新建文件/文件夹/链接，成功后选中新建的条目，失败时在提示中显示原因
+     * @参数1       &CreateKind
+     * @参数2       &str，名字，可以包含/
+     */
+    fn fm_create(&mut self, kind: &CreateKind, name: &str) {
+        match self.mooncell.fm_create(kind, name) {
+            Ok(path) => {
+                self.file_manage_tips = format!("created {} {}", kind.to_str(), path.to_string_lossy());
+                // 嵌套的路径选中第一层
+                let first = name.split('/').find(|part| !part.is_empty()).unwrap_or(name).to_string();
+                let folder = self.mooncell.get_path_str().map(PathBuf::from);
+                if let Some(folder) = folder {
+                    self.fm_jump(&folder, Some(&first));
+                }
+            }
+            Err(e) => self.file_manage_tips = e.to_string(),
+        }
+    }
+
+    /*
+     * @概述        文件管理模式下，在列表里输入时的按键处理
+     */
+    fn handle_key_event_fm_input(&mut self, key_event: KeyEvent) {
+        match &mut self.fm_input {
+            FmInput::Rename(input) => {
+                match key_event.code {
+                    KeyCode::Esc => self.fm_input = FmInput::Null,
+                    KeyCode::Enter => {
+                        let new_name = input.get_text().to_string();
+                        self.fm_input = FmInput::Null;
+                        if let Some(file) = self.fm_get_highlight()
+                            && new_name != file.name {
+                            match self.mooncell.fm_rename(&file.path, &new_name) {
+                                Ok(_) => {
+                                    self.file_manage_tips = format!("renamed to {}    undo[u]", new_name);
+                                    self.mooncell.clear_select();
+                                    let folder = self.mooncell.get_path_str().map(PathBuf::from);
+                                    if let Some(folder) = folder {
+                                        self.fm_jump(&folder, Some(&new_name));
+                                    }
+                                }
+                                Err(e) => self.file_manage_tips = format!("can`t rename: {}", e.to_string()),
+                            }
+                        }
+                    }
+                    _ => { input.handle_key(key_event); }
+                }
+            }
+            FmInput::Filter(input, mode) => {
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.fm_input = FmInput::Null;
+                        self.mooncell.fm_clear_filter();
+                        self.fm_refresh_filtered();
+                    }
+                    // 保留过滤，回到列表里操作
+                    KeyCode::Enter => self.fm_input = FmInput::Null,
+                    KeyCode::Up => self.file_list_previous(),
+                    KeyCode::Down => self.file_list_next(),
+                    _ => {
+                        let is_changed = match key_event.code {
+                            KeyCode::Tab => {
+                                *mode = mode.next();
+                                true
+                            }
+                            _ => input.handle_key(key_event),
+                        };
+                        if is_changed {
+                            let (pattern, mode) = (input.get_text().to_string(), *mode);
+                            match self.mooncell.fm_set_filter(&pattern, mode) {
+                                Ok(_) => {
+                                    self.file_manage_tips.clear();
+                                    self.fm_refresh_filtered();
+                                }
+                                // 正则表达式还没输入完整时保留原来的结果
+                                Err(e) => self.file_manage_tips = format!("invalid filter: {}", e.to_string()),
+                            }
+                        }
+                    }
+                }
+            }
+            FmInput::PersistentFilter(input, mode) => {
+                match key_event.code {
+                    KeyCode::Esc => self.fm_input = FmInput::Null,
+                    KeyCode::Tab => *mode = mode.next(),
+                    KeyCode::Enter => {
+                        let (pattern, mode) = (input.get_text().to_string(), *mode);
+                        match self.mooncell.fm_set_persistent_filter(&pattern, mode) {
+                            Ok(_) => {
+                                self.fm_input = FmInput::Null;
+                                self.file_manage_tips.clear();
+                                self.fm_refresh_filtered();
+                            }
+                            Err(e) => self.file_manage_tips = format!("invalid filter: {}", e.to_string()),
+                        }
+                    }
+                    _ => { input.handle_key(key_event); }
+                }
+            }
+            FmInput::Path(input) => {
+                match key_event.code {
+                    KeyCode::Esc => {
+                        self.fm_input = FmInput::Null;
+                        self.file_manage_tips.clear();
+                    }
+                    KeyCode::Tab => {
+                        let (completed, candidates) = self.mooncell.fm_complete_path(input.get_text());
+                        *input = LineInput::new(&completed);
+                        self.file_manage_tips = match candidates.len() {
+                            0 => String::from("no matching folder"),
+                            1 => String::new(),
+                            _ => candidates.join("  "),
+                        };
+                    }
+                    KeyCode::Enter => {
+                        // 文件跳转到所在的文件夹并选中，路径不存在时保留输入
+                        match self.mooncell.fm_expand_path(input.get_text()) {
+                            Ok(path) if !path.exists() => {
+                                self.file_manage_tips = format!("{} does not exist", path.to_string_lossy());
+                            }
+                            Ok(path) if path.is_dir() => {
+                                self.fm_input = FmInput::Null;
+                                self.file_manage_tips.clear();
+                                self.fm_jump(&path, None);
+                            }
+                            Ok(path) => {
+                                self.fm_input = FmInput::Null;
+                                self.file_manage_tips.clear();
+                                let name = path.file_name().map(|name| name.to_string_lossy().to_string());
+                                if let Some(folder) = path.parent() {
+                                    self.fm_jump(folder, name.as_deref());
+                                }
+                            }
+                            Err(e) => self.file_manage_tips = e.to_string(),
+                        }
+                    }
+                    _ => { input.handle_key(key_event); }
+                }
+            }
+            FmInput::SetBookmark => {
+                self.fm_input = FmInput::Null;
+                if let KeyCode::Char(key) = key_event.code {
+                    self.mooncell.fm_bookmark_set(key);
+                    self.file_manage_tips = format!("bookmark '{} set to {}", key, self.mooncell.get_path_str().unwrap_or("..."));
+                }
+            }
+            FmInput::JumpBookmark => {
+                self.fm_input = FmInput::Null;
+                if let KeyCode::Char(key) = key_event.code {
+                    match self.mooncell.fm_bookmark_get(key) {
+                        Some(path) => { self.fm_jump(&path, None); }
+                        None => self.file_manage_tips = format!("no bookmark '{}", key),
+                    }
+                }
+            }
+            FmInput::ConfirmDelete => {
+                self.fm_input = FmInput::Null;
+                if key_event.code == KeyCode::Char('y') {
+                    self.fm_paste();
+                } else {
+                    self.mooncell.fm_cancel_operation();
+                    self.file_manage_tips = String::from("permanent delete cancelled");
+                }
+            }
+            FmInput::Null => {}
+        }
+    }
+
+    /*
+     * @概述        快速跳转窗口的输入改变后重新读取书签和访问过的文件夹
+     */
+    fn fm_places_refresh(&mut self) {
+        if let FmPopup::Places(view) = &mut self.fm_popup {
+            let recent = self.mooncell.fm_recent_dirs(view.get_query(), PLACES_LIMIT);
+            view.set_entries(self.mooncell.fm_bookmarks(), recent);
+        }
+    }
+
+    /*
+     * @概述        过滤改变后立即刷新列表，光标回到第一项
+     */
+    fn fm_refresh_filtered(&mut self) {
+        self.mooncell.refresh_file_list();
+        self.list_state.select_first();
+    }
+
+    /*
+     * @概述        高亮文件名中匹配过滤的字符
+     * @参数1       &str，列表中显示的名字，文件夹带有[]
+     * @参数2       &str，文件名
+     * @返回值      Line
+     */
+    fn fm_highlight_name(&self, display: &str, name: &str) -> Line<'static> {
+        let positions = self.mooncell.fm_match_positions(name);
+        if positions.is_empty() {
+            return Line::from(display.to_string());
+        }
+        // 文件夹显示时前面多了一个[
+        let offset = usize::from(display != name);
+        let spans: Vec<Span> = display
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                let is_match = i >= offset && positions.contains(&(i - offset));
+                let span = Span::raw(ch.to_string());
+                if is_match { span.fg(Color::Yellow).add_modifier(Modifier::BOLD) } else { span }
+            })
+            .collect();
+        Line::from(spans)
+    }
+
+    /*
+     * @概述        文件类型的颜色，配置的颜色无法解析时不设置颜色
+     * @参数1       FileType
+     * @返回值      Color
+     */
+    fn fm_type_color(&self, file_type: FileType) -> Color {
+        self.mooncell.fm_type_style(file_type).color.parse().unwrap_or(Color::Reset)
+    }
+
+    /*
+     * @概述        需要操作的文件，有选中的文件时为选中的文件，否则为光标所在的文件
+     * @返回值      Vec<FileUnit>
+     */
+    fn fm_targets(&self) -> Vec<FileUnit> {
+        let files = self.mooncell.fm_get_select();
+        if !files.is_empty() {
+            return files;
+        }
+        self.fm_get_highlight().map(|file| vec![file]).unwrap_or_default()
+    }
+
+    fn fm_target_paths(&self) -> Vec<PathBuf> {
+        self.fm_targets().into_iter().map(|file| file.path).collect()
+    }
+
+    /*
+     * @概述        暂停界面，把文件名写进临时文件交给$VISUAL/$EDITOR修改，返回后显示重命名预览
+     *              删除的行保持原名，不会删除文件
+     * @参数1       &mut DefaultTerminal
+     * @参数2       Vec<PathBuf>，需要重命名的文件
+     */
+    fn fm_edit_names(&mut self, terminal: &mut DefaultTerminal, paths: Vec<PathBuf>) {
+        let file = match self.mooncell.fm_editor_file(&paths) {
+            Ok(file) => file,
+            Err(e) => {
+                self.file_manage_tips = e.to_string();
+                return;
+            }
+        };
+        let editor = std::env::var("VISUAL")
+            .or_else(|_| std::env::var("EDITOR"))
+            .unwrap_or_else(|_| String::from("vi"));
+
+        // 编辑器需要完整的终端，结束后重新初始化
+        ratatui::restore();
+        let status = Command::new("sh")
+            .arg("-c")
+            .arg(format!("{} \"$1\"", editor))
+            .arg("mooncell")
+            .arg(&file)
+            .status();
+        *terminal = ratatui::init();
+
+        let text = fs::read_to_string(&file);
+        let _ = fs::remove_file(&file);
+        let text = match (status, text) {
+            (Ok(status), Ok(text)) if status.success() => text,
+            (Ok(status), _) if !status.success() => {
+                self.file_manage_tips = format!("{} exited with {}, nothing was changed", editor, status);
+                return;
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                self.file_manage_tips = format!("can`t run {}: {}", editor, e);
+                return;
+            }
+            _ => return,
+        };
+        match self.mooncell.fm_editor_previews(&text, &paths) {
+            Ok(previews) if previews.iter().all(|preview| !preview.is_changed()) => {
+                self.file_manage_tips = String::from("no names changed");
+            }
+            Ok(previews) => self.fm_popup = FmPopup::Rename(Renameview::from_editor(paths, previews)),
+            Err(e) => self.file_manage_tips = format!("can`t read edited names: {}", e.to_string()),
+        }
+    }
+
+    /*
+     * @概述        交换当前标签页的列表状态和tab_list_states里的位置，在Mooncell切换标签页的前后各调用一次
+     */
+    fn fm_swap_tab_states(&mut self) {
+        let (list_state, other_list_state) = &mut self.tab_list_states[self.mooncell.fm_tab_index()];
+        std::mem::swap(&mut self.list_state, list_state);
+        std::mem::swap(&mut self.other_list_state, other_list_state);
+    }
+
+    /*
+     * @概述        粘贴准备好的文件，有冲突时先询问，全部决定后再开始
+     */
+    fn fm_paste(&mut self) {
+        let conflicts = self.mooncell.fm_find_conflicts();
+        if conflicts.is_empty() {
+            self.fm_perform_operations(ConflictPlan::new());
+        } else {
+            self.fm_popup = FmPopup::Conflict(Conflictview::new(conflicts));
+        }
+    }
+
+    /*
+     * @概述        开始执行准备好的文件操作
+     * @参数1       ConflictPlan，冲突的处理方式
+     */
+    fn fm_perform_operations(&mut self, plan: ConflictPlan) {
+        match self.mooncell.fm_perform_operations(plan) {
+            Ok(id) => self.file_manage_tips = format!("job #{} started, view jobs[j]", id),
+            Err(e) => self.file_manage_tips = format!("nothing to paste: {}", e.to_string()),
+        }
+    }
+
+    /*
+     * @概述        切换到文件管理模式并打开路径，select不为空时选中该文件
+     *              文件被隐藏文件设置或一直生效的过滤隐藏时，显示隐藏文件或者清除过滤，并在提示中说明
+     * @参数1       &Path，文件夹路径
+     * @参数2       Option<&str>，需要选中的文件名
+     * @返回值      bool
+     */
+    fn fm_jump(&mut self, path: &Path, select: Option<&str>) -> bool {
+        if !self.mooncell.fm_set_path(path) {
+            self.file_manage_tips = format!("can`t open {}", path.to_string_lossy());
+            return false;
+        }
+        self.mooncell.refresh_file_list();
+        self.model = DisplayModel::FileManage;
+        self.list_state.select_first();
+        let name = match select {
+            Some(name) => name,
+            None => return true,
+        };
+        let find = |mooncell: &Mooncell| mooncell.get_file_list().and_then(|list| list.iter().position(|file| file.name == name));
+
+        let mut pos = find(&self.mooncell);
+        let mut notes: Vec<&str> = Vec::new();
+        if pos.is_none() && fs::symlink_metadata(path.join(name)).is_ok() {
+            if name.starts_with('.') && !self.mooncell.fm_is_show_hidden() {
+                self.mooncell.fm_switch_hidden();
+                self.mooncell.refresh_file_list();
+                pos = find(&self.mooncell);
+                notes.push("hidden files shown");
+            }
+            if pos.is_none() && self.mooncell.fm_get_persistent_filter().is_some() {
+                self.mooncell.fm_clear_persistent_filter();
+                self.mooncell.refresh_file_list();
+                pos = find(&self.mooncell);
+                notes.push("persistent filter cleared");
+            }
+        }
+        let note = match pos {
+            Some(pos) => {
+                self.list_state.select(Some(pos));
+                match notes.is_empty() {
+                    true => None,
+                    false => Some(format!("{} to show {}", notes.join(", "), name)),
+                }
+            }
+            None => Some(format!("{} is not in the list", name)),
+        };
+        if let Some(note) = note {
+            if !self.file_manage_tips.is_empty() {
+                self.file_manage_tips.push_str("    ");
+            }
+            self.file_manage_tips.push_str(&note);
+        }
+        true
+    }
+
+    fn jump_to_process_cwd(&mut self, pid: u32) {
+        match self.mooncell.get_process_cwd(pid) {
+            Some(cwd) => { self.fm_jump(&cwd, None); }
+            None => self.file_manage_tips = format!("can`t read cwd of {}", pid),
+        }
+    }
+
+    fn jump_to_process_exe(&mut self, pid: u32) {
+        match self.mooncell.get_process_exe(pid) {
+            Some(exe) => {
+                let name = exe.file_name().map(|name| name.to_string_lossy().to_string());
+                if let Some(folder) = exe.parent() {
+                    self.fm_jump(folder, name.as_deref());
+                }
+            }
+            None => self.file_manage_tips = format!("can`t read exe of {}", pid),
+        }
+    }
+
+    /*
+     * @概述        提示准备进行的文件操作
+     */
+    fn fm_ready_tips(&mut self) {
+        self.file_manage_tips = match self.mooncell.fm_get_operation_name() {
+            Some(operate) => format!("ready to {}, perform with [v]", operate),
+            None => String::from("nothing selected"),
+        };
+    }
+
+    /*
+     * @概述        返回文件列表中光标所在的文件
+     * @返回值      Option<FileUnit>
+     */
+    fn fm_get_highlight(&self) -> Option<FileUnit> {
+        let pos = self.list_state.selected()?;
+        self.mooncell.get_file_list()?.get(pos).cloned()
+    }
     fn handle_key_event_fv(&mut self, key_event: KeyEvent) {
         match key_event.code {
             KeyCode::Backspace => {
@@ -534,6 +1764,220 @@ impl App {
         }
     }
 
+    fn handle_key_event_socket(&mut self, key_event: KeyEvent) {
+        // 正在输入过滤字符串
+        if self.socket_view.is_filter_editing() {
+            match key_event.code {
+                KeyCode::Char(ch) => self.socket_view.filter_push(ch),
+                KeyCode::Backspace => self.socket_view.filter_pop(),
+                KeyCode::Enter => self.socket_view.stop_filter_edit(),
+                KeyCode::Esc => self.socket_view.filter_clear(),
+                _ => {}
+            }
+            return;
+        }
+
+        let len = self.socket_view.visible(self.mooncell.get_sockets()).len();
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Backspace => self.model = DisplayModel::Top,
+            KeyCode::Up => self.socket_view.previous(len),
+            KeyCode::Down => self.socket_view.next(len),
+            KeyCode::Char('/') => self.socket_view.start_filter_edit(),
+            KeyCode::Char('p') => self.socket_view.switch_protocol(),
+            KeyCode::Char('a') => self.socket_view.switch_show_all(),
+            KeyCode::Char('w') => {
+                if let Some(pid) = self.socket_view.get_selected(self.mooncell.get_sockets()).and_then(|unit| unit.pid) {
+                    self.jump_to_process_cwd(pid);
+                }
+            }
+            KeyCode::Char('e') => {
+                if let Some(pid) = self.socket_view.get_selected(self.mooncell.get_sockets()).and_then(|unit| unit.pid) {
+                    self.jump_to_process_exe(pid);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_event_disk(&mut self, key_event: KeyEvent) {
+        let disks = self.mooncell.get_disks();
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Backspace => self.model = DisplayModel::Top,
+            KeyCode::Up => self.disk_view.previous(disks.len()),
+            KeyCode::Down => self.disk_view.next(disks.len()),
+            KeyCode::Enter => {
+                if let Some(disk) = self.disk_view.get_selected(&disks) {
+                    self.fm_jump(&disk.mount_point, None);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_event_inventory(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Backspace => self.model = DisplayModel::Top,
+            KeyCode::Up => self.inventory_view.scroll_up(1),
+            KeyCode::Down => self.inventory_view.scroll_down(1),
+            KeyCode::PageUp => self.inventory_view.scroll_up(20),
+            KeyCode::PageDown => self.inventory_view.scroll_down(20),
+            KeyCode::Char('s') => {
+                match self.mooncell.export_inventory() {
+                    Ok(path) => self.inventory_view.set_message(format!("exported to {}", path.to_string_lossy())),
+                    Err(e) => self.inventory_view.set_message(format!("export failed: {}", e.to_string())),
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_key_event_jobs(&mut self, key_event: KeyEvent) {
+        let jobs = self.mooncell.get_jobs();
+        let selected = self.job_view.get_selected_id(&jobs);
+        self.job_view.set_message(String::new());
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Backspace => {
+                self.exit_pending = false;
+                self.model = DisplayModel::FileManage;
+            }
+            KeyCode::Up => self.job_view.previous(jobs.len()),
+            KeyCode::Down => self.job_view.next(jobs.len()),
+            KeyCode::Char('p') => {
+                if let Some(id) = selected {
+                    self.mooncell.job_switch_pause(id);
+                }
+            }
+            KeyCode::Char('c') => {
+                if let Some(id) = selected {
+                    self.mooncell.job_cancel(id);
+                }
+            }
+            KeyCode::Char('C') => self.mooncell.jobs_clear_history(),
+            _ => {}
+        }
+    }
+
+    fn handle_key_event_trash(&mut self, key_event: KeyEvent) {
+        let selected = self.trash_view.get_selected(self.mooncell.get_trash_items());
+        let len = self.mooncell.get_trash_items().len();
+        self.trash_view.set_message(String::new());
+
+        // 等待确认永久删除，除了y以外的按键都取消
+        match self.trash_view.take_pending() {
+            TrashPending::Remove => {
+                if let (KeyCode::Char('y'), Some(item)) = (key_event.code, selected) {
+                    match self.mooncell.trash_remove(&item) {
+                        Ok(_) => self.trash_view.set_message(format!("{} deleted permanently", item.original_path.to_string_lossy())),
+                        Err(e) => self.trash_view.set_message(format!("delete failed: {}", e.to_string())),
+                    }
+                }
+                return;
+            }
+            TrashPending::Empty => {
+                if key_event.code == KeyCode::Char('y') {
+                    let (count, errors) = self.mooncell.trash_empty();
+                    match errors.first() {
+                        Some(e) => self.trash_view.set_message(format!("{} item(s) deleted, {} failed: {}", count, errors.len(), e)),
+                        None => self.trash_view.set_message(format!("{} item(s) deleted", count)),
+                    }
+                }
+                return;
+            }
+            TrashPending::Null => {}
+        }
+
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Backspace => {
+                self.fm_dirty = true;
+                self.model = DisplayModel::FileManage;
+            }
+            KeyCode::Up => self.trash_view.previous(len),
+            KeyCode::Down => self.trash_view.next(len),
+            KeyCode::Char('r') => {
+                if let Some(item) = selected {
+                    match self.mooncell.trash_restore(&item) {
+                        Ok(_) => self.trash_view.set_message(format!("restored to {}", item.original_path.to_string_lossy())),
+                        Err(e) => self.trash_view.set_message(format!("restore failed: {}", e.to_string())),
+                    }
+                }
+            }
+            KeyCode::Char('d') if selected.is_some() => self.trash_view.ready(TrashPending::Remove),
+            KeyCode::Char('E') if len > 0 => self.trash_view.ready(TrashPending::Empty),
+            _ => {}
+        }
+    }
+
+    fn handle_key_event_usage(&mut self, key_event: KeyEvent) {
+        let entries = self.mooncell.usage_entries();
+        let selected = self.usage_view.get_selected(&entries);
+        self.usage_view.set_message(String::new());
+
+        // 等待确认删除，除了y以外的按键都取消
+        let pending = self.usage_view.take_pending();
+        if pending != UsagePending::Null {
+            if let (KeyCode::Char('y'), Some(entry)) = (key_event.code, selected) {
+                match self.mooncell.usage_remove(entry, pending == UsagePending::Delete) {
+                    Ok(id) => self.usage_view.set_message(format!("job #{} removing {}", id, entry.name)),
+                    Err(e) => self.usage_view.set_message(e.to_string()),
+                }
+            }
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Esc => self.exit(),
+            KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right if self.usage_view.is_treemap() => {
+                self.usage_view.treemap_move(key_event.code);
+            }
+            KeyCode::Up => self.usage_view.previous(entries.len()),
+            KeyCode::Down => self.usage_view.next(entries.len()),
+            KeyCode::Char('t') => self.usage_view.set_treemap(!self.usage_view.is_treemap()),
+            KeyCode::Enter => {
+                if let Some(entry) = selected
+                    && self.mooncell.usage_enter(entry) {
+                    self.usage_view.select(0);
+                }
+            }
+            KeyCode::Backspace => {
+                // 回到上一层并选中离开的文件夹，已经在最上层时回到文件管理
+                match self.mooncell.usage_back() {
+                    Some(left) => {
+                        let pos = self.mooncell.usage_entries().iter().position(|entry| entry.id == left).unwrap_or(0);
+                        self.usage_view.select(pos);
+                    }
+                    None => {
+                        self.fm_dirty = true;
+                        self.model = DisplayModel::FileManage;
+                    }
+                }
+            }
+            KeyCode::Char('d') if selected.is_some() => self.usage_view.ready(UsagePending::Trash),
+            KeyCode::Char('D') if selected.is_some() => self.usage_view.ready(UsagePending::Delete),
+            KeyCode::Char('f') => {
+                if let Some(path) = selected.and_then(|entry| self.mooncell.usage_path(entry.id))
+                    && let (Some(parent), Some(name)) = (path.parent(), path.file_name()) {
+                    let name = name.to_string_lossy().to_string();
+                    self.fm_jump(parent, Some(&name));
+                }
+            }
+            KeyCode::Char('r') => {
+                self.mooncell.usage_rescan(self.usage_view.is_cross_fs());
+                self.usage_view.select(0);
+            }
+            KeyCode::Char('x') => {
+                self.usage_view.switch_cross_fs();
+                self.mooncell.usage_rescan(self.usage_view.is_cross_fs());
+                self.usage_view.select(0);
+            }
+            _ => {}
+        }
+    }
+
     fn key_event_to_char(&mut self, key_event: KeyEvent) -> bool{
         for i in ' '..'~' {
             if key_event.code == KeyCode::Char(i) {
@@ -575,7 +2019,7 @@ impl App {
                             0
                         } else {
                             if i == 0 {
-                                vec.len()
+                                vec.len().saturating_sub(1)
                             } else {
                                 i - 1
                             }
@@ -589,27 +2033,46 @@ impl App {
         }
     }
 
+    /*
+     * @概述      退出，还有后台任务时先切换到任务列表确认一次
+     */
     fn exit(&mut self) {
+        let running = self.mooncell.jobs_running_count();
+        if running > 0 && !self.exit_pending {
+            self.exit_pending = true;
+            self.model = DisplayModel::Jobs;
+            self.job_view.set_message(format!("{} job(s) still running, press esc again to cancel them and exit", running));
+            return;
+        }
         self.mooncell.exit();
     }
 
     /*
-     * @概述      根据mooncell的disks创建Vec
+     * @概述      根据mooncell的disks创建Vec，以挂载点作为标签
      * @返回值    Vec<(String, u64)>
      */
     pub fn create_disk_list(&mut self) -> Vec<(String, u64)> {
         let mut usage_list: Vec<(String, u64)> = Vec::new();
 
         for disk in self.mooncell.get_disks() {
-            let usage = if disk.all_space == 0.0 {
-                0
-            } else {
-                let used = disk.all_space - disk.available_space;
-                ((used * 100.0) / disk.all_space).min(100.0) as u64
-            };
-            usage_list.push((disk.name, usage));
+            usage_list.push((disk.mount_point.to_string_lossy().to_string(), disk.usage_percent()));
         }
         return usage_list;
     }
 }
 
+
+/*
+ * @概述        计算弹出窗口的位置，居中显示
+ * @参数1       Rect，整个区域
+ * @参数2       u16，宽度百分比
+ * @参数3       u16，高度百分比
+ * @返回值      Rect
+ */
+fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::vertical([Constraint::Percentage(percent_y)]).flex(Flex::Center);
+    let horizontal = Layout::horizontal([Constraint::Percentage(percent_x)]).flex(Flex::Center);
+    let [area] = vertical.areas(area);
+    let [area] = horizontal.areas(area);
+    area
+}
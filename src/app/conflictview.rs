@@ -0,0 +1,147 @@
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    widgets::{Block, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use super::mooncell::{Conflict, ConflictPlan, Mooncell, Resolution};
+
+/*
+ * @概述        粘贴前逐个询问已经存在的目标怎么处理，全部回答后才开始操作
+ *              选择合并时，文件夹里的冲突会插入到队列前面继续询问
+ */
+pub struct Conflictview {
+    queue: VecDeque<Conflict>,    // 还没有处理的冲突
+    plan: ConflictPlan,    // 已经做出的决定
+    apply_all: bool,    // 下一个决定应用到之后所有的冲突
+    sticky: Option<Resolution>,    // 应用到全部的决定
+    message: String,    // 提示
+}
+
+impl Conflictview {
+    pub fn new(conflicts: Vec<Conflict>) -> Self {
+        Self {
+            queue: VecDeque::from(conflicts),
+            plan: ConflictPlan::new(),
+            apply_all: false,
+            sticky: None,
+            message: String::new(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    pub fn take_plan(&mut self) -> ConflictPlan {
+        std::mem::take(&mut self.plan)
+    }
+
+    pub fn switch_apply_all(&mut self) {
+        self.apply_all = !self.apply_all;
+    }
+
+    /*
+     * @概述        处理当前的冲突，开启了应用到全部时继续自动处理后面可以接受这个决定的冲突
+     * @参数1       Resolution
+     * @参数2       &Mooncell，合并时查找文件夹里的冲突
+     */
+    pub fn resolve(&mut self, resolution: Resolution, mooncell: &Mooncell) {
+        match self.queue.front() {
+            Some(conflict) if conflict.accepts(resolution) => {}
+            Some(_) => {
+                self.message = format!("can`t {} here", resolution.to_str());
+                return;
+            }
+            None => return,
+        }
+        self.message.clear();
+        if self.apply_all {
+            self.sticky = Some(resolution);
+        }
+        self.apply(resolution, mooncell);
+
+        while let Some(sticky) = self.sticky
+            && let Some(conflict) = self.queue.front()
+            && conflict.accepts(sticky) {
+            self.apply(sticky, mooncell);
+        }
+    }
+
+    fn apply(&mut self, resolution: Resolution, mooncell: &Mooncell) {
+        if let Some(conflict) = self.queue.pop_front() {
+            if resolution == Resolution::Merge {
+                for child in mooncell.fm_merge_conflicts(&conflict).into_iter().rev() {
+                    self.queue.push_front(child);
+                }
+            }
+            self.plan.insert(conflict.target, resolution);
+        }
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let layout_conflict = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 冲突信息
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let title = format!("conflict ({} left)", self.queue.len());
+        let info_str = match self.queue.front() {
+            Some(conflict) => {
+                let mut str = format!(
+                    "{} already exists\n\nsource: {}\n        {}\ntarget: {}\n        {}",
+                    conflict.target.to_string_lossy(),
+                    conflict.source.to_string_lossy(),
+                    Self::describe(conflict.source_is_dir, conflict.source_size, conflict.source_modified),
+                    conflict.target.to_string_lossy(),
+                    Self::describe(conflict.target_is_dir, conflict.target_size, conflict.target_modified),
+                );
+                if conflict.same_file {
+                    str.push_str("\n\nsource and target are the same file");
+                }
+                str.push_str(&format!("\n\napply to all: {}", if self.apply_all { "on" } else { "off" }));
+                str
+            }
+            None => String::new(),
+        };
+        let info_p = Paragraph::new(info_str)
+            .block(Block::bordered().title(title))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(info_p, layout_conflict[0]);
+
+        let tips_str = if self.message.is_empty() {
+            String::from("overwrite[o]  skip[s]  keep both[k]  if newer[n]  merge[m]  apply to all[a]  cancel[esc]")
+        } else {
+            self.message.clone()
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Color::DarkGray));
+        frame.render_widget(tips_p, layout_conflict[1]);
+    }
+
+    /*
+     * @概述        生成一边的类型、大小和修改时间
+     */
+    fn describe(is_dir: bool, size: u64, modified: Option<SystemTime>) -> String {
+        let modified_str = match modified {
+            Some(time) => DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => String::from("-"),
+        };
+        if is_dir {
+            format!("folder, modified {}", modified_str)
+        } else {
+            format!("{}, modified {}", Mooncell::bytes_to_string(size), modified_str)
+        }
+    }
+}
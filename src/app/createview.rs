@@ -0,0 +1,165 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::Line,
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+use super::mooncell::CreateKind;
+
+/*
+ * @概述        新建窗口，先选择新建的类型，再输入名字
+ *              链接指向打开窗口时光标所在的文件
+ */
+pub struct Createview {
+    options: Vec<CreateKind>,
+    list_state: ListState,
+    input: Option<LineInput>,    // 选择类型后输入名字
+}
+
+impl Createview {
+    /*
+     * @参数1       Option<PathBuf>，光标所在的文件，用于新建链接
+     * @参数2       Vec<PathBuf>，模板文件
+     */
+    pub fn new(highlight: Option<PathBuf>, templates: Vec<PathBuf>) -> Self {
+        let mut options = vec![CreateKind::File, CreateKind::Folder];
+        if let Some(path) = highlight {
+            options.push(CreateKind::Symlink(path.clone()));
+            options.push(CreateKind::Hardlink(path));
+        }
+        options.extend(templates.into_iter().map(CreateKind::Template));
+
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        Self { options, list_state, input: None }
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.options.len() => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.options.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn is_naming(&self) -> bool {
+        self.input.is_some()
+    }
+
+    /*
+     * @概述        选中第一个这种类型的选项并开始输入名字，用于快捷键
+     * @参数1       &str，CreateKind::to_str
+     */
+    pub fn choose_kind(&mut self, kind: &str) {
+        if let Some(pos) = self.options.iter().position(|option| option.to_str() == kind) {
+            self.list_state.select(Some(pos));
+            self.choose();
+        }
+    }
+
+    /*
+     * @概述        选择光标所在的类型，开始输入名字，链接和模板默认使用原来的名字
+     */
+    pub fn choose(&mut self) {
+        let name = match self.get_kind() {
+            Some(CreateKind::Symlink(path)) | Some(CreateKind::Hardlink(path)) | Some(CreateKind::Template(path)) => Self::file_name(&path),
+            _ => String::new(),
+        };
+        let mut input = LineInput::new(&name);
+        if let Some(pos) = name.rfind('.')
+            && pos > 0 {
+            input.set_cursor(name[..pos].chars().count());
+        }
+        self.input = Some(input);
+    }
+
+    pub fn cancel_naming(&mut self) {
+        self.input = None;
+    }
+
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        match &mut self.input {
+            Some(input) => input.handle_key(key_event),
+            None => false,
+        }
+    }
+
+    pub fn get_kind(&self) -> Option<CreateKind> {
+        self.options.get(self.list_state.selected()?).cloned()
+    }
+
+    pub fn get_name(&self) -> String {
+        self.input.as_ref().map(|input| input.get_text().to_string()).unwrap_or_default()
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let layout_create = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 类型
+                Constraint::Length(3),    // 名字
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let items: Vec<String> = self.options.iter().map(Self::describe).collect();
+        let list = List::new(items)
+            .block(Block::bordered().title("new"))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, layout_create[0], &mut self.list_state);
+
+        let name_line = match &self.input {
+            Some(input) => input.to_line(""),
+            None => Line::from(""),
+        };
+        let name_p = Paragraph::new(name_line)
+            .block(Block::bordered().title("name, use / to create nested folders"));
+        frame.render_widget(name_p, layout_create[1]);
+
+        let tips_str = if self.is_naming() {
+            "create[enter]  back[esc]"
+        } else {
+            "file[f]  folder[d]  symlink[l]  hardlink[h]  choose[enter]  cancel[esc]"
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Color::DarkGray));
+        frame.render_widget(tips_p, layout_create[2]);
+    }
+
+    fn describe(kind: &CreateKind) -> String {
+        match kind {
+            CreateKind::File => String::from("empty file"),
+            CreateKind::Folder => String::from("folder"),
+            CreateKind::Symlink(path) => format!("symlink to {}", path.to_string_lossy()),
+            CreateKind::Hardlink(path) => format!("hardlink to {}", path.to_string_lossy()),
+            CreateKind::Template(path) => format!("from template {}", Self::file_name(path)),
+        }
+    }
+
+    fn file_name(path: &Path) -> String {
+        path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+    }
+}
@@ -0,0 +1,128 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::mooncell::DiskInfo;
+
+/*
+ * @概述        可选中的硬盘列表，显示挂载点、文件系统、挂载选项和inode使用情况
+ */
+pub struct Diskview {
+    table_state: TableState,
+}
+
+impl Diskview {
+    pub fn new() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select_first();
+        Self { table_state }
+    }
+
+    pub fn next(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(0) | None => len.saturating_sub(1),
+            Some(i) => (i - 1).min(len.saturating_sub(1)),
+        };
+        self.table_state.select(Some(i));
+    }
+
+    /*
+     * @概述        返回当前选中的硬盘
+     */
+    pub fn get_selected(&self, disks: &[DiskInfo]) -> Option<DiskInfo> {
+        disks.get(self.table_state.selected()?).cloned()
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, disks: &[DiskInfo]) {
+        let layout_disk = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 硬盘列表
+                Constraint::Length(5),    // 选中硬盘的详细信息
+                Constraint::Length(1),    // 提示
+            ])
+            .split(frame.area());
+
+        if let Some(pos) = self.table_state.selected()
+            && pos >= disks.len() {
+            self.table_state.select(Some(disks.len().saturating_sub(1)));
+        }
+
+        // 硬盘列表
+        let rows: Vec<Row> = disks
+            .iter()
+            .map(|disk| {
+                let inode_str = match disk.inode_usage_percent() {
+                    Some(percent) => format!("{}%", percent),
+                    None => String::from("-"),
+                };
+                Row::new(vec![
+                    disk.mount_point.to_string_lossy().to_string(),
+                    disk.name.clone(),
+                    disk.file_system.clone(),
+                    format!("{:.1}G", disk.all_space),
+                    format!("{:.1}G", disk.all_space - disk.available_space),
+                    format!("{}%", disk.usage_percent()),
+                    inode_str,
+                    String::from(if disk.removable { "yes" } else { "no" }),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(9),
+            Constraint::Length(9),
+            Constraint::Length(5),
+            Constraint::Length(6),
+            Constraint::Length(9),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Mount", "Device", "Type", "Size", "Used", "Use%", "Inode%", "Removable"])
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .block(Block::bordered().title("disks"))
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_stateful_widget(table, layout_disk[0], &mut self.table_state);
+
+        // 详细信息
+        let detail_str = match self.get_selected(disks) {
+            Some(disk) => format!(
+                "{} on {} type {}\noptions: {}\ninodes: {} total, {} free",
+                disk.name,
+                disk.mount_point.to_string_lossy(),
+                disk.file_system,
+                disk.mount_options,
+                disk.inodes_total,
+                disk.inodes_free,
+            ),
+            None => String::from("no disk"),
+        };
+        let detail_p = Paragraph::new(detail_str)
+            .block(Block::bordered().title("detail"));
+        frame.render_widget(detail_p, layout_disk[1]);
+
+        // tips
+        let tips_p = Paragraph::new("open in filemanage[enter]    back to top[backspace]    exit[esc]")
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(tips_p, layout_disk[2]);
+    }
+}
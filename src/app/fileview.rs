@@ -1,13 +1,15 @@
 use std::path::PathBuf;
-use std::fs::read_to_string;
+use std::fs::read;
 use ratatui::{
-    layout::{Constraint, Direction, Layout}, widgets::{Block, Paragraph}, Frame
+    layout::{Constraint, Direction, Layout}, style::{Color, Modifier, Style, Stylize}, text::{Line, Span}, widgets::{Block, Paragraph}, Frame
 };
 
 pub struct Fileview {
     path: PathBuf,
     terminal_size: (u16, u16),
     start_number: usize,
+    highlight: Option<(usize, usize, usize)>,    // 高亮的(行号, 开始字节, 结束字节)，内容查找打开时使用
+    pending_jump: bool,    // 下一次绘制时滚动到高亮的行
 }
 
 impl Fileview {
@@ -16,11 +18,25 @@ impl Fileview {
             path: PathBuf::new(),
             terminal_size: (0, 0),
             start_number: 1,
+            highlight: None,
+            pending_jump: false,
         }
     }
 
     pub fn start_number_rezero(&mut self) {
         self.start_number = 1;
+        self.highlight = None;
+    }
+
+    /*
+     * @概述        打开后滚动到某一行并高亮匹配的部分
+     * @参数1       usize，行号，从1开始
+     * @参数2       usize，匹配在这一行中的开始字节
+     * @参数3       usize，结束字节
+     */
+    pub fn jump_to(&mut self, line: usize, start: usize, end: usize) {
+        self.highlight = Some((line, start, end));
+        self.pending_jump = true;
     }
 
     pub fn start_number_up(&mut self) {
@@ -49,12 +65,13 @@ impl Fileview {
             ])
             .split(frame.area());
 
-        let file_content_str = match read_to_string(self.path.clone()) {
+        // 和内容查找一样，不是UTF-8的部分替换后显示，行号保持一致
+        let file_content_str = match read(self.path.clone()) {
             Err(e) => String::from(format!("file error\n{}", e.to_string())),
-            Ok(str) => str,
+            Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
         };
-        let file_content_str = self.str_display_format(file_content_str);
-        let file_content_p = Paragraph::new(file_content_str.clone())
+        let file_content_lines = self.str_display_format(file_content_str);
+        let file_content_p = Paragraph::new(file_content_lines)
             .block(Block::bordered().title(self.get_path_str()));
         frame.render_widget(file_content_p, layout_fileview[0]);
     }
@@ -66,47 +83,63 @@ impl Fileview {
         }
     }
 
-    fn str_display_format(&self, str: String) -> String {
-        // 添加行号
-        let numbered_str = str
-            .lines()                         // 将内容按行分割
-            .enumerate()                    // 枚举行号（从0开始）
-            .map(|(i, line)| format!("{}|   {}", Self::number_format(i + 1), line)) // 添加行号并格式化
-            .collect::<Vec<String>>()       // 收集为 Vec<String>
-            .join("\n");                    // 用换行符连接成最终字符串
-
-        // 按照终端尺寸对内容分割
-        let mut format_str = String::new();
-        for line in numbered_str.split_inclusive('\n') {
-            if line.len() > (self.terminal_size.0 - 9) as usize {
-                let mut line_length = 0;
-                let mut new_line = String::new();
-                for ch in line.chars() {
-                    line_length += 1;
-                    if line_length == (self.terminal_size.0 - 9) {
-                        new_line.push_str(&format!("\n       {}", ch));
-                        line_length = 1;
-                    } else {
-                        new_line.push(ch);
+    fn str_display_format(&mut self, str: String) -> Vec<Line<'static>> {
+        // 按照终端尺寸对内容分割，续行前面空出行号的位置
+        let width = (self.terminal_size.0.saturating_sub(9) as usize).max(2);
+        let mut display_lines: Vec<Line> = Vec::new();
+        let mut jump_number = None;
+        for (i, line) in str.lines().enumerate() {
+            let number = i + 1;
+            let highlight = match self.highlight {
+                Some((line, start, end)) if line == number => Some((start, end)),
+                _ => None,
+            };
+            if highlight.is_some() {
+                jump_number = Some(display_lines.len() + 1);
+            }
+
+            // 添加行号，匹配的部分换成字符位置
+            let prefix = format!("{}|   ", Self::number_format(number));
+            let prefix_len = prefix.chars().count();
+            let highlight_chars = highlight.map(|(start, end)| {
+                let to_chars = |byte: usize| line.char_indices().take_while(|(pos, _)| *pos < byte).count();
+                (prefix_len + to_chars(start), prefix_len + to_chars(end))
+            });
+            let chars: Vec<char> = prefix.chars().chain(line.chars()).collect();
+            for (chunk_i, chunk) in chars.chunks(width - 1).enumerate() {
+                let offset = chunk_i * (width - 1);
+                let mut spans = Vec::new();
+                if chunk_i > 0 {
+                    spans.push(Span::raw("       "));
+                }
+                match highlight_chars {
+                    Some((start, end)) => {
+                        for (j, ch) in chunk.iter().enumerate() {
+                            let span = Span::raw(ch.to_string());
+                            let pos = offset + j;
+                            spans.push(if pos >= start && pos < end { span.fg(Color::Black).bg(Color::Yellow).add_modifier(Modifier::BOLD) } else { span });
+                        }
                     }
+                    None => spans.push(Span::raw(chunk.iter().collect::<String>())),
+                }
+                let mut display_line = Line::from(spans);
+                if highlight.is_some() {
+                    display_line = display_line.style(Style::default().bg(Color::DarkGray));
                 }
-                format_str.push_str(&new_line);
-            } else {
-                format_str.push_str(line);
+                display_lines.push(display_line);
             }
         }
 
-        // 删去起始前的内容
-        let mut count: usize = 1;
-        let mut neo_format_str = String::new();
-        for line in format_str.split_inclusive('\n') {
-            if count >= self.start_number {
-                neo_format_str.push_str(line);
+        // 滚动到高亮的行，上面留几行上下文
+        if self.pending_jump {
+            self.pending_jump = false;
+            if let Some(jump_number) = jump_number {
+                self.start_number = jump_number.saturating_sub(3).max(1);
             }
-            count += 1;
         }
 
-        return neo_format_str;
+        // 删去起始前的内容
+        display_lines.into_iter().skip(self.start_number - 1).collect()
     }
 
     fn number_format(number: usize) -> String {
@@ -0,0 +1,166 @@
+use std::path::PathBuf;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+use super::mooncell::FinderMatch;
+
+/*
+ * @概述        递归查找窗口，上面输入，下面显示按分数排序的结果
+ *              后台还在遍历时，找到的条目数变化后重新匹配
+ */
+pub struct Finderview {
+    root: PathBuf,
+    input: LineInput,
+    matches: Vec<FinderMatch>,
+    list_state: ListState,
+    searched_count: Option<usize>,    // 上次匹配时已经找到的条目数，None表示需要重新匹配
+}
+
+pub const FINDER_LIMIT: usize = 500;    // 最多显示的结果数
+
+impl Finderview {
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            input: LineInput::new(""),
+            matches: Vec::new(),
+            list_state: ListState::default(),
+            searched_count: None,
+        }
+    }
+
+    /*
+     * @概述        处理输入，内容改变后需要重新匹配
+     * @参数1       KeyEvent
+     * @返回值      bool，按键是否被处理
+     */
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        let old = self.input.get_text().to_string();
+        let handled = self.input.handle_key(key_event);
+        if old != self.input.get_text() {
+            self.searched_count = None;
+        }
+        handled
+    }
+
+    pub fn get_query(&self) -> &str {
+        self.input.get_text()
+    }
+
+    /*
+     * @概述        是否需要重新匹配
+     * @参数1       usize，现在找到的条目数
+     * @返回值      bool
+     */
+    pub fn need_search(&self, count: usize) -> bool {
+        self.searched_count != Some(count)
+    }
+
+    /*
+     * @概述        设置重新匹配的结果，之前选中的条目还在时保持选中，否则选中第一个
+     * @参数1       Vec<FinderMatch>
+     * @参数2       usize，匹配时已经找到的条目数
+     */
+    pub fn set_matches(&mut self, matches: Vec<FinderMatch>, count: usize) {
+        let selected = self.get_selected().map(|found| found.path.clone());
+        self.matches = matches;
+        self.searched_count = Some(count);
+        let pos = selected.and_then(|path| self.matches.iter().position(|found| found.path == path));
+        match pos {
+            Some(pos) => self.list_state.select(Some(pos)),
+            None if self.matches.is_empty() => self.list_state.select(None),
+            None => self.list_state.select_first(),
+        }
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.matches.len() => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.matches.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn get_selected(&self) -> Option<&FinderMatch> {
+        self.matches.get(self.list_state.selected()?)
+    }
+
+    /*
+     * @参数3       (usize, bool)，(已经找到的条目数, 是否遍历完成)
+     */
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, status: (usize, bool)) {
+        let layout_finder = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(3),    // 输入
+                Constraint::Fill(1),    // 结果
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let (count, done) = status;
+        let title = format!(
+            "find in {}    {} {} entries",
+            self.root.to_string_lossy(),
+            if done { "searched" } else { "searching..." },
+            count,
+        );
+        let input_p = Paragraph::new(self.input.to_line("> "))
+            .block(Block::bordered().title(title));
+        frame.render_widget(input_p, layout_finder[0]);
+
+        let items: Vec<Line> = self.matches.iter().map(Self::match_line).collect();
+        let list = List::new(items)
+            .block(Block::bordered().title(format!("{} match(es)", self.matches.len())))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, layout_finder[1], &mut self.list_state);
+
+        let tips_p = Paragraph::new("jump to it[enter]  open[ctrl-o]  move[up/down]  cancel[esc]")
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Color::DarkGray));
+        frame.render_widget(tips_p, layout_finder[2]);
+    }
+
+    /*
+     * @概述        高亮匹配的字符，文件夹以/结尾
+     */
+    fn match_line(found: &FinderMatch) -> Line<'static> {
+        let mut spans: Vec<Span> = found.path
+            .to_string_lossy()
+            .chars()
+            .enumerate()
+            .map(|(i, ch)| {
+                let span = Span::raw(ch.to_string());
+                if found.positions.contains(&i) { span.fg(Color::Yellow).add_modifier(Modifier::BOLD) } else { span }
+            })
+            .collect();
+        if found.is_dir {
+            spans.push(Span::raw("/"));
+        }
+        Line::from(spans)
+    }
+}
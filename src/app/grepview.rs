@@ -0,0 +1,233 @@
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+use super::mooncell::{GrepOptions, GrepResult};
+
+/*
+ * @概述        内容查找窗口，上面是查找条件，下面是不断增加的结果 文件:行:片段
+ *              Tab在查找内容、文件名、结果之间切换
+ */
+#[derive(PartialEq)]
+enum GrepFocus {
+    Pattern,
+    Globs,
+    Results,
+}
+
+pub struct Grepview {
+    pattern: LineInput,
+    globs: LineInput,    // 文件名通配符，空格或者逗号分隔
+    focus: GrepFocus,
+    use_regex: bool,
+    ignore_case: bool,
+    skip_binary: bool,
+    results: Vec<(String, GrepResult)>,    // (显示的相对路径, 结果)
+    list_state: ListState,
+    error: String,
+}
+
+impl Grepview {
+    pub fn new() -> Self {
+        Self {
+            pattern: LineInput::new(""),
+            globs: LineInput::new(""),
+            focus: GrepFocus::Pattern,
+            use_regex: false,
+            ignore_case: true,
+            skip_binary: true,
+            results: Vec::new(),
+            list_state: ListState::default(),
+            error: String::new(),
+        }
+    }
+
+    pub fn switch_focus(&mut self) {
+        self.focus = match self.focus {
+            GrepFocus::Pattern => GrepFocus::Globs,
+            GrepFocus::Globs if !self.results.is_empty() => GrepFocus::Results,
+            _ => GrepFocus::Pattern,
+        };
+    }
+
+    pub fn is_results_focus(&self) -> bool {
+        self.focus == GrepFocus::Results
+    }
+
+    pub fn switch_regex(&mut self) {
+        self.use_regex = !self.use_regex;
+    }
+
+    pub fn switch_case(&mut self) {
+        self.ignore_case = !self.ignore_case;
+    }
+
+    pub fn switch_binary(&mut self) {
+        self.skip_binary = !self.skip_binary;
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = error;
+    }
+
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        match self.focus {
+            GrepFocus::Pattern => self.pattern.handle_key(key_event),
+            GrepFocus::Globs => self.globs.handle_key(key_event),
+            GrepFocus::Results => false,
+        }
+    }
+
+    /*
+     * @概述        根据输入生成查找条件
+     * @返回值      GrepOptions
+     */
+    pub fn get_options(&self) -> GrepOptions {
+        GrepOptions {
+            pattern: self.pattern.get_text().to_string(),
+            use_regex: self.use_regex,
+            ignore_case: self.ignore_case,
+            globs: self.globs
+                .get_text()
+                .split([' ', ','])
+                .filter(|glob| !glob.is_empty())
+                .map(String::from)
+                .collect(),
+            skip_binary: self.skip_binary,
+            show_hidden: false,
+        }
+    }
+
+    /*
+     * @概述        开始新的查找，清空结果并切换到结果列表
+     */
+    pub fn start(&mut self) {
+        self.results.clear();
+        self.list_state.select(None);
+        self.error.clear();
+        self.focus = GrepFocus::Results;
+    }
+
+    pub fn get_count(&self) -> usize {
+        self.results.len()
+    }
+
+    /*
+     * @概述        追加新的结果
+     * @参数1       Vec<(String, GrepResult)>，(显示的相对路径, 结果)
+     */
+    pub fn push_results(&mut self, results: Vec<(String, GrepResult)>) {
+        self.results.extend(results);
+        if self.list_state.selected().is_none() && !self.results.is_empty() {
+            self.list_state.select_first();
+        }
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.results.len() => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.results.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn get_selected(&self) -> Option<&GrepResult> {
+        self.results.get(self.list_state.selected()?).map(|(_, result)| result)
+    }
+
+    /*
+     * @参数3       (usize, usize, bool)，(查找过的文件数, 结果数, 是否结束)
+     */
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, status: (usize, usize, bool)) {
+        let layout_grep = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(4),    // 查找条件
+                Constraint::Fill(1),    // 结果
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let focus_style = Style::default().fg(Color::Yellow);
+        let pattern_line = match self.focus {
+            GrepFocus::Pattern => self.pattern.to_line("search: ").style(focus_style),
+            _ => Line::from(format!("search: {}", self.pattern.get_text())),
+        };
+        let globs_line = match self.focus {
+            GrepFocus::Globs => self.globs.to_line("files:  ").style(focus_style),
+            _ => Line::from(format!("files:  {}", self.globs.get_text())),
+        };
+        let on_off = |on: bool| if on { "on" } else { "off" };
+        let options_p = Paragraph::new(vec![pattern_line, globs_line])
+            .block(Block::bordered().title(format!(
+                "grep    regex: {}    ignore case: {}    skip binary: {}",
+                on_off(self.use_regex),
+                on_off(self.ignore_case),
+                on_off(self.skip_binary),
+            )));
+        frame.render_widget(options_p, layout_grep[0]);
+
+        let (files, count, done) = status;
+        let items: Vec<Line> = self.results.iter().map(|(path, result)| Self::result_line(path, result)).collect();
+        let results_title = format!(
+            "{} match(es) in {} file(s) searched{}",
+            count,
+            files,
+            if done { "" } else { ", searching..." },
+        );
+        let mut results_block = Block::bordered().title(results_title);
+        if self.focus == GrepFocus::Results {
+            results_block = results_block.border_style(focus_style);
+        }
+        let list = List::new(items)
+            .block(results_block)
+            .highlight_style(Style::default().bg(Color::DarkGray))
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, layout_grep[1], &mut self.list_state);
+
+        let (tips_str, tips_style) = if !self.error.is_empty() {
+            (self.error.clone(), Style::default().bg(Color::DarkGray).fg(Color::Red))
+        } else if !done {
+            (String::from("stop[esc]  switch[tab]  open[enter]"), Style::default().bg(Color::DarkGray))
+        } else {
+            (
+                String::from("switch[tab]  search/open[enter]  regex[F2]  case[F3]  binary[F4]  close[esc]    files: *.rs *.toml"),
+                Style::default().bg(Color::DarkGray),
+            )
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(tips_style);
+        frame.render_widget(tips_p, layout_grep[2]);
+    }
+
+    /*
+     * @概述        文件:行:片段，高亮匹配的部分
+     */
+    fn result_line(path: &str, result: &GrepResult) -> Line<'static> {
+        let snippet = &result.snippet;
+        Line::from(vec![
+            Span::raw(path.to_string()).fg(Color::LightBlue),
+            Span::raw(format!(":{}: ", result.line)).fg(Color::Green),
+            Span::raw(snippet[..result.snippet_start].to_string()),
+            Span::raw(snippet[result.snippet_start..result.snippet_end].to_string()).fg(Color::Yellow).add_modifier(Modifier::BOLD),
+            Span::raw(snippet[result.snippet_end..].to_string()),
+        ])
+    }
+}
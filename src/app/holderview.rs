@@ -0,0 +1,154 @@
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use super::mooncell::FileHolder;
+
+/*
+ * @概述        显示占用了某个文件的进程列表，并可以向它们发送信号
+ */
+pub struct Holderview {
+    path: PathBuf,    // 被占用的文件
+    holders: Vec<FileHolder>,
+    list_state: ListState,
+    pending_signal: Option<&'static str>,    // 等待确认的信号
+    message: String,    // 操作结果的提示
+    refresh_at: Option<Instant>,    // 发送信号后等进程处理完再刷新
+}
+
+impl Holderview {
+    pub fn new(path: PathBuf, holders: Vec<FileHolder>) -> Self {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        Self {
+            path,
+            holders,
+            list_state,
+            pending_signal: None,
+            message: String::new(),
+            refresh_at: None,
+        }
+    }
+
+    pub fn get_path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn set_holders(&mut self, holders: Vec<FileHolder>) {
+        self.holders = holders;
+        if let Some(pos) = self.list_state.selected()
+            && pos >= self.holders.len() {
+            self.list_state.select(Some(self.holders.len().saturating_sub(1)));
+        }
+    }
+
+    /*
+     * @概述        一段时间后刷新进程列表，不阻塞界面
+     */
+    pub fn refresh_later(&mut self) {
+        self.refresh_at = Some(Instant::now() + Duration::from_millis(100));
+    }
+
+    /*
+     * @返回值      bool，是否到了需要刷新的时间
+     */
+    pub fn take_refresh(&mut self) -> bool {
+        match self.refresh_at {
+            Some(time) if Instant::now() >= time => {
+                self.refresh_at = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<FileHolder> {
+        self.holders.get(self.list_state.selected()?).cloned()
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.holders.len() => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.holders.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    /*
+     * @概述        准备发送信号，需要再确认一次
+     * @参数1       &'static str，信号名
+     */
+    pub fn ready_signal(&mut self, signal: &'static str) {
+        if self.get_selected().is_some() {
+            self.pending_signal = Some(signal);
+        }
+    }
+
+    pub fn take_pending_signal(&mut self) -> Option<&'static str> {
+        self.pending_signal.take()
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.pending_signal.is_some()
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let layout_holder = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 进程列表
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let title = format!("processes using {}", self.path.to_string_lossy());
+        let items: Vec<String> = if self.holders.is_empty() {
+            vec![String::from("no process is using it")]
+        } else {
+            self.holders
+                .iter()
+                .map(|holder| format!("{:>7}  {:<16} {:<8} {}", holder.pid, holder.name, holder.usage, holder.target.to_string_lossy()))
+                .collect()
+        };
+        let holder_list = List::new(items)
+            .block(Block::bordered().title(title))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(holder_list, layout_holder[0], &mut self.list_state);
+
+        let tips_str = match (self.pending_signal, self.get_selected()) {
+            (Some(signal), Some(holder)) => format!("send SIG{} to {} ({})? [y/n]", signal, holder.pid, holder.name),
+            _ if !self.message.is_empty() => self.message.clone(),
+            _ => String::from("SIGTERM[t]    SIGKILL[k]    open cwd[w]    open exe[e]    refresh[r]    close[backspace]"),
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Color::DarkGray));
+        frame.render_widget(tips_p, layout_holder[1]);
+    }
+}
@@ -0,0 +1,67 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    widgets::{Block, Paragraph},
+    Frame,
+};
+
+/*
+ * @概述        硬件清单界面，只负责滚动显示文本
+ */
+pub struct Inventoryview {
+    scroll: u16,    // 向下滚动的行数
+    message: String,    // 导出结果的提示
+}
+
+impl Inventoryview {
+    pub fn new() -> Self {
+        Self {
+            scroll: 0,
+            message: String::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.scroll = 0;
+        self.message.clear();
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_add(lines);
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, text: &str) {
+        let layout_inventory = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),
+                Constraint::Length(1),
+            ])
+            .split(frame.area());
+
+        // 不允许滚动超过最后一行
+        let line_count = text.lines().count() as u16;
+        self.scroll = self.scroll.min(line_count.saturating_sub(1));
+
+        let text_p = Paragraph::new(text.to_string())
+            .block(Block::bordered().title("hardware inventory"))
+            .scroll((self.scroll, 0));
+        frame.render_widget(text_p, layout_inventory[0]);
+
+        let tips_str = if self.message.is_empty() {
+            String::from("export as text[s]    scroll[up/down/pgup/pgdn]    back to top[backspace]    exit[esc]")
+        } else {
+            self.message.clone()
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(tips_p, layout_inventory[1]);
+    }
+}
@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Gauge, List, ListState, Paragraph, Wrap},
+    Frame,
+};
+
+use super::mooncell::{JobSnapshot, JobState, Mooncell};
+
+/*
+ * @概述        后台文件操作列表，进行中的在前，历史记录在后
+ */
+pub struct Jobview {
+    list_state: ListState,
+    message: String,    // 提示
+}
+
+impl Jobview {
+    pub fn new() -> Self {
+        let mut list_state = ListState::default();
+        list_state.select_first();
+        Self {
+            list_state,
+            message: String::new(),
+        }
+    }
+
+    pub fn next(&mut self, len: usize) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self, len: usize) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => len.saturating_sub(1),
+            Some(i) => (i - 1).min(len.saturating_sub(1)),
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn get_selected_id(&self, jobs: &[JobSnapshot]) -> Option<usize> {
+        jobs.get(self.list_state.selected()?).map(|job| job.id)
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, jobs: &[JobSnapshot]) {
+        let layout_job = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 任务列表
+                Constraint::Length(3),    // 选中任务的进度条
+                Constraint::Length(8),    // 选中任务的详细信息
+                Constraint::Length(1),    // 提示
+            ])
+            .split(frame.area());
+
+        if let Some(pos) = self.list_state.selected()
+            && pos >= jobs.len() {
+            self.list_state.select(Some(jobs.len().saturating_sub(1)));
+        }
+
+        // 任务列表
+        let items: Vec<String> = jobs.iter().map(Self::job_line).collect();
+        let job_list = List::new(items)
+            .block(Block::bordered().title("jobs"))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(job_list, layout_job[0], &mut self.list_state);
+
+        // 选中任务的进度和详细信息
+        let selected = self.list_state.selected().and_then(|pos| jobs.get(pos));
+        let ratio = match selected {
+            Some(job) if job.bytes_total > 0 => (job.bytes_done as f64 / job.bytes_total as f64).min(1.0),
+            Some(job) if job.items_total > 0 => (job.items_done as f64 / job.items_total as f64).min(1.0),
+            Some(job) if job.state.is_finished() => 1.0,
+            _ => 0.0,
+        };
+        let gauge = Gauge::default()
+            .block(Block::bordered().title("progress"))
+            .gauge_style(Style::default().fg(Color::Yellow))
+            .ratio(ratio);
+        frame.render_widget(gauge, layout_job[1]);
+
+        let detail_str = match selected {
+            Some(job) => Self::job_detail(job),
+            None => String::from("no job"),
+        };
+        let detail_p = Paragraph::new(detail_str)
+            .block(Block::bordered().title("detail"))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(detail_p, layout_job[2]);
+
+        // tips
+        let tips_str = if self.message.is_empty() {
+            String::from("pause/resume[p]    cancel[c]    clear history[C]    back to filemanage[backspace]    exit[esc]")
+        } else {
+            self.message.clone()
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(tips_p, layout_job[3]);
+    }
+
+    /*
+     * @概述        生成任务列表中的一行
+     */
+    fn job_line(job: &JobSnapshot) -> String {
+        let target = match &job.target {
+            Some(target) => format!(" -> {}", target.to_string_lossy()),
+            None => String::new(),
+        };
+        let mut line = format!("#{} {} {} item(s){}  [{}]", job.id, job.kind.to_str(), job.source_count, target, job.state.to_str());
+        if !job.state.is_finished() {
+            line.push_str(&format!(
+                "  {}/{}  {}/s",
+                Mooncell::bytes_to_string(job.bytes_done),
+                Mooncell::bytes_to_string(job.bytes_total),
+                Mooncell::bytes_to_string(job.speed as u64),
+            ));
+            if let Some(eta) = job.eta {
+                line.push_str(&format!("  ETA {}", Self::duration_to_string(eta)));
+            }
+        } else if !job.errors.is_empty() {
+            line.push_str(&format!("  {} error(s)", job.errors.len()));
+        }
+        line
+    }
+
+    /*
+     * @概述        生成选中任务的详细信息，结束的任务显示错误列表
+     */
+    fn job_detail(job: &JobSnapshot) -> String {
+        let mut str = format!(
+            "{} / {} items, {} / {}",
+            job.items_done,
+            job.items_total,
+            Mooncell::bytes_to_string(job.bytes_done),
+            Mooncell::bytes_to_string(job.bytes_total),
+        );
+        match job.state {
+            JobState::Running | JobState::Paused => {
+                str.push_str(&format!("\ncurrent: {}", job.current.to_string_lossy()));
+            }
+            _ => {
+                if job.skipped > 0 {
+                    str.push_str(&format!(", {} skipped", job.skipped));
+                }
+                for (path, e) in &job.errors {
+                    str.push_str(&format!("\n{}: {}", path.to_string_lossy(), e));
+                }
+            }
+        }
+        str
+    }
+
+    fn duration_to_string(duration: Duration) -> String {
+        let secs = duration.as_secs();
+        if secs >= 3600 {
+            format!("{}:{:02}:{:02}", secs / 3600, secs % 3600 / 60, secs % 60)
+        } else {
+            format!("{:02}:{:02}", secs / 60, secs % 60)
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    style::{Modifier, Style},
+    text::{Line, Span},
+};
+
+/*
+ * @概述        单行文本输入，记录光标位置，供重命名、过滤等需要输入的地方使用
+ *              光标按字符计数，而不是字节
+ */
+pub struct LineInput {
+    text: String,
+    cursor: usize,    // 光标前面的字符数
+}
+
+impl LineInput {
+    pub fn new(text: &str) -> Self {
+        Self { text: text.to_string(), cursor: text.chars().count() }
+    }
+
+    pub fn get_text(&self) -> &str {
+        &self.text
+    }
+
+    /*
+     * @概述        移动光标到某个字符前
+     * @参数1       usize，字符位置
+     */
+    pub fn set_cursor(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.text.chars().count());
+    }
+
+    /*
+     * @概述        处理编辑按键
+     * @参数1       KeyEvent
+     * @返回值      bool，按键是否被处理
+     */
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        let len = self.text.chars().count();
+        match key_event.code {
+            KeyCode::Char('a') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.cursor = 0,
+            KeyCode::Char('e') if key_event.modifiers.contains(KeyModifiers::CONTROL) => self.cursor = len,
+            KeyCode::Char('u') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                // 删除光标前的内容
+                self.text = self.text.chars().skip(self.cursor).collect();
+                self.cursor = 0;
+            }
+            KeyCode::Char(ch) if !key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                let pos = self.byte_pos(self.cursor);
+                self.text.insert(pos, ch);
+                self.cursor += 1;
+            }
+            KeyCode::Backspace if self.cursor > 0 => {
+                let pos = self.byte_pos(self.cursor - 1);
+                self.text.remove(pos);
+                self.cursor -= 1;
+            }
+            KeyCode::Delete if self.cursor < len => {
+                let pos = self.byte_pos(self.cursor);
+                self.text.remove(pos);
+            }
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(len),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = len,
+            KeyCode::Backspace | KeyCode::Delete => {}
+            _ => return false,
+        }
+        true
+    }
+
+    /*
+     * @概述        生成带光标的Line，光标处的字符反色显示
+     * @参数1       &str，显示在前面的提示
+     * @返回值      Line
+     */
+    pub fn to_line(&self, prompt: &str) -> Line<'static> {
+        let before: String = self.text.chars().take(self.cursor).collect();
+        let cursor: String = match self.text.chars().nth(self.cursor) {
+            Some(ch) => ch.to_string(),
+            None => String::from(" "),
+        };
+        let after: String = self.text.chars().skip(self.cursor + 1).collect();
+        Line::from(vec![
+            Span::raw(prompt.to_string()),
+            Span::raw(before),
+            Span::styled(cursor, Style::default().add_modifier(Modifier::REVERSED)),
+            Span::raw(after),
+        ])
+    }
+
+    fn byte_pos(&self, cursor: usize) -> usize {
+        match self.text.char_indices().nth(cursor) {
+            Some((pos, _)) => pos,
+            None => self.text.len(),
+        }
+    }
+}
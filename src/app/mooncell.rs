@@ -1,25 +1,147 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+
 use super::TopError;
 
 mod info;
-use info::{Info, DiskInfo};
+use info::Info;
+pub use info::DiskInfo;
 
 mod filemanage;
-use filemanage::{FileType, FileUnit, FileManage, FileOperation};
+use filemanage::{FileManage, FileOperation, SortMode};
+pub use filemanage::{FileType, FileUnit};
+
+mod filetype;
+use filetype::TypeTable;
+pub use filetype::TypeStyle;
+
+mod fileops;
+pub use fileops::{Conflict, ConflictPlan, Resolution};
+use fileops::OperateReport;
+
+mod jobs;
+use jobs::{JobKind, JobManager};
+pub use jobs::{JobSnapshot, JobState};
+
+mod journal;
+use journal::{Action, Journal};
+
+mod process;
+pub use process::FileHolder;
+
+mod inventory;
+use inventory::Inventory;
+
+mod trash;
+pub use trash::TrashItem;
+
+mod rename;
+pub use rename::{CaseMode, RenamePreview, RenameRule};
+
+mod create;
+pub use create::CreateKind;
+
+mod permission;
+pub use permission::{ModeChange, PermissionChange, PermissionPreview};
+
+mod filter;
+use filter::ListFilter;
+pub use filter::FilterMode;
+
+mod ignore;
 
+mod finder;
+use finder::Finder;
+pub use finder::FinderMatch;
+
+mod grep;
+use grep::Grep;
+pub use grep::{GrepOptions, GrepResult};
+
+mod usage;
+use usage::{UsageScan, UsageTree};
+pub use usage::{UsageEntry, UsageFlag, UsageProgress};
+
+mod places;
+use places::Places;
+
+mod pathinput;
+
+mod socket;
+use socket::SocketInfo;
+pub use socket::{SocketProtocol, SocketUnit};
 
 
 pub struct Mooncell {
     run: bool,
     info: Info,
+    socket: SocketInfo,
+    inventory: Option<Inventory>,
+    jobs: JobManager,
+    journal: Journal,
+    places: Places,    // 书签和访问过的文件夹
+    trash: Vec<TrashItem>,
+    users: HashMap<u32, String>,    // uid和用户名
+    groups: HashMap<u32, String>,    // gid和组名
+    finder: Option<Finder>,    // 打开查找窗口时在后台遍历
+    grep: Option<Grep>,    // 内容查找，关闭窗口前保留结果
+    usage_root: PathBuf,    // 统计的文件夹
+    usage_scan: Option<UsageScan>,    // 进行中的磁盘占用统计
+    usage_tree: Option<UsageTree>,    // 完成的统计结果
+    usage_node: usize,    // 正在查看的文件夹
+    usage_removing: Vec<(usize, usize, PathBuf)>,    // (job id, 节点, 路径)，job成功后从统计结果中去掉
+    file_types: Arc<TypeTable>,    // 文件类型的判断和颜色、图标，所有FileManage共用
+    file_manage: FileManage,    // 当前操作的一栏
+    fm_other: Option<FileManage>,    // 双栏时的另一栏，切换时和file_manage交换
+    fm_left_active: bool,    // 当前操作的是左边一栏
+    fm_tabs: Vec<FmTab>,    // 所有标签页，当前标签页的状态在上面三个字段里，这里的位置只是占位
+    fm_tab: usize,    // 当前标签页
+}
+
+// 一个标签页的文件管理状态
+struct FmTab {
     file_manage: FileManage,
+    other: Option<FileManage>,
+    left_active: bool,
+}
+
+impl FmTab {
+    fn new(file_types: &Arc<TypeTable>) -> Self {
+        Self { file_manage: FileManage::new(Arc::clone(file_types)), other: None, left_active: true }
+    }
 }
 
 impl Mooncell {
     pub fn new() -> Self {
+        let file_types = Arc::new(TypeTable::load());
         Self {
             run: true,
             info: Info::new(),
-            file_manage: FileManage::new(),
+            socket: SocketInfo::new(),
+            inventory: None,
+            jobs: JobManager::new(),
+            journal: Journal::load(),
+            places: Places::load(),
+            trash: Vec::new(),
+            users: permission::user_names(),
+            groups: permission::group_names(),
+            finder: None,
+            grep: None,
+            usage_root: PathBuf::new(),
+            usage_scan: None,
+            usage_tree: None,
+            usage_node: 0,
+            usage_removing: Vec::new(),
+            file_manage: FileManage::new(Arc::clone(&file_types)),
+            fm_other: None,
+            fm_left_active: true,
+            fm_tabs: vec![FmTab::new(&file_types)],
+            file_types,
+            fm_tab: 0,
         }
     }
 
@@ -31,10 +153,48 @@ impl Mooncell {
         self.info.refresh_cpu_data();
     }
 
+    pub fn disk_refresh(&mut self) {
+        self.info.refresh_disks();
+    }
+
+    /*
+     * @概述        重新收集硬件清单，只在打开清单界面时调用
+     */
+    pub fn inventory_refresh(&mut self) {
+        self.inventory = Some(Inventory::collect());
+    }
+
+    pub fn get_inventory_text(&self) -> String {
+        match &self.inventory {
+            Some(inventory) => inventory.to_text(),
+            None => String::from("no data"),
+        }
+    }
+
+    /*
+     * @概述        将硬件清单导出到文件管理当前所在的文件夹
+     * @返回值      Result<PathBuf, TopError>，导出的文件路径
+     */
+    pub fn export_inventory(&self) -> Result<PathBuf, TopError> {
+        let folder = self.file_manage.get_path_str().ok_or(TopError::OpenError)?;
+        let file_name = format!("mooncell-inventory-{}-{}.txt", self.info.host_name, chrono::Local::now().format("%Y%m%d-%H%M%S"));
+        let path = Path::new(folder).join(file_name);
+        let text = format!("{}@{}\nCPU: {}\n\n{}", self.info.os_name, self.info.host_name, self.info.cpu_info.name, self.get_inventory_text());
+        std::fs::write(&path, text).map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+        Ok(path)
+    }
+
+    pub fn socket_refresh(&mut self) {
+        self.socket.refresh();
+    }
+
 
 /**********************************************文件管理**********************************************/
     pub fn refresh_file_list(&mut self) {
         self.file_manage.refresh_file_list();
+        if let Some(other) = &mut self.fm_other {
+            other.refresh_file_list();
+        }
     }
 
     pub fn get_file_list(&self) -> Option<Vec<FileUnit>> {
@@ -46,7 +206,9 @@ impl Mooncell {
     }
 
     pub fn back_upper_layer(&mut self) {
-        self.file_manage.back_upper_layer();
+        if self.file_manage.back_upper_layer() {
+            self.places.visit(self.file_manage.get_now_path());
+        }
     }
 
     pub fn get_path_str(&mut self) -> Option<&str> {
@@ -58,8 +220,12 @@ impl Mooncell {
      * @返回值      Vec<String>
      */
     pub fn fm_create_name_list(&self) -> Vec<String> {
+        Self::name_list(&self.file_manage)
+    }
+
+    fn name_list(file_manage: &FileManage) -> Vec<String> {
         let mut return_vec: Vec<String> = Vec::new();
-        if let Some(file_list) = self.file_manage.get_file_list() {
+        if let Some(file_list) = file_manage.get_file_list() {
             for deal_unit in file_list.iter() {
                 match deal_unit.file_type {
                     FileType::Folder => {
@@ -77,10 +243,99 @@ impl Mooncell {
         }
     }
 
+    /*
+     * @概述        生成文件详细信息的每一行：大小、时间、权限、所有者、inode、链接、扩展属性
+     * @参数1       &FileUnit
+     * @返回值      Vec<String>
+     */
+    pub fn fm_metadata_lines(&self, file: &FileUnit) -> Vec<String> {
+        let time_to_string = |time: Option<SystemTime>| match time {
+            Some(time) => DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => String::from("-"),
+        };
+        let kind = match (&file.link_target, &file.file_type) {
+            (Some(_), _) => 'l',
+            (None, FileType::Folder) => 'd',
+            _ => '-',
+        };
+        let user = self.users.get(&file.uid).cloned().unwrap_or_else(|| file.uid.to_string());
+        let group = self.groups.get(&file.gid).cloned().unwrap_or_else(|| file.gid.to_string());
+
+        let mut lines = vec![
+            file.name.clone(),
+            Self::filetype_to_string(&file.file_type),
+            format!("size: {} ({} bytes)", Self::bytes_to_string(file.size), file.size),
+            format!("on disk: {}", Self::bytes_to_string(file.disk_usage)),
+            format!("mode: {} ({:04o})", permission::mode_to_string(file.mode, kind), file.mode),
+            format!("owner: {}:{}", user, group),
+            format!("modified: {}", time_to_string(file.modified)),
+            format!("accessed: {}", time_to_string(file.accessed)),
+            format!("changed:  {}", time_to_string(file.changed)),
+            format!("born:     {}", time_to_string(file.created)),
+            format!("inode: {}  links: {}", file.inode, file.nlink),
+            format!("device: {}:{}", libc::major(file.dev), libc::minor(file.dev)),
+        ];
+        if let Some(target) = &file.link_target {
+            lines.push(format!(
+                "-> {}{}",
+                target.to_string_lossy(),
+                if file.link_broken { " (broken)" } else { "" },
+            ));
+        }
+        for (name, value) in &file.xattrs {
+            lines.push(format!("{} = {}", name, value));
+        }
+        lines
+    }
+
+    /*
+     * @概述        切换当前文件夹的排序依据
+     */
+    pub fn fm_cycle_sort(&mut self) {
+        let sort = self.file_manage.get_sort();
+        self.file_manage.set_sort(SortMode { key: sort.key.next(), ..sort });
+    }
+
+    pub fn fm_reverse_sort(&mut self) {
+        let sort = self.file_manage.get_sort();
+        self.file_manage.set_sort(SortMode { reverse: !sort.reverse, ..sort });
+    }
+
+    pub fn fm_switch_dirs_first(&mut self) {
+        let sort = self.file_manage.get_sort();
+        self.file_manage.set_sort(SortMode { dirs_first: !sort.dirs_first, ..sort });
+    }
+
+    pub fn fm_switch_hidden(&mut self) {
+        self.file_manage.switch_hidden();
+    }
+
+    pub fn fm_is_show_hidden(&self) -> bool {
+        self.file_manage.is_show_hidden()
+    }
+
+    /*
+     * @概述        当前的排序和隐藏文件设置，显示在列表标题上
+     * @返回值      String，例如 name asc, dirs first, hidden shown
+     */
+    pub fn fm_sort_description(&self) -> String {
+        let sort = self.file_manage.get_sort();
+        let mut str = format!("{} {}", sort.key.to_str(), if sort.reverse { "desc" } else { "asc" });
+        if sort.dirs_first {
+            str.push_str(", dirs first");
+        }
+        if self.file_manage.is_show_hidden() {
+            str.push_str(", hidden shown");
+        }
+        str
+    }
+
     pub fn enter_folder(&mut self, file: &FileUnit) -> bool {
         match file.file_type {
             FileType::Folder => {
-                let _ = self.file_manage.enter_new_folder(file);
+                if self.file_manage.enter_new_folder(file) {
+                    self.places.visit(self.file_manage.get_now_path());
+                }
                 return true
             },
             _ => return false,
@@ -95,18 +350,146 @@ impl Mooncell {
         self.file_manage.select_ready_operation(FileOperation::Move);
     }
 
+    pub fn fm_trash_ready(&mut self) {
+        self.file_manage.select_ready_operation(FileOperation::Trash);
+    }
+
     pub fn fm_del_ready(&mut self) {
         self.file_manage.select_ready_operation(FileOperation::Delete);
     }
 
-    pub fn fm_perform_operations(&mut self) {
-        self.file_manage.select_operate();
+    /*
+     * @概述        找出粘贴到当前文件夹时已经存在的目标，删除操作没有冲突
+     * @返回值      Vec<Conflict>
+     */
+    pub fn fm_find_conflicts(&self) -> Vec<Conflict> {
+        match self.file_manage.get_operation_name() {
+            Some("copy") | Some("move") => {
+                let sources: Vec<PathBuf> = self.file_manage.get_select().iter().map(|file| file.path.clone()).collect();
+                fileops::find_conflicts(&sources, self.file_manage.get_operation_target())
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /*
+     * @概述        合并文件夹时里面的冲突
+     * @参数1       &Conflict
+     * @返回值      Vec<Conflict>
+     */
+    pub fn fm_merge_conflicts(&self, conflict: &Conflict) -> Vec<Conflict> {
+        fileops::merge_conflicts(conflict)
+    }
+
+    /*
+     * @概述        将准备好的文件操作交给后台执行
+     * @参数1       ConflictPlan，目标已经存在时的处理方式
+     * @返回值      Result<usize, TopError>，job id
+     */
+    pub fn fm_perform_operations(&mut self, plan: ConflictPlan) -> Result<usize, TopError> {
+        let target = self.file_manage.get_operation_target().to_path_buf();
+        let (operate, sources) = self.file_manage.select_operate()?;
+        let id = match operate {
+            FileOperation::Copy => self.jobs.push(JobKind::Copy, sources, Some(target), plan),
+            FileOperation::Move => self.jobs.push(JobKind::Move, sources, Some(target), plan),
+            FileOperation::Trash => self.jobs.push(JobKind::Trash, sources, None, plan),
+            FileOperation::Delete => self.jobs.push(JobKind::Delete, sources, None, plan),
+            FileOperation::Null => return Err(TopError::EmptyError),
+        };
+        Ok(id)
+    }
+
+    /*
+     * @概述        返回准备进行的文件操作的名称，没有时返回None
+     */
+    pub fn fm_get_operation_name(&self) -> Option<&'static str> {
+        self.file_manage.get_operation_name()
+    }
+
+    /*
+     * @概述        准备进行操作的文件数
+     */
+    pub fn fm_get_operation_count(&self) -> usize {
+        self.file_manage.get_operation_count()
+    }
+
+    pub fn fm_cancel_operation(&mut self) {
+        self.file_manage.cancel_operation();
     }
 
     pub fn clear_select(&mut self) {
         self.file_manage.clear_select();
     }
 
+    /*
+     * @概述        查找占用了文件/文件夹的进程
+     * @参数1       &Path
+     * @返回值      Vec<FileHolder>
+     */
+    pub fn find_holders(&self, path: &Path) -> Vec<FileHolder> {
+        process::find_holders(path)
+    }
+
+    pub fn send_signal(&self, pid: u32, signal: &str) -> Result<(), TopError> {
+        process::send_signal(pid, signal)
+    }
+
+    pub fn get_process_cwd(&self, pid: u32) -> Option<PathBuf> {
+        process::process_cwd(pid)
+    }
+
+    pub fn get_process_exe(&self, pid: u32) -> Option<PathBuf> {
+        process::process_exe(pid)
+    }
+
+    /*
+     * @概述        文件管理跳转到指定的文件夹
+     * @参数1       &Path
+     * @返回值      bool
+     */
+    pub fn fm_set_path(&mut self, path: &Path) -> bool {
+        if !self.file_manage.set_path(path) {
+            return false;
+        }
+        self.places.visit(path);
+        true
+    }
+
+    /*
+     * @概述        文件类型的颜色和图标，可以在配置文件中修改
+     * @参数1       FileType
+     * @返回值      TypeStyle
+     */
+    pub fn fm_type_style(&self, file_type: FileType) -> TypeStyle {
+        self.file_types.get_style(file_type)
+    }
+
+    /*
+     * @概述        展开路径栏输入的路径，相对路径相对于当前文件夹
+     * @参数1       &str
+     * @返回值      Result<PathBuf, TopError>，环境变量不存在时返回Err，不检查路径是否存在
+     */
+    pub fn fm_expand_path(&self, input: &str) -> Result<PathBuf, TopError> {
+        pathinput::expand(input, self.file_manage.get_now_path())
+    }
+
+    /*
+     * @概述        补全路径栏输入的文件夹名
+     * @参数1       &str
+     * @返回值      (String, Vec<String>)，(补全后的输入, 所有候选的名字)
+     */
+    pub fn fm_complete_path(&self, input: &str) -> (String, Vec<String>) {
+        pathinput::complete(input, self.file_manage.get_now_path())
+    }
+
+    /*
+     * @概述        返回选中的文件
+     * @返回值      Vec<FileUnit>
+     */
+    pub fn fm_get_select(&self) -> Vec<FileUnit> {
+        self.file_manage.get_select()
+    }
+
     pub fn create_select_str(&self) -> String {
         let mut str = String::new();
         for file in self.file_manage.get_select() {
@@ -114,6 +497,771 @@ impl Mooncell {
         }
         str
     }
+/**********************************************双栏**********************************************/
+    /*
+     * @概述        打开或关闭双栏，打开时另一栏从当前文件夹开始，关闭时保留当前操作的一栏
+     */
+    pub fn fm_switch_dual(&mut self) {
+        if self.fm_other.take().is_none() {
+            let mut other = FileManage::new(Arc::clone(&self.file_types));
+            other.set_path(self.file_manage.get_now_path());
+            other.refresh_file_list();
+            self.fm_other = Some(other);
+        }
+        self.fm_left_active = true;
+    }
+
+    pub fn fm_is_dual(&self) -> bool {
+        self.fm_other.is_some()
+    }
+
+    /*
+     * @概述        切换到另一栏，两栏的文件夹、选中的文件和过滤互不影响
+     * @返回值      bool，不是双栏时返回false
+     */
+    pub fn fm_switch_pane(&mut self) -> bool {
+        match &mut self.fm_other {
+            Some(other) => {
+                std::mem::swap(&mut self.file_manage, other);
+                self.fm_left_active = !self.fm_left_active;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn fm_is_left_active(&self) -> bool {
+        self.fm_left_active
+    }
+
+    pub fn fm_other_path_str(&self) -> Option<&str> {
+        self.fm_other.as_ref()?.get_path_str()
+    }
+
+    pub fn fm_other_name_list(&self) -> Vec<String> {
+        self.fm_other.as_ref().map(Self::name_list).unwrap_or_default()
+    }
+
+    /*
+     * @概述        准备好的复制/移动改为粘贴到另一栏的文件夹
+     * @返回值      bool，不是双栏时返回false
+     */
+    pub fn fm_target_other(&mut self) -> bool {
+        match &self.fm_other {
+            Some(other) => {
+                self.file_manage.set_operation_target(Some(other.get_now_path().to_path_buf()));
+                true
+            }
+            None => false,
+        }
+    }
+
+/**********************************************标签页**********************************************/
+    /*
+     * @概述        在当前标签页后面打开新的标签页，从当前文件夹开始
+     */
+    pub fn fm_tab_new(&mut self) {
+        let mut tab = FmTab::new(&self.file_types);
+        tab.file_manage.set_path(self.file_manage.get_now_path());
+        tab.file_manage.refresh_file_list();
+        self.swap_tab();
+        self.fm_tab += 1;
+        self.fm_tabs.insert(self.fm_tab, tab);
+        self.swap_tab();
+    }
+
+    /*
+     * @概述        关闭当前标签页，切换到后面一个
+     * @返回值      bool，只剩一个标签页时不关闭，返回false
+     */
+    pub fn fm_tab_close(&mut self) -> bool {
+        if self.fm_tabs.len() <= 1 {
+            return false;
+        }
+        self.fm_tabs.remove(self.fm_tab);
+        self.fm_tab = self.fm_tab.min(self.fm_tabs.len() - 1);
+        self.swap_tab();
+        true
+    }
+
+    /*
+     * @概述        切换到指定的标签页
+     * @参数1       usize，超出范围时不切换
+     */
+    pub fn fm_tab_select(&mut self, index: usize) {
+        if index >= self.fm_tabs.len() || index == self.fm_tab {
+            return;
+        }
+        self.swap_tab();
+        self.fm_tab = index;
+        self.swap_tab();
+    }
+
+    pub fn fm_tab_index(&self) -> usize {
+        self.fm_tab
+    }
+
+    pub fn fm_tab_count(&self) -> usize {
+        self.fm_tabs.len()
+    }
+
+    /*
+     * @概述        每个标签页的名字，为当前操作的文件夹名
+     * @返回值      Vec<String>
+     */
+    pub fn fm_tab_names(&self) -> Vec<String> {
+        (0..self.fm_tabs.len())
+            .map(|i| {
+                let path = if i == self.fm_tab { self.file_manage.get_now_path() } else { self.fm_tabs[i].file_manage.get_now_path() };
+                match path.file_name() {
+                    Some(name) => name.to_string_lossy().to_string(),
+                    None => path.to_string_lossy().to_string(),
+                }
+            })
+            .collect()
+    }
+
+    /*
+     * @概述        交换当前标签页的状态和fm_tabs里的位置
+     */
+    fn swap_tab(&mut self) {
+        let tab = &mut self.fm_tabs[self.fm_tab];
+        std::mem::swap(&mut self.file_manage, &mut tab.file_manage);
+        std::mem::swap(&mut self.fm_other, &mut tab.other);
+        std::mem::swap(&mut self.fm_left_active, &mut tab.left_active);
+    }
+
+/**********************************************书签**********************************************/
+    /*
+     * @概述        把当前文件夹设置为书签
+     * @参数1       char，书签对应的字符
+     */
+    pub fn fm_bookmark_set(&mut self, key: char) {
+        self.places.set_bookmark(key, self.file_manage.get_now_path());
+    }
+
+    pub fn fm_bookmark_get(&self, key: char) -> Option<PathBuf> {
+        self.places.get_bookmark(key).map(Path::to_path_buf)
+    }
+
+    pub fn fm_bookmark_remove(&mut self, key: char) {
+        self.places.remove_bookmark(key);
+    }
+
+    pub fn fm_bookmarks(&self) -> Vec<(char, PathBuf)> {
+        self.places.get_bookmarks()
+    }
+
+    /*
+     * @概述        访问过的文件夹，按frecency排序
+     * @参数1       &str，空格分隔的关键字
+     * @参数2       usize，最多返回的数量
+     * @返回值      Vec<PathBuf>
+     */
+    pub fn fm_recent_dirs(&self, query: &str, limit: usize) -> Vec<PathBuf> {
+        self.places.recent(query, limit)
+    }
+
+    pub fn fm_forget_dir(&mut self, path: &Path) {
+        self.places.forget(path);
+    }
+
+/**********************************************后台任务**********************************************/
+    /*
+     * @概述        检查后台任务，结束的任务记录到操作日志
+     * @返回值      Vec<JobSnapshot>，本次新结束的任务
+     */
+    pub fn jobs_poll(&mut self) -> Vec<JobSnapshot> {
+        let mut snapshots = Vec::new();
+        for finished in self.jobs.poll() {
+            self.journal.finish(finished.snapshot.id, finished.snapshot.kind, &finished.sources, &finished.report);
+            self.usage_finish_remove(finished.snapshot.id, &finished.report);
+            snapshots.push(finished.snapshot);
+        }
+        snapshots
+    }
+
+    pub fn get_jobs(&self) -> Vec<JobSnapshot> {
+        self.jobs.snapshots()
+    }
+
+    pub fn jobs_running_count(&self) -> usize {
+        self.jobs.running_count()
+    }
+
+    pub fn job_switch_pause(&mut self, id: usize) {
+        self.jobs.switch_pause(id);
+    }
+
+    pub fn job_cancel(&mut self, id: usize) {
+        self.jobs.cancel(id);
+    }
+
+    pub fn jobs_clear_history(&mut self) {
+        self.jobs.clear_history();
+    }
+
+/**********************************************重命名**********************************************/
+    /*
+     * @概述        重命名一个文件，新名字不能和已有的文件冲突
+     * @参数1       &Path，需要重命名的文件
+     * @参数2       &str，新名字
+     * @返回值      Result<PathBuf, TopError>，新路径
+     */
+    pub fn fm_rename(&mut self, path: &Path, new_name: &str) -> Result<PathBuf, TopError> {
+        let mut previews = vec![RenamePreview { from: path.to_path_buf(), to: path.with_file_name(new_name), problem: None }];
+        rename::check_problems(&mut previews);
+        self.fm_rename_apply(&previews)?;
+        Ok(previews.remove(0).to)
+    }
+
+    /*
+     * @概述        按照规则生成批量重命名的预览
+     * @参数1       &[PathBuf]，需要重命名的文件
+     * @参数2       &RenameRule
+     * @返回值      Result<Vec<RenamePreview>, TopError>
+     */
+    pub fn fm_rename_preview(&self, paths: &[PathBuf], rule: &RenameRule) -> Result<Vec<RenamePreview>, TopError> {
+        rename::preview(paths, rule)
+    }
+
+    /*
+     * @概述        执行预览中的重命名并记录到操作日志，有冲突时不执行
+     * @参数1       &[RenamePreview]
+     * @返回值      Result<usize, TopError>，重命名的文件数
+     */
+    pub fn fm_rename_apply(&mut self, previews: &[RenamePreview]) -> Result<usize, TopError> {
+        if let Some(preview) = previews.iter().find(|preview| preview.problem.is_some()) {
+            return Err(TopError::ErrorInformation(format!(
+                "{}: {}",
+                preview.to.to_string_lossy(),
+                preview.problem.clone().unwrap_or_default(),
+            )));
+        }
+        let pairs: Vec<(PathBuf, PathBuf)> = previews
+            .iter()
+            .filter(|preview| preview.is_changed())
+            .map(|preview| (preview.from.clone(), preview.to.clone()))
+            .collect();
+        if pairs.is_empty() {
+            return Ok(0);
+        }
+
+        let report = fileops::rename_pairs(&pairs);
+        if !report.pairs.is_empty() {
+            self.journal.record(Action::Rename { pairs: report.pairs.clone() });
+        }
+        match report.errors.first() {
+            Some((path, e)) => Err(TopError::ErrorInformation(format!(
+                "{} renamed, {} failed, {}: {}",
+                report.done,
+                report.errors.len(),
+                path.to_string_lossy(),
+                e,
+            ))),
+            None => Ok(report.done),
+        }
+    }
+
+    /*
+     * @概述        把文件名列表写进只有自己可以读写的临时文件，交给编辑器修改
+     * @参数1       &[PathBuf]
+     * @返回值      Result<PathBuf, TopError>，临时文件的路径
+     */
+    pub fn fm_editor_file(&self, paths: &[PathBuf]) -> Result<PathBuf, TopError> {
+        rename::write_editor_file(paths)
+            .map_err(|e| TopError::ErrorInformation(format!("can`t create temporary file: {}", e)))
+    }
+
+    /*
+     * @概述        读取编辑器修改后的文件名列表，生成重命名预览
+     * @参数1       &str，修改后的内容
+     * @参数2       &[PathBuf]，fm_editor_text使用的文件
+     * @返回值      Result<Vec<RenamePreview>, TopError>
+     */
+    pub fn fm_editor_previews(&self, text: &str, paths: &[PathBuf]) -> Result<Vec<RenamePreview>, TopError> {
+        rename::from_editor_text(text, paths)
+    }
+
+/**********************************************新建**********************************************/
+    /*
+     * @概述        在当前文件夹新建文件、文件夹或链接，连同自动创建的父文件夹一起记录到操作日志
+     * @参数1       &CreateKind
+     * @参数2       &str，名字，可以包含/
+     * @返回值      Result<PathBuf, TopError>，新建的路径
+     */
+    pub fn fm_create(&mut self, kind: &CreateKind, name: &str) -> Result<PathBuf, TopError> {
+        let paths = create::create(kind, self.file_manage.get_now_path(), name)?;
+        let path = paths.last().cloned().unwrap_or_default();
+        self.journal.record(Action::Create { paths, kind: kind.clone() });
+        Ok(path)
+    }
+
+    /*
+     * @概述        可以用来新建文件的模板
+     * @返回值      Vec<PathBuf>
+     */
+    pub fn fm_templates(&self) -> Vec<PathBuf> {
+        create::list_templates()
+    }
+
+/**********************************************权限**********************************************/
+    /*
+     * @概述        生成修改权限和所有者的预览
+     * @参数1       &[PathBuf]，选中的文件
+     * @参数2       &PermissionChange
+     * @返回值      Vec<PermissionPreview>
+     */
+    pub fn fm_permission_preview(&self, paths: &[PathBuf], change: &PermissionChange) -> Vec<PermissionPreview> {
+        permission::preview(paths, change)
+    }
+
+    /*
+     * @概述        执行预览中的修改，成功修改的条目记录到操作日志
+     * @参数1       &[PermissionPreview]
+     * @返回值      (usize, Vec<(PathBuf, String)>)，(成功的数量, 失败的文件和原因)
+     */
+    pub fn fm_permission_apply(&mut self, previews: &[PermissionPreview]) -> (usize, Vec<(PathBuf, String)>) {
+        let (done, errors) = permission::apply(previews);
+        let applied: Vec<PermissionPreview> = previews
+            .iter()
+            .filter(|preview| preview.is_changed() && !errors.iter().any(|(path, _)| *path == preview.path))
+            .cloned()
+            .collect();
+        if !applied.is_empty() {
+            self.journal.record(Action::Permission { previews: applied });
+        }
+        (done, errors)
+    }
+
+    pub fn get_user_names(&self) -> HashMap<u32, String> {
+        self.users.clone()
+    }
+
+    pub fn get_group_names(&self) -> HashMap<u32, String> {
+        self.groups.clone()
+    }
+
+    pub fn parse_user(&self, user: &str) -> Result<u32, TopError> {
+        permission::parse_user(user)
+    }
+
+    pub fn parse_group(&self, group: &str) -> Result<u32, TopError> {
+        permission::parse_group(group)
+    }
+
+    /*
+     * @概述        把权限转换为ls -l的格式
+     * @参数1       u32，权限
+     * @参数2       char，类型，d、l、-
+     * @返回值      String
+     */
+    pub fn mode_to_string(mode: u32, kind: char) -> String {
+        permission::mode_to_string(mode, kind)
+    }
+
+/**********************************************过滤**********************************************/
+    /*
+     * @概述        设置输入时的过滤，离开当前文件夹时自动清除
+     * @参数1       &str，输入的内容，为空时清除过滤
+     * @参数2       FilterMode
+     * @返回值      Result<(), TopError>，正则表达式错误时返回Err，保留原来的过滤
+     */
+    pub fn fm_set_filter(&mut self, pattern: &str, mode: FilterMode) -> Result<(), TopError> {
+        let filter = Self::build_filter(pattern, mode)?;
+        self.file_manage.set_filter(filter);
+        Ok(())
+    }
+
+    /*
+     * @概述        设置一直生效的过滤，例如只显示*.rs，文件夹不受影响
+     * @参数1       &str，为空时清除过滤
+     * @参数2       FilterMode
+     * @返回值      Result<(), TopError>
+     */
+    pub fn fm_set_persistent_filter(&mut self, pattern: &str, mode: FilterMode) -> Result<(), TopError> {
+        let filter = Self::build_filter(pattern, mode)?;
+        self.file_manage.set_persistent_filter(filter);
+        Ok(())
+    }
+
+    pub fn fm_clear_filter(&mut self) {
+        self.file_manage.set_filter(None);
+    }
+
+    pub fn fm_clear_persistent_filter(&mut self) {
+        self.file_manage.set_persistent_filter(None);
+    }
+
+    pub fn fm_has_filter(&self) -> bool {
+        self.file_manage.get_filter().is_some()
+    }
+
+    /*
+     * @概述        当前的一直生效的过滤，用于再次编辑
+     * @返回值      Option<(String, FilterMode)>
+     */
+    pub fn fm_get_persistent_filter(&self) -> Option<(String, FilterMode)> {
+        self.file_manage
+            .get_persistent_filter()
+            .map(|filter| (filter.get_pattern().to_string(), filter.get_mode()))
+    }
+
+    /*
+     * @概述        生效中的过滤，显示在列表标题上
+     * @返回值      String，没有过滤时为空
+     */
+    pub fn fm_filter_description(&self) -> String {
+        let mut str = String::new();
+        if let Some(filter) = self.file_manage.get_persistent_filter() {
+            str.push_str(&format!(" only: {} ", filter.describe()));
+        }
+        if let Some(filter) = self.file_manage.get_filter() {
+            str.push_str(&format!(" filter: {} ", filter.describe()));
+        }
+        str
+    }
+
+    /*
+     * @概述        名字中匹配过滤的字符位置，优先使用输入时的过滤
+     * @参数1       &str，文件名
+     * @返回值      Vec<usize>，字符的下标
+     */
+    pub fn fm_match_positions(&self, name: &str) -> Vec<usize> {
+        self.file_manage
+            .get_filter()
+            .or(self.file_manage.get_persistent_filter())
+            .and_then(|filter| filter.matches(name))
+            .unwrap_or_default()
+    }
+
+    fn build_filter(pattern: &str, mode: FilterMode) -> Result<Option<ListFilter>, TopError> {
+        if pattern.is_empty() {
+            return Ok(None);
+        }
+        ListFilter::new(pattern, mode).map(Some)
+    }
+
+/**********************************************查找**********************************************/
+    /*
+     * @概述        从当前文件夹开始在后台遍历，替换之前的查找
+     * @返回值      PathBuf，开始查找的文件夹
+     */
+    pub fn fm_finder_start(&mut self) -> PathBuf {
+        let root = self.file_manage.get_now_path().to_path_buf();
+        self.finder = Some(Finder::start(&root, self.file_manage.is_show_hidden()));
+        root
+    }
+
+    /*
+     * @概述        停止遍历并释放找到的条目
+     */
+    pub fn fm_finder_stop(&mut self) {
+        self.finder = None;
+    }
+
+    /*
+     * @返回值      (usize, bool)，(已经找到的条目数, 是否遍历完成)
+     */
+    pub fn fm_finder_status(&self) -> (usize, bool) {
+        match &self.finder {
+            Some(finder) => finder.get_status(),
+            None => (0, true),
+        }
+    }
+
+    /*
+     * @概述        模糊匹配已经找到的条目
+     * @参数1       &str，输入的内容
+     * @参数2       usize，最多返回的数量
+     * @返回值      Vec<FinderMatch>，按分数从高到低
+     */
+    pub fn fm_finder_search(&self, query: &str, limit: usize) -> Vec<FinderMatch> {
+        match &self.finder {
+            Some(finder) => finder.search(query, limit),
+            None => Vec::new(),
+        }
+    }
+
+    /*
+     * @概述        匹配结果的完整路径
+     * @参数1       &FinderMatch
+     * @返回值      Option<PathBuf>，没有进行中的查找时返回None
+     */
+    pub fn fm_finder_path(&self, found: &FinderMatch) -> Option<PathBuf> {
+        self.finder.as_ref().map(|finder| finder.get_root().join(&found.path))
+    }
+
+/**********************************************内容查找**********************************************/
+    /*
+     * @概述        在当前文件夹下查找文件内容，替换之前的查找
+     * @参数1       GrepOptions，show_hidden使用文件列表的设置
+     * @返回值      Result<PathBuf, TopError>，开始查找的文件夹
+     */
+    pub fn fm_grep_start(&mut self, mut options: GrepOptions) -> Result<PathBuf, TopError> {
+        let root = self.file_manage.get_now_path().to_path_buf();
+        options.show_hidden = self.file_manage.is_show_hidden();
+        self.grep = Some(Grep::start(&root, options)?);
+        Ok(root)
+    }
+
+    /*
+     * @概述        停止查找，保留已经找到的结果
+     */
+    pub fn fm_grep_cancel(&mut self) {
+        if let Some(grep) = &self.grep {
+            grep.cancel();
+        }
+    }
+
+    /*
+     * @概述        停止查找并丢弃结果
+     */
+    pub fn fm_grep_close(&mut self) {
+        self.grep = None;
+    }
+
+    /*
+     * @返回值      (usize, usize, bool)，(查找过的文件数, 结果数, 是否结束)
+     */
+    pub fn fm_grep_status(&self) -> (usize, usize, bool) {
+        match &self.grep {
+            Some(grep) => grep.get_status(),
+            None => (0, 0, true),
+        }
+    }
+
+    /*
+     * @概述        取出新的结果
+     * @参数1       usize，已经取过的数量
+     * @返回值      Vec<GrepResult>
+     */
+    pub fn fm_grep_results(&self, from: usize) -> Vec<GrepResult> {
+        match &self.grep {
+            Some(grep) => grep.get_results(from),
+            None => Vec::new(),
+        }
+    }
+
+    /*
+     * @概述        结果相对于开始查找的文件夹的路径，用于显示
+     * @参数1       &Path
+     * @返回值      String
+     */
+    pub fn fm_grep_relative(&self, path: &Path) -> String {
+        match &self.grep {
+            Some(grep) => path.strip_prefix(grep.get_root()).unwrap_or(path).to_string_lossy().to_string(),
+            None => path.to_string_lossy().to_string(),
+        }
+    }
+
+/**********************************************磁盘占用**********************************************/
+    /*
+     * @概述        在后台统计文件管理当前文件夹的磁盘占用，替换之前的结果
+     * @参数1       bool，是否进入其他文件系统
+     */
+    pub fn usage_start(&mut self, cross_fs: bool) {
+        self.usage_root = self.file_manage.get_now_path().to_path_buf();
+        self.usage_rescan(cross_fs);
+    }
+
+    /*
+     * @概述        重新统计上一次统计的文件夹
+     * @参数1       bool，是否进入其他文件系统
+     */
+    pub fn usage_rescan(&mut self, cross_fs: bool) {
+        self.usage_tree = None;
+        self.usage_node = 0;
+        self.usage_removing.clear();
+        self.usage_scan = Some(UsageScan::start(&self.usage_root, cross_fs, Arc::clone(&self.file_types)));
+    }
+
+    pub fn usage_get_root(&self) -> &Path {
+        &self.usage_root
+    }
+
+    /*
+     * @概述        检查统计是否完成，完成后显示结果
+     */
+    pub fn usage_poll(&mut self) {
+        if let Some(tree) = self.usage_scan.as_ref().and_then(|scan| scan.take_result()) {
+            self.usage_scan = None;
+            self.usage_tree = Some(tree);
+            self.usage_node = 0;
+        }
+    }
+
+    /*
+     * @返回值      Option<UsageProgress>，没有进行中的统计时返回None
+     */
+    pub fn usage_progress(&self) -> Option<UsageProgress> {
+        self.usage_scan.as_ref()?.get_progress()
+    }
+
+    /*
+     * @概述        正在查看的文件夹
+     * @返回值      Option<(PathBuf, UsageEntry)>，还没有结果时返回None
+     */
+    pub fn usage_current(&self) -> Option<(PathBuf, UsageEntry)> {
+        let tree = self.usage_tree.as_ref()?;
+        Some((tree.get_path(self.usage_node), tree.get_entry(self.usage_node)?))
+    }
+
+    /*
+     * @概述        正在查看的文件夹的子条目
+     * @返回值      Vec<UsageEntry>，按占用空间从大到小
+     */
+    pub fn usage_entries(&self) -> Vec<UsageEntry> {
+        match &self.usage_tree {
+            Some(tree) => tree.get_children(self.usage_node),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn usage_path(&self, id: usize) -> Option<PathBuf> {
+        self.usage_tree.as_ref().map(|tree| tree.get_path(id))
+    }
+
+    /*
+     * @概述        进入子文件夹
+     * @参数1       &UsageEntry
+     * @返回值      bool，不是文件夹时返回false
+     */
+    pub fn usage_enter(&mut self, entry: &UsageEntry) -> bool {
+        if !entry.is_dir || self.usage_tree.is_none() {
+            return false;
+        }
+        self.usage_node = entry.id;
+        true
+    }
+
+    /*
+     * @概述        回到上一层
+     * @返回值      Option<usize>，离开的文件夹，已经在开始统计的文件夹时返回None
+     */
+    pub fn usage_back(&mut self) -> Option<usize> {
+        let parent = self.usage_tree.as_ref()?.get_parent(self.usage_node)?;
+        let left = self.usage_node;
+        self.usage_node = parent;
+        Some(left)
+    }
+
+    /*
+     * @概述        在后台移到回收站或者永久删除，job没有错误地结束后才从统计结果中去掉这个条目
+     * @参数1       &UsageEntry
+     * @参数2       bool，true为永久删除
+     * @返回值      Result<usize, TopError>，job id
+     */
+    pub fn usage_remove(&mut self, entry: &UsageEntry, permanent: bool) -> Result<usize, TopError> {
+        let tree = self.usage_tree.as_mut().ok_or(TopError::EmptyError)?;
+        if tree.get_parent(entry.id).is_none() {
+            return Err(TopError::ErrorInformation(String::from("can`t remove the scanned folder itself")));
+        }
+        let path = tree.get_path(entry.id);
+        let kind = if permanent { JobKind::Delete } else { JobKind::Trash };
+        let id = self.jobs.push(kind, vec![path.clone()], None, ConflictPlan::new());
+        self.usage_removing.push((id, entry.id, path));
+        Ok(id)
+    }
+
+    /*
+     * @概述        usage_remove的job结束，成功时从统计结果中去掉条目
+     *              统计结果可能已经改变，路径不同时不处理
+     * @参数1       usize，job id
+     * @参数2       &OperateReport
+     */
+    fn usage_finish_remove(&mut self, job_id: usize, report: &OperateReport) {
+        let pos = match self.usage_removing.iter().position(|(id, _, _)| *id == job_id) {
+            Some(pos) => pos,
+            None => return,
+        };
+        let (_, node, path) = self.usage_removing.remove(pos);
+        if !report.errors.is_empty() || report.cancelled {
+            return;
+        }
+        if let Some(tree) = self.usage_tree.as_mut()
+            && tree.get_entry(node).is_some()
+            && tree.get_path(node) == path {
+            tree.remove(node);
+        }
+    }
+
+/**********************************************撤销/重做**********************************************/
+    /*
+     * @概述        撤销最近一次文件操作，在后台执行
+     * @返回值      Result<String, TopError>，提示信息
+     */
+    pub fn fm_undo(&mut self) -> Result<String, TopError> {
+        let (entry, paths, task) = self.journal.start_undo()?;
+        let message = format!("undo {} from {}", entry.action.describe(), entry.time.format("%m-%d %H:%M"));
+        let id = self.jobs.push_task(JobKind::Undo, paths, None, task);
+        self.journal.set_running(id, entry, true);
+        Ok(format!("job #{} {}", id, message))
+    }
+
+    /*
+     * @概述        重做最近一次撤销的操作，在后台执行
+     * @返回值      Result<String, TopError>，提示信息
+     */
+    pub fn fm_redo(&mut self) -> Result<String, TopError> {
+        let (entry, paths, task) = self.journal.start_redo()?;
+        let message = format!("redo {}", entry.action.describe());
+        let id = self.jobs.push_task(JobKind::Redo, paths, None, task);
+        self.journal.set_running(id, entry, false);
+        Ok(format!("job #{} {}", id, message))
+    }
+
+    /*
+     * @概述        最近一次可以撤销的操作的描述
+     */
+    pub fn fm_last_undo(&self) -> Option<String> {
+        self.journal.last_undo().map(|entry| entry.action.describe())
+    }
+
+/**********************************************回收站**********************************************/
+    /*
+     * @概述        重新读取回收站，只在打开回收站界面和操作之后调用
+     */
+    pub fn trash_refresh(&mut self) {
+        self.trash = trash::list_items();
+    }
+
+    pub fn get_trash_items(&self) -> &[TrashItem] {
+        &self.trash
+    }
+
+    /*
+     * @概述        还原到原来的位置
+     * @参数1       &TrashItem
+     * @返回值      Result<(), TopError>
+     */
+    pub fn trash_restore(&mut self, item: &TrashItem) -> Result<(), TopError> {
+        let result = trash::restore(item);
+        self.trash_refresh();
+        result
+    }
+
+    /*
+     * @概述        永久删除回收站中的一个条目
+     * @参数1       &TrashItem
+     * @返回值      Result<(), TopError>
+     */
+    pub fn trash_remove(&mut self, item: &TrashItem) -> Result<(), TopError> {
+        let result = trash::remove(item);
+        self.trash_refresh();
+        result
+    }
+
+    /*
+     * @概述        清空回收站
+     * @返回值      (usize, Vec<String>)，(删除的条目数, 错误信息)
+     */
+    pub fn trash_empty(&mut self) -> (usize, Vec<String>) {
+        let result = trash::empty();
+        self.trash_refresh();
+        result
+    }
+
 /**********************************************获取数据**********************************************/
     /*********other*********/
     pub fn is_run(&self) -> bool {
@@ -159,8 +1307,8 @@ impl Mooncell {
         return self.info.cpu_info.power.clone();
     }
 
-    pub fn get_cpus(&self) -> u8 {
-        return self.info.cpu_info.siblings.clone();
+    pub fn get_cpus(&self) -> usize {
+        self.info.cpu_info.siblings
     }
 
     pub fn get_cpu_usage(&self) -> Vec<f32> {
@@ -184,6 +1332,11 @@ impl Mooncell {
         return self.info.mem_info.usage_history.clone();
     }
 
+    /*********socket*********/
+    pub fn get_sockets(&self) -> &[SocketUnit] {
+        &self.socket.list
+    }
+
     /*********disk*********/
     pub fn get_disks(&self) -> Vec<DiskInfo> {
         return self.info.disks.clone();
@@ -196,15 +1349,20 @@ impl Mooncell {
     */
     pub fn filetype_to_string(file_type: &FileType) -> String {
         match file_type {
-            FileType::Zip => return String::from("Zip File"),
-            FileType::Normal => return String::from("File"),
-            FileType::Folder => return String::from("Folder"),
-            FileType::Video => return String::from("Video File"),
-            FileType::Audio => return String::from("Audio File"),
-            FileType::Image => return String::from("Image File"),
-            FileType::Code => return String::from("Code source"),
-            FileType::Markdown => return String::from("Markdown"),
-            FileType::Document => return String::from("Document File"),
+            FileType::Zip => String::from("Zip File"),
+            FileType::Normal => String::from("File"),
+            FileType::Folder => String::from("Folder"),
+            FileType::Video => String::from("Video File"),
+            FileType::Audio => String::from("Audio File"),
+            FileType::Image => String::from("Image File"),
+            FileType::Code => String::from("Code source"),
+            FileType::Markdown => String::from("Markdown"),
+            FileType::Document => String::from("Document File"),
+            FileType::Executable => String::from("Executable"),
+            FileType::Symlink => String::from("Symbolic Link"),
+            FileType::Device => String::from("Device"),
+            FileType::Socket => String::from("Socket"),
+            FileType::Fifo => String::from("FIFO"),
         }
     }
     
@@ -219,9 +1377,12 @@ impl Mooncell {
     }
     
     /*
-    * @概述        退出
+    * @概述        退出，取消的Job已经完成的部分记录到操作日志，下次启动后仍然可以撤销
     */
     pub fn exit(&mut self) {
+        for finished in self.jobs.cancel_all() {
+            self.journal.finish(finished.snapshot.id, finished.snapshot.kind, &finished.sources, &finished.report);
+        }
         self.run = false;
     }
 
@@ -230,7 +1391,7 @@ impl Mooncell {
      * @参数1       Vec<f32>
      */
     pub fn deal_cpu_usage(core_usage_data: Vec<f32>) -> String {
-        let mut siblings:u8 = 0;
+        let mut siblings: usize = 0;
         let mut str = String::new();
         if core_usage_data.is_empty() {
             return String::from("data is empty");
@@ -289,6 +1450,26 @@ impl Mooncell {
         return str;
     }
 
+    /*
+     * @概述        将字节数转换成带单位的String
+     * @参数1       u64
+     * @返回值      String，如 1.5MB
+     */
+    pub fn bytes_to_string(bytes: u64) -> String {
+        let units = ["B", "KB", "MB", "GB", "TB"];
+        let mut value = bytes as f64;
+        let mut unit = 0;
+        while value >= 1024.0 && unit < units.len() - 1 {
+            value /= 1024.0;
+            unit += 1;
+        }
+        if unit == 0 {
+            format!("{}{}", bytes, units[0])
+        } else {
+            format!("{:.1}{}", value, units[unit])
+        }
+    }
+
     /*
      * @概述        将f32仅保留后两位小数转换成String
      * @返回值      String
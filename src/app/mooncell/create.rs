@@ -0,0 +1,154 @@
+use std::env;
+use std::fs;
+use std::os::unix::fs::symlink;
+use std::path::{Component, Path, PathBuf};
+
+use super::TopError;
+
+/*
+ * @概述        在文件夹里新建文件、文件夹、链接，或者从模板新建文件
+ *              名字可以包含/，中间缺少的文件夹会自动创建，和mkdir -p一样
+ *              模板是$XDG_CONFIG_HOME/mooncell/templates里的文件
+ */
+
+#[derive(Clone)]
+pub enum CreateKind {
+    File,
+    Folder,
+    Symlink(PathBuf),    // 指向的路径
+    Hardlink(PathBuf),    // 链接的文件
+    Template(PathBuf),    // 模板文件
+}
+
+impl CreateKind {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            CreateKind::File => "file",
+            CreateKind::Folder => "folder",
+            CreateKind::Symlink(_) => "symlink",
+            CreateKind::Hardlink(_) => "hardlink",
+            CreateKind::Template(_) => "template",
+        }
+    }
+
+    /*
+     * @概述        to_str的反向转换，用于读取操作日志
+     * @参数1       &str，to_str的结果
+     * @参数2       PathBuf，链接指向的路径或者模板，其他类型忽略
+     * @返回值      Option<CreateKind>
+     */
+    pub fn from_str(kind: &str, target: PathBuf) -> Option<CreateKind> {
+        match kind {
+            "file" => Some(CreateKind::File),
+            "folder" => Some(CreateKind::Folder),
+            "symlink" => Some(CreateKind::Symlink(target)),
+            "hardlink" => Some(CreateKind::Hardlink(target)),
+            "template" => Some(CreateKind::Template(target)),
+            _ => None,
+        }
+    }
+
+    pub fn get_target(&self) -> Option<&Path> {
+        match self {
+            CreateKind::File | CreateKind::Folder => None,
+            CreateKind::Symlink(target) | CreateKind::Hardlink(target) | CreateKind::Template(target) => Some(target),
+        }
+    }
+}
+
+/*
+ * @概述        保存配置的文件夹，$XDG_CONFIG_HOME/mooncell，默认~/.config/mooncell
+ * @返回值      Option<PathBuf>，没有$HOME时返回None
+ */
+pub fn config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("mooncell")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/mooncell")),
+    }
+}
+
+/*
+ * @概述        列出模板文件夹里的文件，按名字排序
+ * @返回值      Vec<PathBuf>，没有模板文件夹时为空
+ */
+pub fn list_templates() -> Vec<PathBuf> {
+    let dir = match config_dir() {
+        Some(dir) => dir.join("templates"),
+        None => return Vec::new(),
+    };
+    let mut templates: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_file())
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    templates.sort();
+    templates
+}
+
+/*
+ * @概述        在dir下新建一个条目
+ * @参数1       &CreateKind
+ * @参数2       &Path，所在的文件夹
+ * @参数3       &str，名字，可以包含/
+ * @返回值      Result<Vec<PathBuf>, TopError>，新建的路径，前面是自动创建的父文件夹，最后一个是新建的条目
+ */
+pub fn create(kind: &CreateKind, dir: &Path, name: &str) -> Result<Vec<PathBuf>, TopError> {
+    let relative = Path::new(name);
+    let is_valid = !name.is_empty()
+        && relative.components().all(|component| matches!(component, Component::Normal(_)));
+    if !is_valid {
+        return Err(TopError::ErrorInformation(format!("invalid name \"{}\"", name)));
+    }
+    let path = dir.join(relative);
+    if fs::symlink_metadata(&path).is_ok() {
+        return Err(TopError::ErrorInformation(format!("{} already exists", path.to_string_lossy())));
+    }
+
+    // 记录create_dir_all会创建哪些父文件夹，撤销时一起删除
+    let mut created: Vec<PathBuf> = path
+        .ancestors()
+        .skip(1)
+        .take_while(|ancestor| fs::symlink_metadata(ancestor).is_err())
+        .map(Path::to_path_buf)
+        .collect();
+    created.reverse();
+
+    let to_error = |e: std::io::Error| TopError::ErrorInformation(format!("can`t create {}: {}", path.to_string_lossy(), e));
+    let result = match path.parent() {
+        Some(parent) => fs::create_dir_all(parent),
+        None => Ok(()),
+    };
+    // 失败时删掉刚才创建的父文件夹，不留下空文件夹
+    if let Err(e) = result.and_then(|_| create_entry(kind, &path)) {
+        for dir in created.iter().rev() {
+            let _ = fs::remove_dir(dir);
+        }
+        return Err(to_error(e));
+    }
+    created.push(path);
+    Ok(created)
+}
+
+/*
+ * @概述        新建一个条目，父文件夹需要已经存在
+ * @参数1       &CreateKind
+ * @参数2       &Path，新建的路径
+ * @返回值      std::io::Result<()>
+ */
+pub fn create_entry(kind: &CreateKind, path: &Path) -> std::io::Result<()> {
+    match kind {
+        CreateKind::File => fs::File::create_new(path).map(|_| ()),
+        CreateKind::Folder => fs::create_dir(path),
+        CreateKind::Symlink(target) => symlink(target, path),
+        CreateKind::Hardlink(target) => fs::hard_link(target, path),
+        CreateKind::Template(template) => {
+            // 先独占创建，防止覆盖同时出现的同名文件，复制失败时删掉空文件
+            fs::File::create_new(path).and_then(|_| {
+                fs::copy(template, path).map(|_| ()).inspect_err(|_| { let _ = fs::remove_file(path); })
+            })
+        }
+    }
+}
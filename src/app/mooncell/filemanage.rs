@@ -2,8 +2,20 @@ use std::process::Command;
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::ffi::OsString;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use super::TopError;
+use super::filter::ListFilter;
+pub use super::filetype::FileType;
+use super::filetype::TypeTable;
+use super::journal::state_dir;
+use super::trash::{decode_path, encode_path};
 
 /*
  * @概述        FileManage是整个功能的集合体
@@ -14,26 +26,32 @@ use super::TopError;
  *              self(指代filemanager)的file_list用来存储now_path下的内容，需要调用refresh_file_list刷新
  *              如果需要对文件操作需要先通过select_push将待操作的fileunit加入self的select_list
  *              因为复制/剪切需要切换目录，所以又将select_list读进wait_operation_list等待粘贴
- *              最终由select_operate来按照self.file_operation执行操作
+ *              最终由select_operate取出self.file_operation，交给后台任务执行
  */
 
-pub enum FileType {
-    Normal,
-    Markdown,
-    Code,
-    Document,
-    Audio,
-    Video,
-    Image,
-    Zip,
-    Folder,
-}
-
 pub enum FileOperation {
     Null,
     Copy,
     Move,
-    Delete,
+    Trash,    // 放进回收站
+    Delete,    // 永久删除
+}
+
+// 排序依据
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Name,    // 自然排序，file2在file10前面
+    Size,
+    Modified,
+    Extension,
+    Type,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct SortMode {
+    pub key: SortKey,
+    pub reverse: bool,
+    pub dirs_first: bool,
 }
 
 pub struct FileUnit {
@@ -41,6 +59,21 @@ pub struct FileUnit {
     pub file_type: FileType,
     pub occupy: f64,
     pub path: PathBuf,
+    pub mode: u32,    // 权限位，包括setuid/setgid/sticky
+    pub uid: u32,
+    pub gid: u32,
+    pub size: u64,    // 文件大小
+    pub disk_usage: u64,    // 实际占用的磁盘空间
+    pub modified: Option<SystemTime>,
+    pub accessed: Option<SystemTime>,
+    pub changed: Option<SystemTime>,    // inode修改时间
+    pub created: Option<SystemTime>,    // 创建时间，文件系统不支持时为None
+    pub inode: u64,
+    pub nlink: u64,    // 硬链接数
+    pub dev: u64,    // 所在的设备
+    pub link_target: Option<PathBuf>,    // 符号链接指向的路径
+    pub link_broken: bool,    // 符号链接指向的文件不存在
+    pub xattrs: Vec<(String, String)>,    // 扩展属性(名字, 值)
 }
 
 pub struct FileManage {
@@ -49,11 +82,41 @@ pub struct FileManage {
     select_list: Vec<FileUnit>,    // 选中的文件列表
     wait_operation_list: Vec<FileUnit>,    // 等待操作的文件列表
     file_operation: FileOperation,     // 准备进行的文件操作
+    operation_target: Option<PathBuf>,    // 复制/移动的目标，None时为now_path
+    show_hidden: bool,    // 显示以.开头的文件
+    default_sort: SortMode,    // 没有单独设置过的文件夹使用的排序
+    dir_sorts: HashMap<PathBuf, SortMode>,    // 每个文件夹记住的排序
+    filter: Option<ListFilter>,    // 输入时的过滤，离开文件夹时清除
+    persistent_filter: Option<ListFilter>,    // 一直生效的过滤，不影响文件夹
+    file_types: Arc<TypeTable>,    // 判断文件类型
+    type_cache: HashMap<PathBuf, (Option<SystemTime>, u64, FileType)>,    // (修改时间, 大小, 类型)，没有变化时不再读取文件
 }
 
+const TYPE_CACHE_LIMIT: usize = 100_000;
+
 impl FileUnit {
     pub fn new() -> Self {
-        Self { name: String::new(), file_type: FileType::Normal, occupy: 0.0, path: PathBuf::new()}
+        Self {
+            name: String::new(),
+            file_type: FileType::Normal,
+            occupy: 0.0,
+            path: PathBuf::new(),
+            mode: 0,
+            uid: 0,
+            gid: 0,
+            size: 0,
+            disk_usage: 0,
+            modified: None,
+            accessed: None,
+            changed: None,
+            created: None,
+            inode: 0,
+            nlink: 0,
+            dev: 0,
+            link_target: None,
+            link_broken: false,
+            xattrs: Vec::new(),
+        }
     }
 
     pub fn is_equal(file_a: FileUnit, file_b: FileUnit) -> bool {
@@ -67,29 +130,119 @@ impl FileUnit {
         true
     }
 }
+impl SortKey {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            SortKey::Name => "name",
+            SortKey::Size => "size",
+            SortKey::Modified => "mtime",
+            SortKey::Extension => "extension",
+            SortKey::Type => "type",
+        }
+    }
+
+    fn from_str(str: &str) -> Option<Self> {
+        match str {
+            "name" => Some(SortKey::Name),
+            "size" => Some(SortKey::Size),
+            "mtime" => Some(SortKey::Modified),
+            "extension" => Some(SortKey::Extension),
+            "type" => Some(SortKey::Type),
+            _ => None,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            SortKey::Name => SortKey::Size,
+            SortKey::Size => SortKey::Modified,
+            SortKey::Modified => SortKey::Extension,
+            SortKey::Extension => SortKey::Type,
+            SortKey::Type => SortKey::Name,
+        }
+    }
+}
+
+impl Default for SortMode {
+    fn default() -> Self {
+        Self { key: SortKey::Name, reverse: false, dirs_first: true }
+    }
+}
+
+/*
+ * @概述        自然排序，数字部分按数值比较，其他部分忽略大小写
+ * @参数1       &str
+ * @参数2       &str
+ * @返回值      Ordering
+ */
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().peekable();
+    let mut b_chars = b.chars().peekable();
+    loop {
+        match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a_ch), Some(b_ch)) if a_ch.is_ascii_digit() && b_ch.is_ascii_digit() => {
+                let take_number = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+                    let mut digits = String::new();
+                    while let Some(ch) = chars.peek().copied()
+                        && ch.is_ascii_digit() {
+                        digits.push(ch);
+                        chars.next();
+                    }
+                    digits
+                };
+                let a_num = take_number(&mut a_chars);
+                let b_num = take_number(&mut b_chars);
+                // 去掉前导0后先比较长度，避免数字太大溢出
+                let a_trim = a_num.trim_start_matches('0');
+                let b_trim = b_num.trim_start_matches('0');
+                let order = a_trim.len().cmp(&b_trim.len()).then_with(|| a_trim.cmp(b_trim));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(a_ch), Some(b_ch)) => {
+                let order = a_ch.to_lowercase().cmp(b_ch.to_lowercase());
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a_chars.next();
+                b_chars.next();
+            }
+        }
+    }
+}
+
 impl Clone for FileUnit {
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
             path: self.path.clone(),
             occupy: self.occupy.clone(),
-            file_type: match self.file_type {
-                FileType::Zip => FileType::Zip,
-                FileType::Code => FileType::Code,
-                FileType::Audio => FileType::Audio,
-                FileType::Video => FileType::Video,
-                FileType::Image => FileType::Image,
-                FileType::Folder => FileType::Folder,
-                FileType::Normal => FileType::Normal,
-                FileType::Markdown => FileType::Markdown,
-                FileType::Document => FileType::Document,
-            }
+            mode: self.mode,
+            uid: self.uid,
+            gid: self.gid,
+            size: self.size,
+            disk_usage: self.disk_usage,
+            modified: self.modified,
+            accessed: self.accessed,
+            changed: self.changed,
+            created: self.created,
+            inode: self.inode,
+            nlink: self.nlink,
+            dev: self.dev,
+            link_target: self.link_target.clone(),
+            link_broken: self.link_broken,
+            xattrs: self.xattrs.clone(),
+            file_type: self.file_type,
         }
     }
 }
 
 impl FileManage {
-    pub fn new() -> Self {
+    pub fn new(file_types: Arc<TypeTable>) -> Self {
         Self {
             now_path: match Self::get_pwd() {
                 Ok(str) => PathBuf::from(str),
@@ -106,6 +259,14 @@ impl FileManage {
             select_list: Vec::new(),
             wait_operation_list: Vec::new(),
             file_operation: FileOperation::Null,
+            operation_target: None,
+            show_hidden: false,
+            default_sort: SortMode::default(),
+            dir_sorts: Self::load_sorts(),
+            filter: None,
+            persistent_filter: None,
+            file_types,
+            type_cache: HashMap::new(),
         }
     }
 
@@ -136,6 +297,19 @@ impl FileManage {
     pub fn select_ready_operation(&mut self, operate: FileOperation) {
         self.read_select_operate();
         self.file_operation = operate;
+        self.operation_target = None;
+    }
+
+    /*
+     * @概述        设置复制/移动的目标文件夹，双栏时为另一栏的文件夹
+     * @参数1       Option<PathBuf>，None时粘贴到now_path
+     */
+    pub fn set_operation_target(&mut self, target: Option<PathBuf>) {
+        self.operation_target = target;
+    }
+
+    pub fn get_operation_target(&self) -> &Path {
+        self.operation_target.as_deref().unwrap_or(&self.now_path)
     }
 
     /*
@@ -143,7 +317,7 @@ impl FileManage {
      * @返回值      Option<TopError>，仅在错误时返回
      */
     pub fn refresh_file_list(&mut self) -> Option<TopError> {
-        if let Some(path_str) = self.get_path_str() {
+        if let Some(path_str) = self.get_path_str().map(String::from) {
             match fs::read_dir(&path_str) {
                 Ok(entries) => {
                     let mut file_list: Vec<FileUnit> = Vec::new();
@@ -152,35 +326,33 @@ impl FileManage {
                         match entry {
                             Ok(entry) => {
                                 fileunit.name = Self::osstring_to_string(entry.file_name());
+                                if !self.show_hidden && fileunit.name.starts_with('.') {
+                                    continue;
+                                }
 
                                 // 匹配文件类型
                                 let path = entry.path();
                                 fileunit.path = entry.path();
-                                if path.is_dir() {
-                                    fileunit.file_type = FileType::Folder;
-                                } else {
-                                    if let Some(suffix) = Self::get_file_name_suffix(fileunit.name.clone()) {
-                                        match suffix.as_str() {
-                                            "txt" | "doc" | "docx" => fileunit.file_type = FileType::Document,
-                                            "mp4" => fileunit.file_type = FileType::Video,
-                                            "mp3" | "wav" => fileunit.file_type = FileType::Audio,
-                                            "zip" | "7z" | "rar" => fileunit.file_type = FileType::Zip,
-                                            "md" => fileunit.file_type = FileType::Markdown,
-                                            "png" | "jpg" | "jpeg" => fileunit.file_type = FileType::Image,
-                                            "rs" | "c" | "py" | "cpp" | "h" => fileunit.file_type = FileType::Code,
-                                            _ => {}
-                                        }
-                                    }
+                                let metadata = Self::read_metadata(&mut fileunit);
+                                fileunit.file_type = match metadata {
+                                    _ if path.is_dir() => FileType::Folder,
+                                    Some(metadata) => self.detect_type(&fileunit, &metadata),
+                                    None => FileType::Normal,
+                                };
+                                if !self.is_filter_match(&fileunit) {
+                                    continue;
                                 }
                                 file_list.push(fileunit);
                             }
                             Err(_) => break
                         }
                     }
-                    if file_list.is_empty() {
+                    Self::sort_file_list(&mut file_list, self.get_sort());
+                    let is_empty = file_list.is_empty();
+                    self.file_list = Ok(file_list);
+                    if is_empty {
                         return Some(TopError::EmptyError)
                     }
-                    self.file_list = Ok(file_list);
                     return None;
                 }
                 Err(_) => return Some(TopError::ReadError)
@@ -202,38 +374,26 @@ impl FileManage {
     }
 
     /*
-     * @概述        按照file_operation对wait_operation_list执行操作
-     * @返回值      Option<TopError>
+     * @概述        取出等待执行的操作和文件，复制/移动的目标为get_operation_target
+     *              实际的操作交给后台的JobManager执行
+     * @返回值      Result<(FileOperation, Vec<PathBuf>), TopError>
      */
-    pub fn select_operate(&mut self) -> Option<TopError> {
+    pub fn select_operate(&mut self) -> Result<(FileOperation, Vec<PathBuf>), TopError> {
         if self.wait_operation_list.is_empty() {
-            return  Some(TopError::EmptyError)
-        }
-        let mut path_str = String::new();
-        for file in &self.wait_operation_list {
-            path_str.push_str(&Self::pathbuf_to_string(file.path.clone()));
-        }
-        if let Some(target_path_str) = self.get_path_str() {
-            match self.file_operation {
-                FileOperation::Copy => {
-                    match  Command::new("cp").arg("-r").arg(path_str).arg(target_path_str).output() {
-                        Ok(out) => out,
-                        Err(e) => return Some(TopError::ErrorInformation(e.to_string())),
-                    };
-                },
-                FileOperation::Move => {
-                    match  Command::new("mv").arg("-r").arg(path_str).arg(target_path_str).output() {
-                        Ok(out) => out,
-                        Err(e) => return Some(TopError::ErrorInformation(e.to_string())),
-                    };
-                },
-                FileOperation::Delete => {
-                    todo!()
-                },
-                _ => {},
-            }
+            return Err(TopError::EmptyError)
         }
-        None
+        if let FileOperation::Null = self.file_operation {
+            return Err(TopError::EmptyError)
+        }
+        let sources: Vec<PathBuf> = self.wait_operation_list.iter().map(|file| file.path.clone()).collect();
+        self.wait_operation_list.clear();
+        let operate = std::mem::replace(&mut self.file_operation, FileOperation::Null);
+        self.operation_target = None;
+        Ok((operate, sources))
+    }
+
+    pub fn get_now_path(&self) -> &Path {
+        &self.now_path
     }
 
     /*
@@ -248,6 +408,7 @@ impl FileManage {
      * @概述        处理内部PathBuf回到上一层文件夹
      */
     pub fn back_upper_layer(&mut self) -> bool {
+        self.filter = None;
         self.now_path.pop()
     }
 
@@ -262,6 +423,7 @@ impl FileManage {
             FileType::Folder => {
                 self.now_path.push(folder.name.clone());
                 if self.now_path.exists() {
+                    self.filter = None;
                     return true;
                 } else {
                     self.now_path.pop();
@@ -274,6 +436,51 @@ impl FileManage {
         }
     }
 
+    /*
+     * @概述        直接跳转到某个文件夹
+     * @参数1       &Path，需要跳转的路径
+     * @返回值      bool，路径不是文件夹时返回false
+     */
+    pub fn set_path(&mut self, path: &Path) -> bool {
+        if path.is_dir() {
+            self.now_path = path.to_path_buf();
+            self.clear_select();
+            self.filter = None;
+            return true;
+        }
+        false
+    }
+
+    /*
+     * @概述        取消准备的操作，等待操作的文件放回select_list
+     */
+    pub fn cancel_operation(&mut self) {
+        self.select_list = std::mem::take(&mut self.wait_operation_list);
+        self.file_operation = FileOperation::Null;
+        self.operation_target = None;
+    }
+
+    pub fn get_operation_count(&self) -> usize {
+        self.wait_operation_list.len()
+    }
+
+    /*
+     * @概述        返回准备进行的文件操作的名称
+     * @返回值      Option<&'static str>，没有等待的操作时返回None
+     */
+    pub fn get_operation_name(&self) -> Option<&'static str> {
+        if self.wait_operation_list.is_empty() {
+            return None;
+        }
+        match self.file_operation {
+            FileOperation::Copy => Some("copy"),
+            FileOperation::Move => Some("move"),
+            FileOperation::Trash => Some("trash"),
+            FileOperation::Delete => Some("delete permanently"),
+            FileOperation::Null => None,
+        }
+    }
+
     /*
      * @概述        返回选中的文件列表
      * @返回值      Vec<FileUnit>
@@ -294,18 +501,6 @@ impl FileManage {
         self.now_path.to_str().map(|e|e)
     }
 
-    /*
-     * @概述        忽略错误返回的将pathbuf转换成string
-     * @参数1       PathBuf
-     * @返回值      string
-     */
-    fn pathbuf_to_string(path: PathBuf) -> String {
-        if let Some(str) = path.to_str() {
-            return str.to_string();
-        }
-        String::new()
-    }
-
     /*
      * @概述        调用pwd命令读取当前文件路径
      * @返回值      Result<String, TopError>
@@ -324,11 +519,271 @@ impl FileManage {
         }
     }
 
+    /*
+     * @概述        当前文件夹使用的排序
+     * @返回值      SortMode
+     */
+    pub fn get_sort(&self) -> SortMode {
+        self.dir_sorts.get(&self.now_path).copied().unwrap_or(self.default_sort)
+    }
+
+    /*
+     * @概述        设置当前文件夹的排序，记住并保存到文件
+     * @参数1       SortMode
+     */
+    pub fn set_sort(&mut self, sort: SortMode) {
+        // 其他标签页可能也改过排序，先重新读取再保存
+        self.dir_sorts = Self::load_sorts();
+        if sort == self.default_sort {
+            self.dir_sorts.remove(&self.now_path);
+        } else {
+            self.dir_sorts.insert(self.now_path.clone(), sort);
+        }
+        self.save_sorts();
+    }
+
+    pub fn switch_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+    }
+
+    pub fn is_show_hidden(&self) -> bool {
+        self.show_hidden
+    }
+
+    pub fn set_filter(&mut self, filter: Option<ListFilter>) {
+        self.filter = filter;
+    }
+
+    pub fn get_filter(&self) -> Option<&ListFilter> {
+        self.filter.as_ref()
+    }
+
+    pub fn set_persistent_filter(&mut self, filter: Option<ListFilter>) {
+        self.persistent_filter = filter;
+    }
+
+    pub fn get_persistent_filter(&self) -> Option<&ListFilter> {
+        self.persistent_filter.as_ref()
+    }
+
+    /*
+     * @概述        检查文件是否通过两个过滤，文件夹不受一直生效的过滤影响，保证还能进入子文件夹
+     * @参数1       &FileUnit
+     * @返回值      bool
+     */
+    fn is_filter_match(&self, file: &FileUnit) -> bool {
+        if let Some(filter) = &self.filter
+            && filter.matches(&file.name).is_none() {
+            return false;
+        }
+        if let Some(filter) = &self.persistent_filter
+            && !matches!(file.file_type, FileType::Folder)
+            && filter.matches(&file.name).is_none() {
+            return false;
+        }
+        true
+    }
+
+    /*
+     * @概述        按照SortMode排序，相同时按名字排序
+     * @参数1       &mut Vec<FileUnit>
+     * @参数2       SortMode
+     */
+    fn sort_file_list(file_list: &mut [FileUnit], sort: SortMode) {
+        file_list.sort_by(|a, b| {
+            let order = match sort.key {
+                SortKey::Name => Ordering::Equal,
+                SortKey::Size => a.size.cmp(&b.size),
+                SortKey::Modified => a.modified.cmp(&b.modified),
+                SortKey::Extension => Self::get_extension(a).cmp(&Self::get_extension(b)),
+                SortKey::Type => a.file_type.rank().cmp(&b.file_type.rank()),
+            }
+            .then_with(|| natural_cmp(&a.name, &b.name));
+            let order = if sort.reverse { order.reverse() } else { order };
+            if sort.dirs_first {
+                // 文件夹排在前面，不受倒序影响
+                let a_dir = matches!(a.file_type, FileType::Folder);
+                let b_dir = matches!(b.file_type, FileType::Folder);
+                b_dir.cmp(&a_dir).then(order)
+            } else {
+                order
+            }
+        });
+    }
+
+    fn get_extension(file: &FileUnit) -> String {
+        match file.file_type {
+            FileType::Folder => String::new(),
+            _ => Self::get_file_name_suffix(file.name.clone()).unwrap_or_default().to_lowercase(),
+        }
+    }
+
+    /*
+     * @概述        读取保存的每个文件夹的排序，每行为 排序依据 倒序 文件夹优先 路径，用tab分隔
+     * @返回值      HashMap<PathBuf, SortMode>
+     */
+    fn load_sorts() -> HashMap<PathBuf, SortMode> {
+        let mut sorts = HashMap::new();
+        let content = match state_dir().map(|dir| fs::read_to_string(dir.join("sort"))) {
+            Some(Ok(content)) => content,
+            _ => return sorts,
+        };
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if let [key, reverse, dirs_first, path] = fields[..]
+                && let Some(key) = SortKey::from_str(key) {
+                let sort = SortMode { key, reverse: reverse == "1", dirs_first: dirs_first == "1" };
+                sorts.insert(decode_path(path), sort);
+            }
+        }
+        sorts
+    }
+
+    fn save_sorts(&self) {
+        let dir = match state_dir() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let mut content = String::new();
+        for (path, sort) in &self.dir_sorts {
+            content.push_str(&format!(
+                "{}\t{}\t{}\t{}\n",
+                sort.key.to_str(),
+                sort.reverse as u8,
+                sort.dirs_first as u8,
+                encode_path(path),
+            ));
+        }
+        let _ = fs::create_dir_all(&dir);
+        let _ = fs::write(dir.join("sort"), content);
+    }
+
+    /*
+     * @概述        读取文件的元数据写入FileUnit，符号链接读取链接本身
+     * @参数1       &mut FileUnit，path已经设置
+     * @返回值      Option<fs::Metadata>，读取失败时返回None
+     */
+    fn read_metadata(fileunit: &mut FileUnit) -> Option<fs::Metadata> {
+        let metadata = fs::symlink_metadata(&fileunit.path).ok()?;
+        fileunit.mode = metadata.mode() & 0o7777;
+        fileunit.uid = metadata.uid();
+        fileunit.gid = metadata.gid();
+        fileunit.size = metadata.len();
+        fileunit.disk_usage = metadata.blocks() * 512;
+        fileunit.occupy = metadata.len() as f64 / 1024.0;
+        fileunit.modified = metadata.modified().ok();
+        fileunit.accessed = metadata.accessed().ok();
+        fileunit.changed = u64::try_from(metadata.ctime())
+            .ok()
+            .map(|secs| UNIX_EPOCH + Duration::new(secs, metadata.ctime_nsec() as u32));
+        fileunit.created = metadata.created().ok();
+        fileunit.inode = metadata.ino();
+        fileunit.nlink = metadata.nlink();
+        fileunit.dev = metadata.dev();
+        if metadata.file_type().is_symlink() {
+            fileunit.link_target = fs::read_link(&fileunit.path).ok();
+            fileunit.link_broken = fs::metadata(&fileunit.path).is_err();
+        }
+        fileunit.xattrs = Self::read_xattrs(&fileunit.path);
+        Some(metadata)
+    }
+
+    /*
+     * @概述        判断文件类型，修改时间和大小没有变化时使用上次的结果，避免每次刷新都读取文件开头
+     * @参数1       &FileUnit，元数据已经读取
+     * @参数2       &fs::Metadata
+     * @返回值      FileType
+     */
+    fn detect_type(&mut self, fileunit: &FileUnit, metadata: &fs::Metadata) -> FileType {
+        if let Some((modified, size, file_type)) = self.type_cache.get(&fileunit.path)
+            && *modified == fileunit.modified
+            && *size == fileunit.size {
+            return *file_type;
+        }
+        let file_type = self.file_types.detect(&fileunit.path, &fileunit.name, metadata, true);
+        if self.type_cache.len() >= TYPE_CACHE_LIMIT {
+            self.type_cache.clear();
+        }
+        self.type_cache.insert(fileunit.path.clone(), (fileunit.modified, fileunit.size, file_type));
+        file_type
+    }
+
+    /*
+     * @概述        读取扩展属性，不能显示的值显示为长度
+     * @参数1       &Path
+     * @返回值      Vec<(String, String)>，(名字, 值)
+     */
+    fn read_xattrs(path: &Path) -> Vec<(String, String)> {
+        let path_c = match CString::new(path.as_os_str().as_bytes()) {
+            Ok(path_c) => path_c,
+            Err(_) => return Vec::new(),
+        };
+        // 名字列表以\0分隔
+        let size = unsafe { libc::llistxattr(path_c.as_ptr(), std::ptr::null_mut(), 0) };
+        if size <= 0 {
+            return Vec::new();
+        }
+        let mut names = vec![0u8; size as usize];
+        let size = unsafe { libc::llistxattr(path_c.as_ptr(), names.as_mut_ptr() as *mut libc::c_char, names.len()) };
+        if size <= 0 {
+            return Vec::new();
+        }
+        names.truncate(size as usize);
+
+        let mut xattrs = Vec::new();
+        for name in names.split(|byte| *byte == 0).filter(|name| !name.is_empty()) {
+            let name_c = match CString::new(name) {
+                Ok(name_c) => name_c,
+                Err(_) => continue,
+            };
+            let value_str = match Self::read_xattr(&path_c, &name_c) {
+                Ok(value) => {
+                    let len = value.len();
+                    match String::from_utf8(value) {
+                        Ok(str) if !str.chars().any(|ch| ch.is_control() && ch != '\0') => str.trim_end_matches('\0').to_string(),
+                        _ => format!("<{} bytes>", len),
+                    }
+                }
+                Err(e) => format!("<{}>", e),
+            };
+            xattrs.push((String::from_utf8_lossy(name).to_string(), value_str));
+        }
+        xattrs
+    }
+
+    /*
+     * @概述        读取一个扩展属性的值，缓冲区不够大(ERANGE)时先查询长度再重新读取
+     * @参数1       &CString，路径
+     * @参数2       &CString，属性名
+     * @返回值      std::io::Result<Vec<u8>>
+     */
+    fn read_xattr(path_c: &CString, name_c: &CString) -> std::io::Result<Vec<u8>> {
+        let mut value = vec![0u8; 256];
+        // 两次读取之间值可能变长，多试几次
+        for _ in 0..3 {
+            let len = unsafe { libc::lgetxattr(path_c.as_ptr(), name_c.as_ptr(), value.as_mut_ptr() as *mut libc::c_void, value.len()) };
+            if len >= 0 {
+                value.truncate(len as usize);
+                return Ok(value);
+            }
+            let e = std::io::Error::last_os_error();
+            if e.raw_os_error() != Some(libc::ERANGE) {
+                return Err(e);
+            }
+            let size = unsafe { libc::lgetxattr(path_c.as_ptr(), name_c.as_ptr(), std::ptr::null_mut(), 0) };
+            if size < 0 {
+                return Err(std::io::Error::last_os_error());
+            }
+            value = vec![0u8; size as usize];
+        }
+        Err(std::io::Error::from_raw_os_error(libc::ERANGE))
+    }
+
     /*
      * @概述        获取文件名的后缀
      * @返回值      Option<String>
      */
-    fn get_file_name_suffix(filename: String) -> Option<String> {
+    pub fn get_file_name_suffix(filename: String) -> Option<String> {
         Path::new(&filename)
             .extension()  // 获取后缀部分（不包括点）
             .and_then(|ext| ext.to_str())  // 转换为字符串
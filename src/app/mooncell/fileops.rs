@@ -0,0 +1,898 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::io::{Read, Write};
+use std::os::unix::fs::{symlink, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+use super::trash::{self, TrashItem};
+
+/*
+ * @概述        用std::fs实现的复制、移动、删除
+ *              符号链接不会被跟随，而是按链接本身处理
+ *              单个文件出错不会中断整个操作，错误会收集到OperateReport里
+ *              进度写入Progress，每复制一块数据检查一次Control，以支持暂停和取消
+ *              目标已经存在时按照ConflictPlan处理，ConflictPlan在操作开始前由界面询问用户生成
+ *              被覆盖的目标会先放进回收站，实际创建的路径记录在OperateReport里，供撤销使用
+ */
+
+#[derive(Clone)]
+pub struct OperateReport {
+    pub done: usize,    // 成功处理的顶层条目数
+    pub errors: Vec<(PathBuf, String)>,    // (出错的路径, 错误信息)
+    pub cancelled: bool,    // 是否被取消
+    pub skipped: usize,    // 因为冲突被跳过的条目数
+    pub pairs: Vec<(PathBuf, PathBuf)>,    // (源路径, 新建的目标路径)，合并文件夹时记录到里面的每个条目
+    pub replaced: Vec<TrashItem>,    // 覆盖时放进回收站的目标
+    pub trashed: Vec<TrashItem>,    // 放进回收站的条目
+}
+
+/*
+ * @概述        目标已经存在时的处理方式
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum Resolution {
+    Overwrite,    // 删除已存在的目标再写入
+    Skip,
+    KeepBoth,    // 自动重命名为 name (1).ext
+    OverwriteIfNewer,    // 源文件更新时才覆盖，否则跳过
+    Merge,    // 合并文件夹，里面的冲突单独处理
+}
+
+// 冲突的处理计划，key为已经存在的目标路径
+pub type ConflictPlan = HashMap<PathBuf, Resolution>;
+
+/*
+ * @概述        一个冲突，包含两边的信息供界面显示
+ */
+pub struct Conflict {
+    pub source: PathBuf,
+    pub target: PathBuf,
+    pub source_is_dir: bool,
+    pub target_is_dir: bool,
+    pub source_size: u64,
+    pub target_size: u64,
+    pub source_modified: Option<SystemTime>,
+    pub target_modified: Option<SystemTime>,
+    pub same_file: bool,    // 源和目标是同一个文件，例如在原文件夹粘贴
+}
+
+/*
+ * @概述        操作进度，由工作线程写入，界面读取
+ */
+pub struct Progress {
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub items_done: u64,
+    pub items_total: u64,
+    pub current: PathBuf,    // 正在处理的文件
+    pub paused_time: Duration,    // 累计暂停的时间，计算速度时扣除
+}
+
+/*
+ * @概述        界面对工作线程的控制
+ */
+pub struct Control {
+    pub paused: AtomicBool,
+    pub cancelled: AtomicBool,
+}
+
+// 操作过程中需要的状态
+struct Context<'a> {
+    report: OperateReport,
+    progress: &'a Mutex<Progress>,
+    control: &'a Control,
+    plan: &'a ConflictPlan,
+    count_remove: bool,    // 删除时是否计入进度，跨文件系统移动时复制已经计过一次
+}
+
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+impl Progress {
+    pub fn new() -> Self {
+        Self {
+            bytes_done: 0,
+            bytes_total: 0,
+            items_done: 0,
+            items_total: 0,
+            current: PathBuf::new(),
+            paused_time: Duration::ZERO,
+        }
+    }
+}
+
+impl Control {
+    pub fn new() -> Self {
+        Self {
+            paused: AtomicBool::new(false),
+            cancelled: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Resolution {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            Resolution::Overwrite => "overwrite",
+            Resolution::Skip => "skip",
+            Resolution::KeepBoth => "keep both",
+            Resolution::OverwriteIfNewer => "overwrite if newer",
+            Resolution::Merge => "merge",
+        }
+    }
+}
+
+impl Conflict {
+    fn new(source: &Path, target: &Path) -> Self {
+        let source_metadata = fs::symlink_metadata(source).ok();
+        let target_metadata = fs::symlink_metadata(target).ok();
+        Self {
+            source: source.to_path_buf(),
+            target: target.to_path_buf(),
+            source_is_dir: source_metadata.as_ref().is_some_and(|metadata| metadata.is_dir()),
+            target_is_dir: target_metadata.as_ref().is_some_and(|metadata| metadata.is_dir()),
+            source_size: source_metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            target_size: target_metadata.as_ref().map_or(0, |metadata| metadata.len()),
+            source_modified: source_metadata.and_then(|metadata| metadata.modified().ok()),
+            target_modified: target_metadata.and_then(|metadata| metadata.modified().ok()),
+            same_file: is_same_file(source, target),
+        }
+    }
+
+    /*
+     * @概述        两边都是文件夹时才能合并
+     */
+    pub fn can_merge(&self) -> bool {
+        self.source_is_dir && self.target_is_dir && !self.same_file
+    }
+
+    /*
+     * @概述        这个冲突是否可以用某种方式处理
+     *              同一个文件只能保留两份或跳过，否则会删掉源文件
+     */
+    pub fn accepts(&self, resolution: Resolution) -> bool {
+        match resolution {
+            Resolution::Merge => self.can_merge(),
+            Resolution::Overwrite | Resolution::OverwriteIfNewer => !self.same_file,
+            Resolution::Skip | Resolution::KeepBoth => true,
+        }
+    }
+}
+
+impl Context<'_> {
+    /*
+     * @概述        暂停时阻塞，返回是否已被取消
+     * @返回值      bool，true表示应当停止
+     */
+    fn should_stop(&mut self) -> bool {
+        if self.control.paused.load(Ordering::Relaxed) {
+            let start = Instant::now();
+            while self.control.paused.load(Ordering::Relaxed) && !self.control.cancelled.load(Ordering::Relaxed) {
+                thread::sleep(Duration::from_millis(100));
+            }
+            if let Ok(mut progress) = self.progress.lock() {
+                progress.paused_time += start.elapsed();
+            }
+        }
+        if self.control.cancelled.load(Ordering::Relaxed) {
+            self.report.cancelled = true;
+            return true;
+        }
+        false
+    }
+
+    fn set_current(&self, path: &Path) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.current = path.to_path_buf();
+        }
+    }
+
+    fn add_progress(&self, bytes: u64, items: u64) {
+        if let Ok(mut progress) = self.progress.lock() {
+            progress.bytes_done += bytes;
+            progress.items_done += items;
+        }
+    }
+
+    /*
+     * @概述        处理一个顶层条目，没有出错时计入done
+     * @参数1       &Path，源路径
+     * @参数2       &Path，目标路径
+     * @参数3       fn，copy_path或者move_path
+     */
+    fn run_top(&mut self, source: &Path, target: &Path, operate: fn(&Path, &Path, &mut Context, bool)) {
+        let error_count = self.report.errors.len();
+        operate(source, target, self, true);
+        if self.report.errors.len() == error_count && !self.report.cancelled {
+            self.report.done += 1;
+        }
+    }
+
+    /*
+     * @概述        跳过一个条目，把它的大小计入进度
+     */
+    fn skip(&mut self, source: &Path) {
+        let (bytes, items) = measure_path(source);
+        self.add_progress(bytes, items);
+        self.report.skipped += 1;
+    }
+
+    /*
+     * @概述        目标已经存在时按照plan处理
+     * @参数1       &Path，源路径
+     * @参数2       &Path，目标路径
+     * @返回值      Option<PathBuf>，实际写入的目标路径，None表示跳过或出错
+     */
+    fn resolve_target(&mut self, source: &Path, target: &Path) -> Option<PathBuf> {
+        let target_metadata = match fs::symlink_metadata(target) {
+            Ok(metadata) => metadata,
+            Err(_) => return Some(target.to_path_buf()),
+        };
+        let resolution = match self.plan.get(target) {
+            Some(resolution) => *resolution,
+            None => {
+                self.report.push_error(source, format!("{} already exists", target.to_string_lossy()));
+                return None;
+            }
+        };
+        if is_same_file(source, target) && resolution != Resolution::KeepBoth {
+            self.skip(source);
+            return None;
+        }
+
+        let overwrite = match resolution {
+            Resolution::Skip => false,
+            Resolution::KeepBoth => {
+                let source_is_dir = fs::symlink_metadata(source).is_ok_and(|metadata| metadata.is_dir());
+                return Some(unique_target(target, source_is_dir));
+            }
+            Resolution::Merge => {
+                let source_is_dir = fs::symlink_metadata(source).is_ok_and(|metadata| metadata.is_dir());
+                if source_is_dir && target_metadata.is_dir() {
+                    return Some(target.to_path_buf());
+                }
+                self.report.push_error(source, "only folders can be merged");
+                return None;
+            }
+            Resolution::OverwriteIfNewer => {
+                let source_modified = fs::symlink_metadata(source).and_then(|metadata| metadata.modified());
+                match (source_modified, target_metadata.modified()) {
+                    (Ok(source_time), Ok(target_time)) => source_time > target_time,
+                    _ => false,
+                }
+            }
+            Resolution::Overwrite => true,
+        };
+        if !overwrite {
+            self.skip(source);
+            return None;
+        }
+
+        // 已存在的目标放进回收站，撤销时可以还原
+        match trash::trash_path(target) {
+            Ok(item) => {
+                self.report.replaced.push(item);
+                Some(target.to_path_buf())
+            }
+            Err(e) => {
+                self.report.push_error(target, format!("can`t move to trash: {}", e.to_string()));
+                None
+            }
+        }
+    }
+}
+
+impl OperateReport {
+    pub fn new() -> Self {
+        Self {
+            done: 0,
+            errors: Vec::new(),
+            cancelled: false,
+            skipped: 0,
+            pairs: Vec::new(),
+            replaced: Vec::new(),
+            trashed: Vec::new(),
+        }
+    }
+
+    /*
+     * @概述        合并另一个操作的结果
+     */
+    pub fn append(&mut self, mut other: OperateReport) {
+        self.done += other.done;
+        self.errors.append(&mut other.errors);
+        self.cancelled |= other.cancelled;
+        self.skipped += other.skipped;
+        self.pairs.append(&mut other.pairs);
+        self.replaced.append(&mut other.replaced);
+        self.trashed.append(&mut other.trashed);
+    }
+
+    fn push_error(&mut self, path: &Path, e: impl ToString) {
+        self.errors.push((path.to_path_buf(), e.to_string()));
+    }
+}
+
+/*
+ * @概述        找出复制/移动到target_dir时已经存在的目标
+ * @参数1       &[PathBuf]，需要复制/移动的文件
+ * @参数2       &Path，目标文件夹
+ * @返回值      Vec<Conflict>
+ */
+pub fn find_conflicts(sources: &[PathBuf], target_dir: &Path) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    for source in sources {
+        if let Some(name) = source.file_name() {
+            let target = target_dir.join(name);
+            if fs::symlink_metadata(&target).is_ok() {
+                conflicts.push(Conflict::new(source, &target));
+            }
+        }
+    }
+    conflicts
+}
+
+/*
+ * @概述        合并两个文件夹时，找出里面已经存在的目标
+ * @参数1       &Conflict，选择了合并的冲突
+ * @返回值      Vec<Conflict>
+ */
+pub fn merge_conflicts(conflict: &Conflict) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    if !conflict.can_merge() {
+        return conflicts;
+    }
+    if let Ok(entries) = fs::read_dir(&conflict.source) {
+        for entry in entries.flatten() {
+            let target = conflict.target.join(entry.file_name());
+            if fs::symlink_metadata(&target).is_ok() {
+                conflicts.push(Conflict::new(&entry.path(), &target));
+            }
+        }
+    }
+    conflicts
+}
+
+/*
+ * @概述        统计需要处理的字节数和条目数，写入progress
+ * @参数1       &[PathBuf]
+ * @参数2       &Mutex<Progress>
+ */
+pub fn measure(sources: &[PathBuf], progress: &Mutex<Progress>) {
+    let mut bytes = 0;
+    let mut items = 0;
+    for source in sources {
+        let (source_bytes, source_items) = measure_path(source);
+        bytes += source_bytes;
+        items += source_items;
+    }
+    if let Ok(mut progress) = progress.lock() {
+        progress.bytes_total = bytes;
+        progress.items_total = items;
+    }
+}
+
+fn measure_path(path: &Path) -> (u64, u64) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return (0, 1),
+    };
+    let mut bytes = if metadata.file_type().is_file() { metadata.len() } else { 0 };
+    let mut items = 1;
+    if metadata.file_type().is_dir()
+        && let Ok(entries) = fs::read_dir(path) {
+        for entry in entries.flatten() {
+            let (entry_bytes, entry_items) = measure_path(&entry.path());
+            bytes += entry_bytes;
+            items += entry_items;
+        }
+    }
+    (bytes, items)
+}
+
+/*
+ * @概述        将sources复制到target_dir下
+ * @参数1       &[PathBuf]，需要复制的文件/文件夹
+ * @参数2       &Path，目标文件夹
+ * @参数3       &ConflictPlan，目标已经存在时的处理方式
+ * @参数4       &Mutex<Progress>，写入进度
+ * @参数5       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn copy_into(sources: &[PathBuf], target_dir: &Path, plan: &ConflictPlan, progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan, count_remove: true };
+    for source in sources {
+        if ctx.should_stop() {
+            break;
+        }
+        let target = match check_target(source, target_dir) {
+            Ok(target) => target,
+            Err(e) => {
+                ctx.report.push_error(source, e);
+                continue;
+            }
+        };
+        ctx.run_top(source, &target, copy_path);
+    }
+    ctx.report
+}
+
+/*
+ * @概述        按照给定的(源路径, 目标路径)复制，用于重做
+ * @参数1       &[(PathBuf, PathBuf)]
+ * @参数2       &ConflictPlan，目标已经存在时的处理方式
+ * @参数3       &Mutex<Progress>，写入进度
+ * @参数4       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn copy_pairs(pairs: &[(PathBuf, PathBuf)], plan: &ConflictPlan, progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan, count_remove: true };
+    for (source, target) in pairs {
+        if ctx.should_stop() {
+            break;
+        }
+        ctx.run_top(source, target, copy_path);
+    }
+    ctx.report
+}
+
+/*
+ * @概述        将sources移动到target_dir下，跨文件系统时先复制再删除
+ * @参数1       &[PathBuf]，需要移动的文件/文件夹
+ * @参数2       &Path，目标文件夹
+ * @参数3       &ConflictPlan，目标已经存在时的处理方式
+ * @参数4       &Mutex<Progress>，写入进度
+ * @参数5       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn move_into(sources: &[PathBuf], target_dir: &Path, plan: &ConflictPlan, progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan, count_remove: true };
+    for source in sources {
+        if ctx.should_stop() {
+            break;
+        }
+        let target = match check_target(source, target_dir) {
+            Ok(target) => target,
+            Err(e) => {
+                ctx.report.push_error(source, e);
+                continue;
+            }
+        };
+        ctx.run_top(source, &target, move_path);
+    }
+    ctx.report
+}
+
+/*
+ * @概述        按照给定的(源路径, 目标路径)移动，目标的上级文件夹不存在时会先创建，用于撤销和重做
+ * @参数1       &[(PathBuf, PathBuf)]
+ * @参数2       &ConflictPlan，目标已经存在时的处理方式
+ * @参数3       &Mutex<Progress>，写入进度
+ * @参数4       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn move_pairs(pairs: &[(PathBuf, PathBuf)], plan: &ConflictPlan, progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan, count_remove: true };
+    for (source, target) in pairs {
+        if ctx.should_stop() {
+            break;
+        }
+        if let Some(parent) = target.parent()
+            && let Err(e) = fs::create_dir_all(parent) {
+            ctx.report.push_error(parent, e);
+            continue;
+        }
+        ctx.run_top(source, target, move_path);
+    }
+    ctx.report
+}
+
+/*
+ * @概述        删除sources，文件夹会被递归删除
+ * @参数1       &[PathBuf]
+ * @参数2       &Mutex<Progress>，写入进度
+ * @参数3       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn delete(sources: &[PathBuf], progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let plan = ConflictPlan::new();
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan: &plan, count_remove: true };
+    for source in sources {
+        if ctx.should_stop() {
+            break;
+        }
+        let error_count = ctx.report.errors.len();
+        remove_path(source, &mut ctx);
+        if ctx.report.errors.len() == error_count && !ctx.report.cancelled {
+            ctx.report.done += 1;
+        }
+    }
+    ctx.report
+}
+
+/*
+ * @概述        将sources放进回收站
+ * @参数1       &[PathBuf]
+ * @参数2       &Mutex<Progress>，写入进度
+ * @参数3       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn trash(sources: &[PathBuf], progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let plan = ConflictPlan::new();
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan: &plan, count_remove: true };
+    for source in sources {
+        if ctx.should_stop() {
+            break;
+        }
+        ctx.set_current(source);
+        let (bytes, items) = measure_path(source);
+        match trash::trash_path(source) {
+            Ok(item) => {
+                ctx.add_progress(bytes, items);
+                ctx.report.trashed.push(item);
+                ctx.report.done += 1;
+            }
+            Err(e) => ctx.report.push_error(source, e),
+        }
+    }
+    ctx.report
+}
+
+/*
+ * @概述        把回收站里的条目还原到原来的位置
+ * @参数1       &[TrashItem]
+ * @参数2       &Mutex<Progress>，写入进度
+ * @参数3       &Control，暂停/取消
+ * @返回值      OperateReport
+ */
+pub fn restore(items: &[TrashItem], progress: &Mutex<Progress>, control: &Control) -> OperateReport {
+    let plan = ConflictPlan::new();
+    let mut ctx = Context { report: OperateReport::new(), progress, control, plan: &plan, count_remove: true };
+    for item in items {
+        if ctx.should_stop() {
+            break;
+        }
+        ctx.set_current(&item.original_path);
+        match trash::restore(item) {
+            Ok(_) => {
+                let (bytes, items) = measure_path(&item.original_path);
+                ctx.add_progress(bytes, items);
+                ctx.report.done += 1;
+            }
+            Err(e) => ctx.report.push_error(&item.original_path, e),
+        }
+    }
+    ctx.report
+}
+
+/*
+ * @概述        按照(原路径, 新路径)重命名，新路径可以是本次重命名的另一个原路径，例如a->b、b->a
+ *              先全部改成临时名字再改成新名字，新路径被本次之外的文件占用时跳过并报错
+ * @参数1       &[(PathBuf, PathBuf)]
+ * @返回值      OperateReport，pairs中为成功重命名的路径
+ */
+pub fn rename_pairs(pairs: &[(PathBuf, PathBuf)]) -> OperateReport {
+    let mut report = OperateReport::new();
+    let sources: HashSet<&PathBuf> = pairs.iter().map(|(source, _)| source).collect();
+    let mut staged: Vec<(PathBuf, &PathBuf, &PathBuf)> = Vec::new();    // (临时路径, 原路径, 新路径)
+    for (index, (source, target)) in pairs.iter().enumerate() {
+        if source == target {
+            continue;
+        }
+        if fs::symlink_metadata(target).is_ok() && !sources.contains(target) {
+            report.push_error(source, format!("{} already exists", target.to_string_lossy()));
+            continue;
+        }
+        let temp = source.with_file_name(format!(".mooncell-rename-{}-{}", std::process::id(), index));
+        match fs::rename(source, &temp) {
+            Ok(_) => staged.push((temp, source, target)),
+            Err(e) => report.push_error(source, e),
+        }
+    }
+    for (temp, source, target) in staged {
+        // 新路径仍然存在说明占用它的原路径没有改名成功
+        if fs::symlink_metadata(target).is_ok() {
+            let _ = fs::rename(&temp, source);
+            report.push_error(source, format!("{} already exists", target.to_string_lossy()));
+            continue;
+        }
+        match fs::rename(&temp, target) {
+            Ok(_) => {
+                report.pairs.push((source.clone(), target.clone()));
+                report.done += 1;
+            }
+            Err(e) => {
+                let _ = fs::rename(&temp, source);
+                report.push_error(source, e);
+            }
+        }
+    }
+    report
+}
+
+/*
+ * @概述        计算目标路径并检查是否可以写入，目标已经存在的情况由resolve_target处理
+ * @参数1       &Path，源路径
+ * @参数2       &Path，目标文件夹
+ * @返回值      Result<PathBuf, String>，目标路径
+ */
+fn check_target(source: &Path, target_dir: &Path) -> Result<PathBuf, String> {
+    let name = source.file_name().ok_or_else(|| String::from("invalid file name"))?;
+    let target = target_dir.join(name);
+
+    // 不能把文件夹复制/移动到它自己里面，只规范化上级，源是符号链接时不跟随
+    let source_abs = match source.parent().map(fs::canonicalize) {
+        Some(Ok(parent)) => parent.join(name),
+        _ => source.to_path_buf(),
+    };
+    let target_abs = fs::canonicalize(target_dir).unwrap_or_else(|_| target_dir.to_path_buf());
+    if target_abs.starts_with(&source_abs) {
+        return Err(String::from("can`t copy or move a folder into itself"));
+    }
+    Ok(target)
+}
+
+/*
+ * @概述        判断两个路径是否指向同一个文件(不跟随符号链接)
+ */
+fn is_same_file(a: &Path, b: &Path) -> bool {
+    match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/*
+ * @概述        为已经存在的目标生成一个不冲突的名字，name.ext -> name (1).ext
+ * @参数1       &Path，已经存在的目标路径
+ * @参数2       bool，是否为文件夹，文件夹不拆分后缀
+ * @返回值      PathBuf
+ */
+fn unique_target(target: &Path, is_dir: bool) -> PathBuf {
+    // 直接拼接OsStr，不是UTF-8的名字也保持原样
+    let name = target.file_name().unwrap_or_default();
+    let (stem, extension) = match (is_dir, target.file_stem(), target.extension()) {
+        (false, Some(stem), Some(extension)) => (stem, Some(extension)),
+        _ => (name, None),
+    };
+    let mut index = 1;
+    loop {
+        let mut new_name = stem.to_os_string();
+        new_name.push(format!(" ({})", index));
+        if let Some(extension) = extension {
+            new_name.push(".");
+            new_name.push(extension);
+        }
+        let new_target = target.with_file_name(new_name);
+        if fs::symlink_metadata(&new_target).is_err() {
+            return new_target;
+        }
+        index += 1;
+    }
+}
+
+/*
+ * @概述        移动单个路径，先尝试rename，跨文件系统时复制后删除
+ *              合并文件夹时逐个移动里面的内容，最后删除空的源文件夹
+ * @参数1       &Path，源路径
+ * @参数2       &Path，目标路径
+ * @参数3       &mut Context
+ * @参数4       bool，是否把新建的目标记录到report.pairs
+ */
+fn move_path(source: &Path, target: &Path, ctx: &mut Context, record: bool) {
+    if ctx.should_stop() {
+        return;
+    }
+    let target = match ctx.resolve_target(source, target) {
+        Some(target) => target,
+        None => return,
+    };
+    ctx.set_current(source);
+
+    // 目标仍然存在说明是合并文件夹
+    if fs::symlink_metadata(&target).is_ok() {
+        match fs::read_dir(source) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        Ok(entry) => move_path(&entry.path(), &target.join(entry.file_name()), ctx, true),
+                        Err(e) => ctx.report.push_error(source, e),
+                    }
+                }
+            }
+            Err(e) => return ctx.report.push_error(source, e),
+        }
+        if ctx.report.cancelled {
+            return;
+        }
+        // 有内容被跳过时源文件夹不为空，保留它
+        match fs::remove_dir(source) {
+            Ok(_) => ctx.add_progress(0, 1),
+            Err(e) if e.kind() == io::ErrorKind::DirectoryNotEmpty => ctx.add_progress(0, 1),
+            Err(e) => ctx.report.push_error(source, e),
+        }
+        return;
+    }
+
+    match fs::rename(source, &target) {
+        Ok(_) => {
+            let (bytes, items) = measure_path(&target);
+            ctx.add_progress(bytes, items);
+            if record {
+                ctx.report.pairs.push((source.to_path_buf(), target));
+            }
+        }
+        Err(e) if e.raw_os_error() == Some(libc::EXDEV) => {
+            // 跨文件系统，复制成功后才删除源文件
+            let error_count = ctx.report.errors.len();
+            copy_path(source, &target, ctx, false);
+            if ctx.report.errors.len() == error_count && !ctx.report.cancelled {
+                ctx.count_remove = false;
+                remove_path(source, ctx);
+                ctx.count_remove = true;
+            }
+            if record && fs::symlink_metadata(&target).is_ok() {
+                ctx.report.pairs.push((source.to_path_buf(), target));
+            }
+        }
+        Err(e) => ctx.report.push_error(source, e),
+    }
+}
+
+/*
+ * @概述        递归复制单个路径，符号链接会被重新创建而不是复制它指向的内容
+ * @参数1       &Path，源路径
+ * @参数2       &Path，目标路径
+ * @参数3       &mut Context，收集错误、更新进度
+ * @参数4       bool，是否把新建的目标记录到report.pairs
+ */
+fn copy_path(source: &Path, target: &Path, ctx: &mut Context, record: bool) {
+    if ctx.should_stop() {
+        return;
+    }
+    let resolved = match ctx.resolve_target(source, target) {
+        Some(resolved) => resolved,
+        None => return,
+    };
+    let target = resolved.as_path();
+    let metadata = match fs::symlink_metadata(source) {
+        Ok(metadata) => metadata,
+        Err(e) => return ctx.report.push_error(source, e),
+    };
+    let file_type = metadata.file_type();
+    ctx.set_current(source);
+
+    if file_type.is_symlink() {
+        match fs::read_link(source).and_then(|link| symlink(link, target)) {
+            Ok(_) if record => ctx.report.pairs.push((source.to_path_buf(), target.to_path_buf())),
+            Ok(_) => {}
+            Err(e) => ctx.report.push_error(source, e),
+        }
+        ctx.add_progress(0, 1);
+    } else if file_type.is_dir() {
+        // 合并时目标文件夹已经存在
+        let merge = fs::symlink_metadata(target).is_ok();
+        if !merge {
+            if let Err(e) = fs::create_dir(target) {
+                return ctx.report.push_error(target, e);
+            }
+            if record {
+                ctx.report.pairs.push((source.to_path_buf(), target.to_path_buf()));
+            }
+        }
+        ctx.add_progress(0, 1);
+        match fs::read_dir(source) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        // 合并时里面的条目需要单独记录
+                        Ok(entry) => copy_path(&entry.path(), &target.join(entry.file_name()), ctx, merge),
+                        Err(e) => ctx.report.push_error(source, e),
+                    }
+                }
+            }
+            Err(e) => ctx.report.push_error(source, e),
+        }
+        // 最后再设置权限，避免只读文件夹导致里面的内容无法写入，合并时保留目标的权限
+        if !merge && let Err(e) = fs::set_permissions(target, metadata.permissions()) {
+            ctx.report.push_error(target, e);
+        }
+    } else if file_type.is_file() {
+        match copy_file(source, target, ctx) {
+            Ok(_) => {
+                if record {
+                    ctx.report.pairs.push((source.to_path_buf(), target.to_path_buf()));
+                }
+                if let Err(e) = fs::set_permissions(target, metadata.permissions()) {
+                    ctx.report.push_error(target, e);
+                }
+            }
+            Err(e) => {
+                // 取消或出错时删除复制了一半的文件
+                let _ = fs::remove_file(target);
+                if !ctx.report.cancelled {
+                    ctx.report.push_error(source, e);
+                }
+            }
+        }
+        ctx.add_progress(0, 1);
+    } else {
+        ctx.report.push_error(source, io::Error::other("special file is not supported"));
+        ctx.add_progress(0, 1);
+    }
+}
+
+/*
+ * @概述        分块复制单个文件，每一块都更新进度并检查暂停/取消
+ * @参数1       &Path，源文件
+ * @参数2       &Path，目标文件，必须不存在
+ * @参数3       &mut Context
+ * @返回值      io::Result<()>
+ */
+fn copy_file(source: &Path, target: &Path, ctx: &mut Context) -> io::Result<()> {
+    let mut reader = fs::File::open(source)?;
+    let mut writer = fs::OpenOptions::new().write(true).create_new(true).open(target)?;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        if ctx.should_stop() {
+            return Err(io::Error::other("cancelled"));
+        }
+        let len = reader.read(&mut buffer)?;
+        if len == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..len])?;
+        ctx.add_progress(len as u64, 0);
+    }
+    Ok(())
+}
+
+/*
+ * @概述        递归删除单个路径，不跟随符号链接
+ * @参数1       &Path
+ * @参数2       &mut Context，收集错误、更新进度
+ */
+fn remove_path(path: &Path, ctx: &mut Context) {
+    if ctx.should_stop() {
+        return;
+    }
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) => return ctx.report.push_error(path, e),
+    };
+    ctx.set_current(path);
+
+    if metadata.file_type().is_dir() {
+        match fs::read_dir(path) {
+            Ok(entries) => {
+                for entry in entries {
+                    match entry {
+                        Ok(entry) => remove_path(&entry.path(), ctx),
+                        Err(e) => ctx.report.push_error(path, e),
+                    }
+                }
+            }
+            Err(e) => return ctx.report.push_error(path, e),
+        }
+        if ctx.report.cancelled {
+            return;
+        }
+        if let Err(e) = fs::remove_dir(path) {
+            ctx.report.push_error(path, e);
+        }
+    } else {
+        if let Err(e) = fs::remove_file(path) {
+            ctx.report.push_error(path, e);
+        }
+        if ctx.count_remove && metadata.file_type().is_file() {
+            ctx.add_progress(metadata.len(), 0);
+        }
+    }
+    if ctx.count_remove {
+        ctx.add_progress(0, 1);
+    }
+}
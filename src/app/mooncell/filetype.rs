@@ -0,0 +1,332 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::Read;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use super::create::config_dir;
+use super::filemanage::FileManage;
+
+/*
+ * @概述        判断文件类型，按顺序使用：
+ *              文件本身的类型(文件夹、符号链接、设备、socket、fifo) -> 用户配置的后缀 -> 文件开头的magic bytes
+ *              -> 内置的后缀表 -> shared-mime-info的后缀(/usr/share/mime/globs2) -> 可执行权限
+ *              用户配置在$XDG_CONFIG_HOME/mooncell/filetypes，每行为：
+ *                  ext 类型 后缀...            例如 ext code toml json
+ *                  style 类型 颜色 [图标]      例如 style folder lightblue 📁，颜色为-时使用默认颜色
+ *              启动时读取一次，修改后需要重新打开
+ */
+
+const SNIFF_SIZE: usize = 512;    // 读取文件开头的字节数
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileType {
+    Normal,
+    Markdown,
+    Code,
+    Document,
+    Audio,
+    Video,
+    Image,
+    Zip,
+    Folder,
+    Executable,    // ELF、有可执行权限的文件
+    Symlink,
+    Device,    // 块设备和字符设备
+    Socket,
+    Fifo,
+}
+
+#[derive(Clone)]
+pub struct TypeStyle {
+    pub color: String,    // ratatui能解析的颜色名，例如lightblue、#ff8800
+    pub icon: String,    // 显示在文件名前面，默认为空
+}
+
+pub struct TypeTable {
+    extensions: HashMap<String, FileType>,    // 用户配置的后缀
+    mime_globs: HashMap<String, FileType>,    // shared-mime-info中的后缀，已经换算为类型
+    styles: HashMap<FileType, TypeStyle>,
+}
+
+impl FileType {
+    pub const ALL: [FileType; 14] = [
+        FileType::Folder,
+        FileType::Document,
+        FileType::Markdown,
+        FileType::Code,
+        FileType::Image,
+        FileType::Audio,
+        FileType::Video,
+        FileType::Zip,
+        FileType::Executable,
+        FileType::Symlink,
+        FileType::Device,
+        FileType::Socket,
+        FileType::Fifo,
+        FileType::Normal,
+    ];
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            FileType::Folder => "folder",
+            FileType::Document => "document",
+            FileType::Markdown => "markdown",
+            FileType::Code => "code",
+            FileType::Image => "image",
+            FileType::Audio => "audio",
+            FileType::Video => "video",
+            FileType::Zip => "zip",
+            FileType::Executable => "executable",
+            FileType::Symlink => "symlink",
+            FileType::Device => "device",
+            FileType::Socket => "socket",
+            FileType::Fifo => "fifo",
+            FileType::Normal => "other",
+        }
+    }
+
+    pub fn from_str(str: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|file_type| file_type.to_str() == str)
+    }
+
+    // 按类型排序时的顺序
+    pub fn rank(self) -> u8 {
+        match self {
+            FileType::Folder => 0,
+            FileType::Document => 1,
+            FileType::Markdown => 2,
+            FileType::Code => 3,
+            FileType::Image => 4,
+            FileType::Audio => 5,
+            FileType::Video => 6,
+            FileType::Zip => 7,
+            FileType::Executable => 8,
+            FileType::Symlink => 9,
+            FileType::Device => 10,
+            FileType::Socket => 11,
+            FileType::Fifo => 12,
+            FileType::Normal => 13,
+        }
+    }
+
+    fn default_color(self) -> &'static str {
+        match self {
+            FileType::Folder => "blue",
+            FileType::Document => "lightyellow",
+            FileType::Markdown => "cyan",
+            FileType::Code => "green",
+            FileType::Image => "magenta",
+            FileType::Audio => "yellow",
+            FileType::Video => "lightred",
+            FileType::Zip => "red",
+            FileType::Executable => "lightgreen",
+            FileType::Symlink => "lightcyan",
+            FileType::Device => "lightmagenta",
+            FileType::Socket => "lightblue",
+            FileType::Fifo => "darkgray",
+            FileType::Normal => "reset",
+        }
+    }
+}
+
+impl TypeTable {
+    /*
+     * @概述        读取用户配置和shared-mime-info，都不存在时只使用内置的后缀表
+     */
+    pub fn load() -> Self {
+        let mut table = Self {
+            extensions: HashMap::new(),
+            mime_globs: load_mime_globs(),
+            styles: FileType::ALL
+                .into_iter()
+                .map(|file_type| (file_type, TypeStyle { color: file_type.default_color().to_string(), icon: String::new() }))
+                .collect(),
+        };
+        let content = match config_dir().map(|dir| fs::read_to_string(dir.join("filetypes"))) {
+            Some(Ok(content)) => content,
+            _ => return table,
+        };
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields[..] {
+                ["ext", file_type, ref extensions @ ..] => {
+                    if let Some(file_type) = FileType::from_str(file_type) {
+                        for extension in extensions {
+                            table.extensions.insert(extension.trim_start_matches('.').to_lowercase(), file_type);
+                        }
+                    }
+                }
+                ["style", file_type, color, ref icon @ ..] => {
+                    if let Some(style) = FileType::from_str(file_type).and_then(|file_type| table.styles.get_mut(&file_type)) {
+                        if color != "-" {
+                            style.color = color.to_string();
+                        }
+                        style.icon = icon.join(" ");
+                    }
+                }
+                _ => {}
+            }
+        }
+        table
+    }
+
+    pub fn get_style(&self, file_type: FileType) -> TypeStyle {
+        self.styles.get(&file_type).cloned().unwrap_or(TypeStyle { color: String::from("reset"), icon: String::new() })
+    }
+
+    /*
+     * @概述        判断文件类型
+     * @参数1       &Path
+     * @参数2       &str，文件名
+     * @参数3       &fs::Metadata，符号链接本身的元数据
+     * @参数4       bool，是否读取文件开头，统计磁盘占用时为了速度不读取
+     * @返回值      FileType
+     */
+    pub fn detect(&self, path: &Path, name: &str, metadata: &fs::Metadata, read_content: bool) -> FileType {
+        let kind = metadata.file_type();
+        if kind.is_dir() {
+            return FileType::Folder;
+        }
+        if kind.is_symlink() {
+            return FileType::Symlink;
+        }
+        if kind.is_block_device() || kind.is_char_device() {
+            return FileType::Device;
+        }
+        if kind.is_socket() {
+            return FileType::Socket;
+        }
+        if kind.is_fifo() {
+            return FileType::Fifo;
+        }
+
+        let extension = FileManage::get_file_name_suffix(name.to_string()).map(|extension| extension.to_lowercase());
+        if let Some(file_type) = extension.as_ref().and_then(|extension| self.extensions.get(extension)) {
+            return *file_type;
+        }
+        let is_executable = metadata.permissions().mode() & 0o111 != 0;
+        let by_extension = extension.as_deref().and_then(|extension| builtin_extension(extension).or_else(|| self.mime_globs.get(extension).copied()));
+        if read_content && metadata.len() > 0
+            && let Some(file_type) = sniff(path, is_executable) {
+            // docx、odt、jar等也是zip
+            return match (file_type, by_extension) {
+                (FileType::Zip, Some(FileType::Document)) => FileType::Document,
+                _ => file_type,
+            };
+        }
+        match by_extension {
+            Some(file_type) => file_type,
+            None if is_executable => FileType::Executable,
+            None => FileType::Normal,
+        }
+    }
+}
+
+fn builtin_extension(extension: &str) -> Option<FileType> {
+    let file_type = match extension {
+        "txt" | "doc" | "docx" | "odt" | "rtf" | "pdf" | "xls" | "xlsx" | "ods" | "ppt" | "pptx" | "odp" | "epub" | "csv" => FileType::Document,
+        "md" | "markdown" => FileType::Markdown,
+        "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "py" | "js" | "ts" | "go" | "java" | "kt" | "rb" | "lua" | "sh" | "bash" | "zsh"
+        | "fish" | "toml" | "json" | "yaml" | "yml" | "xml" | "html" | "css" | "sql" | "ini" | "conf" | "lock" => FileType::Code,
+        "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "ico" | "tif" | "tiff" | "heic" | "avif" => FileType::Image,
+        "mp3" | "wav" | "flac" | "ogg" | "opus" | "m4a" | "aac" => FileType::Audio,
+        "mp4" | "mkv" | "webm" | "avi" | "mov" | "wmv" | "flv" | "m4v" => FileType::Video,
+        "zip" | "7z" | "rar" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "deb" | "rpm" | "jar" | "iso" => FileType::Zip,
+        "so" | "exe" | "appimage" => FileType::Executable,
+        _ => return None,
+    };
+    Some(file_type)
+}
+
+/*
+ * @概述        按文件开头的magic bytes判断，无法判断时返回None
+ * @参数1       &Path
+ * @参数2       bool，是否有可执行权限，#!开头的脚本有权限时为可执行文件
+ * @返回值      Option<FileType>
+ */
+fn sniff(path: &Path, is_executable: bool) -> Option<FileType> {
+    let mut head = Vec::with_capacity(SNIFF_SIZE);
+    File::open(path).ok()?.take(SNIFF_SIZE as u64).read_to_end(&mut head).ok()?;
+    let at = |offset: usize, magic: &[u8]| head.get(offset..offset + magic.len()) == Some(magic);
+
+    let file_type = if at(0, b"\x89PNG") || at(0, b"\xFF\xD8\xFF") || at(0, b"GIF8") || at(0, b"II*\0") || at(0, b"MM\0*")
+        || (at(0, b"RIFF") && at(8, b"WEBP")) {
+        FileType::Image
+    } else if at(0, b"ID3") || at(0, b"\xFF\xFB") || at(0, b"\xFF\xF3") || at(0, b"fLaC") || at(0, b"OggS")
+        || (at(0, b"RIFF") && at(8, b"WAVE")) || (at(4, b"ftyp") && at(8, b"M4A ")) {
+        FileType::Audio
+    } else if at(4, b"ftyp") && (at(8, b"heic") || at(8, b"heix") || at(8, b"avif") || at(8, b"mif1")) {
+        FileType::Image
+    } else if at(4, b"ftyp") || at(0, b"\x1A\x45\xDF\xA3") || (at(0, b"RIFF") && at(8, b"AVI ")) {
+        FileType::Video
+    } else if at(0, b"%PDF") || at(0, b"\xD0\xCF\x11\xE0") {
+        FileType::Document
+    } else if at(0, b"PK\x03\x04") || at(0, b"\x1F\x8B") || at(0, b"BZh") || at(0, b"\xFD7zXZ\0") || at(0, b"7z\xBC\xAF\x27\x1C")
+        || at(0, b"Rar!\x1A\x07") || at(0, b"\x28\xB5\x2F\xFD") || at(257, b"ustar") {
+        FileType::Zip
+    } else if at(0, b"\x7FELF") {
+        FileType::Executable
+    } else if at(0, b"#!") {
+        if is_executable { FileType::Executable } else { FileType::Code }
+    } else {
+        return None;
+    };
+    Some(file_type)
+}
+
+/*
+ * @概述        读取shared-mime-info的globs2，只使用*.后缀形式的规则，同一个后缀使用权重最高的
+ *              依次查找$XDG_DATA_HOME和$XDG_DATA_DIRS，前面的优先
+ * @返回值      HashMap<String, FileType>，后缀和类型，没有数据库时为空
+ */
+fn load_mime_globs() -> HashMap<String, FileType> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => dirs.push(PathBuf::from(dir)),
+        _ => dirs.extend(env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share"))),
+    }
+    let data_dirs = env::var("XDG_DATA_DIRS").unwrap_or_default();
+    let data_dirs = if data_dirs.is_empty() { String::from("/usr/local/share:/usr/share") } else { data_dirs };
+    dirs.extend(data_dirs.split(':').filter(|dir| !dir.is_empty()).map(PathBuf::from));
+
+    let mut globs = HashMap::new();
+    for dir in dirs {
+        let content = match fs::read_to_string(dir.join("mime/globs2")) {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+        // 每行为 权重:mime类型:规则[:标记]，文件已经按权重从高到低排序
+        for line in content.lines().filter(|line| !line.starts_with('#')) {
+            let fields: Vec<&str> = line.split(':').collect();
+            if let [_, mime, glob, ..] = fields[..]
+                && let Some(extension) = glob.strip_prefix("*.")
+                && !extension.contains(['*', '?', '['])
+                && let Some(file_type) = mime_to_type(mime) {
+                globs.entry(extension.to_lowercase()).or_insert(file_type);
+            }
+        }
+    }
+    globs
+}
+
+fn mime_to_type(mime: &str) -> Option<FileType> {
+    let (top, sub) = mime.split_once('/')?;
+    let file_type = match (top, sub) {
+        ("image", _) => FileType::Image,
+        ("audio", _) => FileType::Audio,
+        ("video", _) => FileType::Video,
+        ("text", "markdown" | "x-markdown") => FileType::Markdown,
+        ("text", "plain") | ("application", "pdf" | "msword" | "rtf" | "epub+zip") => FileType::Document,
+        ("application", sub) if sub.starts_with("vnd.openxmlformats-officedocument") || sub.starts_with("vnd.oasis.opendocument")
+            || sub.starts_with("vnd.ms-") => FileType::Document,
+        ("application", sub) if sub.contains("compressed") || sub.contains("archive") || sub.ends_with("-package")
+            || matches!(sub, "zip" | "gzip" | "x-tar" | "vnd.rar" | "x-rar" | "x-xz" | "x-bzip" | "x-bzip2" | "zstd" | "x-zstd" | "x-lzma" | "x-rpm" | "x-cd-image") => FileType::Zip,
+        ("application", "x-executable" | "x-sharedlib" | "x-pie-executable" | "x-msdownload") => FileType::Executable,
+        ("text", _) => FileType::Code,
+        ("application", "json" | "toml" | "xml" | "yaml" | "x-yaml" | "javascript" | "sql" | "x-shellscript" | "x-perl" | "x-ruby" | "x-php") => FileType::Code,
+        _ => return None,
+    };
+    Some(file_type)
+}
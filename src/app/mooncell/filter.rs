@@ -0,0 +1,191 @@
+use regex::{Regex, RegexBuilder};
+
+use super::TopError;
+
+/*
+ * @概述        文件列表的过滤
+ *              fuzzy: 按顺序包含输入的每个字符
+ *              glob: 整个名字匹配通配符，支持* ? [abc]
+ *              regex: 名字中包含匹配正则表达式的部分
+ *              输入中没有大写字母时忽略大小写
+ */
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FilterMode {
+    Fuzzy,
+    Glob,
+    Regex,
+}
+
+#[derive(Clone)]
+pub struct ListFilter {
+    pattern: String,
+    mode: FilterMode,
+    regex: Option<Regex>,    // glob和regex模式编译后的正则表达式
+    ignore_case: bool,
+}
+
+impl FilterMode {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            FilterMode::Fuzzy => "fuzzy",
+            FilterMode::Glob => "glob",
+            FilterMode::Regex => "regex",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            FilterMode::Fuzzy => FilterMode::Glob,
+            FilterMode::Glob => FilterMode::Regex,
+            FilterMode::Regex => FilterMode::Fuzzy,
+        }
+    }
+}
+
+impl ListFilter {
+    /*
+     * @概述        创建过滤器
+     * @参数1       &str，输入的内容
+     * @参数2       FilterMode
+     * @返回值      Result<Self, TopError>，正则表达式错误时返回Err
+     */
+    pub fn new(pattern: &str, mode: FilterMode) -> Result<Self, TopError> {
+        let ignore_case = !pattern.chars().any(|ch| ch.is_uppercase());
+        let regex_str = match mode {
+            FilterMode::Fuzzy => None,
+            FilterMode::Glob => Some(glob_to_regex(pattern)),
+            FilterMode::Regex => Some(pattern.to_string()),
+        };
+        let regex = match regex_str {
+            Some(regex_str) => Some(
+                RegexBuilder::new(&regex_str)
+                    .case_insensitive(ignore_case)
+                    .build()
+                    .map_err(|e| TopError::ErrorInformation(e.to_string()))?,
+            ),
+            None => None,
+        };
+        Ok(Self { pattern: pattern.to_string(), mode, regex, ignore_case })
+    }
+
+    pub fn get_pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    pub fn get_mode(&self) -> FilterMode {
+        self.mode
+    }
+
+    pub fn describe(&self) -> String {
+        format!("{} {}", self.mode.to_str(), self.pattern)
+    }
+
+    /*
+     * @概述        检查名字是否匹配
+     * @参数1       &str，文件名
+     * @返回值      Option<Vec<usize>>，匹配时返回需要高亮的字符位置
+     */
+    pub fn matches(&self, name: &str) -> Option<Vec<usize>> {
+        match (&self.regex, self.mode) {
+            (None, _) => self.fuzzy_match(name),
+            (Some(regex), FilterMode::Glob) => {
+                // 通配符中的普通字符放在捕获组里，高亮这些字符
+                let captures = regex.captures(name)?;
+                let mut positions = Vec::new();
+                for group in captures.iter().skip(1).flatten() {
+                    positions.extend(byte_range_to_chars(name, group.start(), group.end()));
+                }
+                Some(positions)
+            }
+            (Some(regex), _) => {
+                let mut positions = Vec::new();
+                for found in regex.find_iter(name) {
+                    positions.extend(byte_range_to_chars(name, found.start(), found.end()));
+                }
+                if positions.is_empty() && !regex.is_match(name) { None } else { Some(positions) }
+            }
+        }
+    }
+
+    fn fuzzy_match(&self, name: &str) -> Option<Vec<usize>> {
+        let mut positions = Vec::new();
+        let mut pattern = self.pattern.chars().peekable();
+        for (i, ch) in name.chars().enumerate() {
+            let wanted = match pattern.peek() {
+                Some(wanted) => *wanted,
+                None => break,
+            };
+            let is_same = if self.ignore_case {
+                ch.to_lowercase().eq(wanted.to_lowercase())
+            } else {
+                ch == wanted
+            };
+            if is_same {
+                positions.push(i);
+                pattern.next();
+            }
+        }
+        if pattern.peek().is_none() { Some(positions) } else { None }
+    }
+}
+
+/*
+ * @概述        把通配符转换为正则表达式，连续的普通字符放进捕获组
+ * @参数1       &str
+ * @返回值      String
+ */
+fn glob_to_regex(glob: &str) -> String {
+    let mut regex = String::from("^");
+    let mut literal = String::new();
+    let flush = |regex: &mut String, literal: &mut String| {
+        if !literal.is_empty() {
+            regex.push_str(&format!("({})", regex::escape(literal)));
+            literal.clear();
+        }
+    };
+    let mut chars = glob.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '*' => {
+                flush(&mut regex, &mut literal);
+                regex.push_str(".*");
+            }
+            '?' => {
+                flush(&mut regex, &mut literal);
+                regex.push('.');
+            }
+            '[' => {
+                flush(&mut regex, &mut literal);
+                let mut class = String::from("[");
+                for class_ch in chars.by_ref() {
+                    if class_ch == ']' {
+                        break;
+                    }
+                    match class_ch {
+                        '!' if class.len() == 1 => class.push('^'),
+                        '\\' | '[' => {
+                            class.push('\\');
+                            class.push(class_ch);
+                        }
+                        _ => class.push(class_ch),
+                    }
+                }
+                class.push(']');
+                regex.push_str(&class);
+            }
+            _ => literal.push(ch),
+        }
+    }
+    flush(&mut regex, &mut literal);
+    regex.push('$');
+    regex
+}
+
+fn byte_range_to_chars(str: &str, start: usize, end: usize) -> Vec<usize> {
+    str.char_indices()
+        .enumerate()
+        .filter(|(_, (byte, _))| *byte >= start && *byte < end)
+        .map(|(i, _)| i)
+        .collect()
+}
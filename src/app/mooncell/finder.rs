@@ -0,0 +1,174 @@
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use super::ignore;
+
+/*
+ * @概述        递归查找文件
+ *              后台线程遍历文件夹，把相对路径放进entries，界面随时可以按输入的内容模糊匹配
+ *              匹配分数：连续的字符、单词开头、文件名里的字符加分，路径越长分数越低
+ */
+
+const MAX_ENTRIES: usize = 500_000;    // 防止在很大的文件夹里占用太多内存
+const BATCH_SIZE: usize = 256;
+
+pub struct Finder {
+    root: PathBuf,
+    entries: Arc<Mutex<Vec<(PathBuf, bool)>>>,    // (相对路径, 是否为文件夹)
+    done: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+}
+
+/*
+ * @概述        一个匹配结果
+ */
+pub struct FinderMatch {
+    pub path: PathBuf,    // 相对于查找开始的文件夹
+    pub is_dir: bool,
+    pub positions: Vec<usize>,    // 匹配的字符位置
+    score: i64,
+}
+
+impl Finder {
+    /*
+     * @概述        开始在后台遍历root
+     * @参数1       &Path
+     * @参数2       bool，是否包括以.开头的文件
+     */
+    pub fn start(root: &Path, show_hidden: bool) -> Self {
+        let entries = Arc::new(Mutex::new(Vec::new()));
+        let done = Arc::new(AtomicBool::new(false));
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let root = root.to_path_buf();
+            let entries = Arc::clone(&entries);
+            let done = Arc::clone(&done);
+            let cancel = Arc::clone(&cancel);
+            thread::spawn(move || {
+                let mut batch = Vec::new();
+                let mut count = 0;
+                ignore::walk(&root, show_hidden, &cancel, |path, is_dir| {
+                    if let Ok(relative) = path.strip_prefix(&root) {
+                        batch.push((relative.to_path_buf(), is_dir));
+                        count += 1;
+                    }
+                    if batch.len() >= BATCH_SIZE
+                        && let Ok(mut entries) = entries.lock() {
+                        entries.append(&mut batch);
+                    }
+                    count < MAX_ENTRIES
+                });
+                if let Ok(mut entries) = entries.lock() {
+                    entries.append(&mut batch);
+                }
+                done.store(true, Ordering::Relaxed);
+            });
+        }
+        Self { root: root.to_path_buf(), entries, done, cancel }
+    }
+
+    pub fn get_root(&self) -> &Path {
+        &self.root
+    }
+
+    /*
+     * @返回值      (usize, bool)，(已经找到的条目数, 是否遍历完成)
+     */
+    pub fn get_status(&self) -> (usize, bool) {
+        let count = self.entries.lock().map(|entries| entries.len()).unwrap_or(0);
+        (count, self.done.load(Ordering::Relaxed))
+    }
+
+    /*
+     * @概述        按分数从高到低返回匹配的条目
+     * @参数1       &str，输入的内容，没有大写字母时忽略大小写，为空时按遍历顺序返回
+     * @参数2       usize，最多返回的数量
+     * @返回值      Vec<FinderMatch>
+     */
+    pub fn search(&self, query: &str, limit: usize) -> Vec<FinderMatch> {
+        let entries = match self.entries.lock() {
+            Ok(entries) => entries,
+            Err(_) => return Vec::new(),
+        };
+        if query.is_empty() {
+            return entries
+                .iter()
+                .take(limit)
+                .map(|(path, is_dir)| FinderMatch { path: path.clone(), is_dir: *is_dir, positions: Vec::new(), score: 0 })
+                .collect();
+        }
+
+        let ignore_case = !query.chars().any(|ch| ch.is_uppercase());
+        let query: Vec<char> = if ignore_case { query.to_lowercase().chars().collect() } else { query.chars().collect() };
+        let mut matches: Vec<FinderMatch> = entries
+            .iter()
+            .filter_map(|(path, is_dir)| {
+                let (score, positions) = fuzzy_score(&query, &path.to_string_lossy(), ignore_case)?;
+                Some(FinderMatch { path: path.clone(), is_dir: *is_dir, positions, score })
+            })
+            .collect();
+        matches.sort_by(|a, b| {
+            b.score.cmp(&a.score).then_with(|| a.path.as_os_str().len().cmp(&b.path.as_os_str().len()))
+        });
+        matches.truncate(limit);
+        matches
+    }
+}
+
+impl Drop for Finder {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/*
+ * @概述        计算模糊匹配的分数，优先在文件名里匹配，不行再在整个路径里匹配
+ * @参数1       &[char]，输入的字符，忽略大小写时已经转为小写
+ * @参数2       &str，相对路径
+ * @参数3       bool，忽略大小写
+ * @返回值      Option<(i64, Vec<usize>)>，(分数, 匹配的字符位置)，不匹配时返回None
+ */
+fn fuzzy_score(query: &[char], path: &str, ignore_case: bool) -> Option<(i64, Vec<usize>)> {
+    let chars: Vec<char> = path.chars().collect();
+    let name_start = chars.iter().rposition(|ch| *ch == '/').map(|pos| pos + 1).unwrap_or(0);
+    let positions = match_from(query, &chars, name_start, ignore_case)
+        .or_else(|| match_from(query, &chars, 0, ignore_case))?;
+
+    let mut score: i64 = 0;
+    let mut last: Option<usize> = None;
+    for &pos in &positions {
+        score += 1;
+        if last.is_some_and(|last| last + 1 == pos) {
+            score += 8;
+        }
+        let is_boundary = pos == 0
+            || matches!(chars[pos - 1], '/' | '_' | '-' | '.' | ' ')
+            || (chars[pos - 1].is_lowercase() && chars[pos].is_uppercase());
+        if is_boundary {
+            score += 6;
+        }
+        if pos >= name_start {
+            score += 4;
+        }
+        last = Some(pos);
+    }
+    score -= chars.len() as i64 / 8;
+    Some((score, positions))
+}
+
+/*
+ * @概述        从start开始按顺序找到每个字符，返回每个字符第一次出现的位置
+ */
+fn match_from(query: &[char], chars: &[char], start: usize, ignore_case: bool) -> Option<Vec<usize>> {
+    let is_same = |ch: char, wanted: char| if ignore_case { ch.to_lowercase().eq(wanted.to_lowercase()) } else { ch == wanted };
+    let mut positions = Vec::with_capacity(query.len());
+    let mut from = start;
+    for &wanted in query {
+        let pos = (from..chars.len()).find(|&pos| is_same(chars[pos], wanted))?;
+        positions.push(pos);
+        from = pos + 1;
+    }
+    Some(positions)
+}
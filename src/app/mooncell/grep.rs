@@ -0,0 +1,208 @@
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::thread;
+
+use regex::{Regex, RegexBuilder};
+
+use super::TopError;
+use super::filter::{FilterMode, ListFilter};
+use super::ignore;
+
+/*
+ * @概述        在文件内容中查找
+ *              后台线程遍历文件夹(遵守.gitignore)，每一行只记录第一个匹配，结果不断追加到results里
+ *              含有\0的文件视为二进制文件，可以选择跳过
+ */
+
+const MAX_RESULTS: usize = 100_000;
+const MAX_FILE_SIZE: u64 = 64 * 1024 * 1024;    // 跳过太大的文件
+const BINARY_CHECK_SIZE: usize = 8192;    // 检查开头这么多字节里是否有\0
+const SNIPPET_BEFORE: usize = 40;    // 片段中匹配前面保留的字节数
+const SNIPPET_AFTER: usize = 120;
+
+pub struct GrepOptions {
+    pub pattern: String,
+    pub use_regex: bool,
+    pub ignore_case: bool,
+    pub globs: Vec<String>,    // 只查找名字匹配的文件，为空时查找所有文件
+    pub skip_binary: bool,
+    pub show_hidden: bool,
+}
+
+/*
+ * @概述        一行中的匹配
+ */
+#[derive(Clone)]
+pub struct GrepResult {
+    pub path: PathBuf,
+    pub line: usize,    // 行号，从1开始
+    pub start: usize,    // 匹配在这一行中的字节位置
+    pub end: usize,
+    pub snippet: String,    // 匹配附近的内容
+    pub snippet_start: usize,    // 匹配在snippet中的字节位置
+    pub snippet_end: usize,
+}
+
+pub struct Grep {
+    root: PathBuf,
+    results: Arc<Mutex<Vec<GrepResult>>>,
+    files_searched: Arc<AtomicUsize>,
+    done: Arc<AtomicBool>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl Grep {
+    /*
+     * @概述        开始在后台查找
+     * @参数1       &Path，开始的文件夹
+     * @参数2       GrepOptions
+     * @返回值      Result<Self, TopError>，正则表达式或者通配符错误时返回Err
+     */
+    pub fn start(root: &Path, options: GrepOptions) -> Result<Self, TopError> {
+        if options.pattern.is_empty() {
+            return Err(TopError::ErrorInformation(String::from("nothing to search for")));
+        }
+        let pattern = if options.use_regex { options.pattern.clone() } else { regex::escape(&options.pattern) };
+        let regex = RegexBuilder::new(&pattern)
+            .case_insensitive(options.ignore_case)
+            .build()
+            .map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+        let globs = options.globs
+            .iter()
+            .map(|glob| ListFilter::new(glob, FilterMode::Glob))
+            .collect::<Result<Vec<ListFilter>, TopError>>()?;
+
+        let results = Arc::new(Mutex::new(Vec::new()));
+        let files_searched = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicBool::new(false));
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let root = root.to_path_buf();
+            let results = Arc::clone(&results);
+            let files_searched = Arc::clone(&files_searched);
+            let done = Arc::clone(&done);
+            let cancel = Arc::clone(&cancel);
+            thread::spawn(move || {
+                let mut count = 0;
+                ignore::walk(&root, options.show_hidden, &cancel, |path, is_dir| {
+                    if is_dir {
+                        return true;
+                    }
+                    let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                    if !globs.is_empty() && !globs.iter().any(|glob| glob.matches(&name).is_some()) {
+                        return true;
+                    }
+                    let found = search_file(path, &regex, options.skip_binary, &cancel);
+                    files_searched.fetch_add(1, Ordering::Relaxed);
+                    count += found.len();
+                    if !found.is_empty()
+                        && let Ok(mut results) = results.lock() {
+                        results.extend(found);
+                    }
+                    count < MAX_RESULTS
+                });
+                done.store(true, Ordering::Relaxed);
+            });
+        }
+        Ok(Self { root: root.to_path_buf(), results, files_searched, done, cancel })
+    }
+
+    pub fn get_root(&self) -> &Path {
+        &self.root
+    }
+
+    /*
+     * @概述        停止查找，已经找到的结果保留
+     */
+    pub fn cancel(&self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+
+    /*
+     * @返回值      (usize, usize, bool)，(查找过的文件数, 结果数, 是否结束)
+     */
+    pub fn get_status(&self) -> (usize, usize, bool) {
+        let count = self.results.lock().map(|results| results.len()).unwrap_or(0);
+        (self.files_searched.load(Ordering::Relaxed), count, self.done.load(Ordering::Relaxed))
+    }
+
+    /*
+     * @概述        取出from之后的新结果
+     * @参数1       usize，已经取过的数量
+     * @返回值      Vec<GrepResult>
+     */
+    pub fn get_results(&self, from: usize) -> Vec<GrepResult> {
+        match self.results.lock() {
+            Ok(results) => results.get(from..).map(|new| new.to_vec()).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        }
+    }
+}
+
+impl Drop for Grep {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+/*
+ * @概述        在一个文件中查找，读取失败的文件直接跳过
+ * @返回值      Vec<GrepResult>
+ */
+fn search_file(path: &Path, regex: &Regex, skip_binary: bool, cancel: &AtomicBool) -> Vec<GrepResult> {
+    let mut found = Vec::new();
+    // 只读普通文件，管道会一直阻塞，设备文件(比如/dev/zero)读不完
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() <= MAX_FILE_SIZE => {}
+        _ => return found,
+    }
+    // 文件在查找时可能变大，最多读MAX_FILE_SIZE，超过的跳过
+    let mut bytes = Vec::new();
+    match File::open(path).and_then(|file| file.take(MAX_FILE_SIZE + 1).read_to_end(&mut bytes)) {
+        Ok(size) if size as u64 <= MAX_FILE_SIZE => {}
+        _ => return found,
+    }
+    if skip_binary && bytes[..bytes.len().min(BINARY_CHECK_SIZE)].contains(&0) {
+        return found;
+    }
+    let content = String::from_utf8_lossy(&bytes);
+    for (i, line) in content.lines().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        if let Some(matched) = regex.find(line) {
+            let (snippet, snippet_start) = make_snippet(line, matched.start(), matched.end());
+            found.push(GrepResult {
+                path: path.to_path_buf(),
+                line: i + 1,
+                start: matched.start(),
+                end: matched.end(),
+                snippet,
+                snippet_start,
+                snippet_end: snippet_start + matched.end() - matched.start(),
+            });
+        }
+    }
+    found
+}
+
+/*
+ * @概述        截取匹配附近的内容，去掉开头的空白，制表符换成空格
+ * @返回值      (String, usize)，(片段, 匹配在片段中的字节位置)
+ */
+fn make_snippet(line: &str, start: usize, end: usize) -> (String, usize) {
+    let mut from = start.saturating_sub(SNIPPET_BEFORE);
+    while !line.is_char_boundary(from) {
+        from -= 1;
+    }
+    let mut to = (end + SNIPPET_AFTER).min(line.len());
+    while !line.is_char_boundary(to) {
+        to += 1;
+    }
+    let before = line[from..start].trim_start();
+    let snippet = format!("{}{}", before, &line[start..to]).replace('\t', " ");
+    (snippet, before.len())
+}
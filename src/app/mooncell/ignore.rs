@@ -0,0 +1,180 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use regex::Regex;
+
+/*
+ * @概述        遍历文件夹，遵守每一层的.gitignore和.ignore
+ *              规则的写法和git相同：#注释、!取反、/结尾只匹配文件夹、包含/时相对规则所在的文件夹匹配
+ *              被忽略的文件夹不会进入，.git文件夹总是跳过，不跟随符号链接
+ */
+
+struct IgnoreRule {
+    regex: Regex,
+    negate: bool,    // !开头，重新包含
+    dir_only: bool,    // /结尾，只匹配文件夹
+    anchored: bool,    // 包含/，匹配相对路径，否则只匹配名字
+    base: PathBuf,    // 规则文件所在的文件夹
+}
+
+const IGNORE_FILES: [&str; 2] = [".gitignore", ".ignore"];
+
+/*
+ * @概述        从root开始深度优先遍历，每个条目调用一次visit
+ * @参数1       &Path，开始的文件夹，自身不会传给visit
+ * @参数2       bool，是否包括以.开头的文件
+ * @参数3       &AtomicBool，为true时停止遍历
+ * @参数4       FnMut(&Path, bool) -> bool，(路径, 是否为文件夹)，返回false时停止遍历
+ */
+pub fn walk(root: &Path, show_hidden: bool, cancel: &AtomicBool, mut visit: impl FnMut(&Path, bool) -> bool) {
+    let mut rules = Vec::new();
+    walk_dir(root, show_hidden, cancel, &mut rules, &mut visit);
+}
+
+/*
+ * @返回值      bool，是否继续遍历
+ */
+fn walk_dir(dir: &Path, show_hidden: bool, cancel: &AtomicBool, rules: &mut Vec<IgnoreRule>, visit: &mut impl FnMut(&Path, bool) -> bool) -> bool {
+    let rules_len = rules.len();
+    for file in IGNORE_FILES {
+        if let Ok(content) = fs::read_to_string(dir.join(file)) {
+            rules.extend(content.lines().filter_map(|line| parse_rule(line, dir)));
+        }
+    }
+
+    let mut entries: Vec<(PathBuf, bool)> = match fs::read_dir(dir) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| {
+                let is_dir = entry.file_type().map(|file_type| file_type.is_dir()).unwrap_or(false);
+                (entry.path(), is_dir)
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+    entries.sort();
+
+    let mut is_continue = true;
+    for (path, is_dir) in entries {
+        if cancel.load(Ordering::Relaxed) {
+            is_continue = false;
+            break;
+        }
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        if name == ".git" || (!show_hidden && name.starts_with('.')) || is_ignored(rules, &path, &name, is_dir) {
+            continue;
+        }
+        if !visit(&path, is_dir) || (is_dir && !walk_dir(&path, show_hidden, cancel, rules, visit)) {
+            is_continue = false;
+            break;
+        }
+    }
+    rules.truncate(rules_len);
+    is_continue
+}
+
+/*
+ * @概述        最后一条匹配的规则决定是否忽略，子文件夹的规则在后面
+ */
+fn is_ignored(rules: &[IgnoreRule], path: &Path, name: &str, is_dir: bool) -> bool {
+    let mut ignored = false;
+    for rule in rules {
+        if rule.dir_only && !is_dir {
+            continue;
+        }
+        let is_match = if rule.anchored {
+            match path.strip_prefix(&rule.base) {
+                Ok(relative) => rule.regex.is_match(&relative.to_string_lossy()),
+                Err(_) => false,
+            }
+        } else {
+            rule.regex.is_match(name)
+        };
+        if is_match {
+            ignored = !rule.negate;
+        }
+    }
+    ignored
+}
+
+/*
+ * @概述        解析.gitignore中的一行
+ * @参数1       &str
+ * @参数2       &Path，规则文件所在的文件夹
+ * @返回值      Option<IgnoreRule>，空行和注释返回None
+ */
+fn parse_rule(line: &str, base: &Path) -> Option<IgnoreRule> {
+    let mut pattern = line.trim_end();
+    if pattern.is_empty() || pattern.starts_with('#') {
+        return None;
+    }
+    let negate = pattern.starts_with('!');
+    // \#和\!表示以#、!开头的名字
+    if negate || pattern.starts_with("\\#") || pattern.starts_with("\\!") {
+        pattern = &pattern[1..];
+    }
+    let dir_only = pattern.ends_with('/');
+    let pattern = pattern.trim_end_matches('/');
+    let anchored = pattern.contains('/');
+    let pattern = pattern.trim_start_matches('/');
+    if pattern.is_empty() {
+        return None;
+    }
+
+    let regex = Regex::new(&format!("^{}$", pattern_to_regex(pattern))).ok()?;
+    Some(IgnoreRule { regex, negate, dir_only, anchored, base: base.to_path_buf() })
+}
+
+/*
+ * @概述        把gitignore的通配符转换为正则表达式，*不匹配/，**匹配任意层文件夹
+ */
+fn pattern_to_regex(pattern: &str) -> String {
+    let mut regex = String::new();
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                let at_start = i == 0 || chars[i - 1] == '/';
+                match chars.get(i + 2) {
+                    Some('/') if at_start => {
+                        regex.push_str("(?:.*/)?");
+                        i += 3;
+                    }
+                    _ => {
+                        regex.push_str(".*");
+                        i += 2;
+                    }
+                }
+                continue;
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let mut class = String::from("[");
+                i += 1;
+                while i < chars.len() && chars[i] != ']' {
+                    match chars[i] {
+                        '!' if class.len() == 1 => class.push('^'),
+                        '\\' | '[' => {
+                            class.push('\\');
+                            class.push(chars[i]);
+                        }
+                        ch => class.push(ch),
+                    }
+                    i += 1;
+                }
+                class.push(']');
+                regex.push_str(&class);
+            }
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&regex::escape(&chars[i].to_string()));
+            }
+            ch => regex.push_str(&regex::escape(&ch.to_string())),
+        }
+        i += 1;
+    }
+    regex
+}
@@ -1,7 +1,11 @@
+use std::fs;
 use std::fs::File;
 use std::io::Read;
 use sysinfo::Disks;
-use std::path::Path;
+use std::ffi::CString;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::os::unix::ffi::OsStrExt;
 use sysinfo::System;
 use std::process::Command;
 use chrono::{DateTime, Local};
@@ -22,7 +26,7 @@ pub struct CpuInfo {
     pub temp: f32,    // 温度
     pub power: f32,     // 功耗
     pub name: String,    // 名称
-    pub siblings: u8,    // 核心数
+    pub siblings: usize,    // 逻辑cpu数
     pub usage: Vec<f32>,     // 0:总的占用率，剩下的为每个核心的占用率
     pub usage_history: Vec<u64>,    // CPU总占用率历史记录，只保留50条记录
 }
@@ -39,14 +43,26 @@ pub struct DiskInfo {
     pub name: String,
     pub all_space: f64,    // 总空间
     pub available_space: f64,    // 可用空间
+    pub mount_point: PathBuf,    // 挂载点
+    pub file_system: String,    // 文件系统类型
+    pub mount_options: String,    // 挂载选项
+    pub removable: bool,    // 是否为可移动设备
+    pub inodes_total: u64,    // inode总数
+    pub inodes_free: u64,    // 可用inode数
 }
 
 impl Clone for DiskInfo {
     fn clone(&self) -> Self {
         Self {
             name: self.name.clone(),
-            all_space: self.all_space.clone(),
-            available_space: self.available_space.clone()
+            all_space: self.all_space,
+            available_space: self.available_space,
+            mount_point: self.mount_point.clone(),
+            file_system: self.file_system.clone(),
+            mount_options: self.mount_options.clone(),
+            removable: self.removable,
+            inodes_total: self.inodes_total,
+            inodes_free: self.inodes_free,
         }
     }
 }
@@ -113,6 +129,7 @@ impl Info {
     pub fn refresh_disks(&mut self) {
         self.disks.clear();
         let disks = Disks::new_with_refreshed_list();
+        let mount_options = Self::read_mount_options();
 
         for disk in disks.list() {
             let mut data = DiskInfo::new();
@@ -131,10 +148,77 @@ impl Info {
             data.all_space = total as f64 / (1024.0 * 1024.0 * 1024.0);
             data.available_space = available as f64 / (1024.0 * 1024.0 * 1024.0);
 
+            // 挂载信息
+            data.mount_point = disk.mount_point().to_path_buf();
+            data.file_system = disk.file_system().to_string_lossy().to_string();
+            data.removable = disk.is_removable();
+            if let Some(options) = mount_options.get(&data.mount_point) {
+                data.mount_options = options.clone();
+            }
+            if let Some((total, free)) = Self::statvfs_inodes(&data.mount_point) {
+                data.inodes_total = total;
+                data.inodes_free = free;
+            }
+
             self.disks.push(data);
         }
     }
 
+    /*
+     * @概述        读取/proc/mounts，获取每个挂载点的挂载选项
+     * @返回值      HashMap<PathBuf, String>，挂载点 -> 挂载选项
+     */
+    pub fn read_mount_options() -> HashMap<PathBuf, String> {
+        let mut map: HashMap<PathBuf, String> = HashMap::new();
+        if let Ok(content) = fs::read_to_string("/proc/mounts") {
+            // 设备 挂载点 文件系统 选项 dump pass
+            for line in content.lines() {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                if fields.len() >= 4 {
+                    map.insert(PathBuf::from(Self::unescape_mount_field(fields[1])), fields[3].to_string());
+                }
+            }
+        }
+        map
+    }
+
+    /*
+     * @概述        /proc/mounts中空格等字符被写成\040这样的八进制转义，还原成原字符
+     * @参数1       &str
+     * @返回值      String
+     */
+    fn unescape_mount_field(field: &str) -> String {
+        let bytes = field.as_bytes();
+        let mut out: Vec<u8> = Vec::new();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'\\' && i + 3 < bytes.len()
+                && let Ok(ch) = u8::from_str_radix(&field[i + 1..i + 4], 8) {
+                out.push(ch);
+                i += 4;
+                continue;
+            }
+            out.push(bytes[i]);
+            i += 1;
+        }
+        String::from_utf8_lossy(&out).to_string()
+    }
+
+    /*
+     * @概述        调用statvfs获取inode使用情况
+     * @参数1       &Path，挂载点
+     * @返回值      Option<(u64, u64)>，(inode总数, 可用inode数)
+     */
+    fn statvfs_inodes(path: &Path) -> Option<(u64, u64)> {
+        let path_c = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+        // SAFETY: path_c是合法的C字符串，stat由调用方分配
+        if unsafe { libc::statvfs(path_c.as_ptr(), &mut stat) } != 0 {
+            return None;
+        }
+        Some((stat.f_files as u64, stat.f_ffree as u64))
+    }
+
     /*
      * @概述        刷新内存数据
      */
@@ -171,17 +255,15 @@ impl Info {
 impl CpuInfo {
     fn new() -> Self {
         let sys = System::new_all();
-        let mut count: u8  = 0;
-        let mut name_str = String::new();
-        for cpu in sys.cpus() {
-            name_str = cpu.brand().to_string();
-            count = count + 1;
-        }
+        let name_str = match sys.cpus().first() {
+            Some(cpu) => cpu.brand().to_string(),
+            None => String::new(),
+        };
 
         Self {
             temp: 0.0,
             power: 0.0,
-            siblings: count,
+            siblings: sys.cpus().len(),
             name: name_str,
             usage: Vec::new(),
             usage_history: Vec::new(),
@@ -286,6 +368,37 @@ impl DiskInfo {
             name: String::new(),
             all_space: 0.0,
             available_space: 0.0,
+            mount_point: PathBuf::new(),
+            file_system: String::new(),
+            mount_options: String::new(),
+            removable: false,
+            inodes_total: 0,
+            inodes_free: 0,
+        }
+    }
+
+    /*
+     * @概述        已使用空间的百分比
+     * @返回值      u64
+     */
+    pub fn usage_percent(&self) -> u64 {
+        if self.all_space == 0.0 {
+            0
+        } else {
+            let used = self.all_space - self.available_space;
+            ((used * 100.0) / self.all_space).min(100.0) as u64
+        }
+    }
+
+    /*
+     * @概述        已使用inode的百分比，文件系统不支持时返回None
+     * @返回值      Option<u64>
+     */
+    pub fn inode_usage_percent(&self) -> Option<u64> {
+        if self.inodes_total == 0 {
+            return None;
         }
+        let used = self.inodes_total - self.inodes_free.min(self.inodes_total);
+        Some(used * 100 / self.inodes_total)
     }
 }
\ No newline at end of file
@@ -0,0 +1,349 @@
+use std::fs;
+use std::path::Path;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/*
+ * @概述        从sysfs收集硬件清单：cpu拓扑、缓存、pci设备、usb设备以及dmi信息
+ *              to_text可以直接导出成文本，用于提交工单
+ */
+
+pub struct Inventory {
+    pub sockets: usize,    // 物理cpu数量
+    pub cores: usize,    // 物理核心数
+    pub threads: usize,    // 逻辑cpu数
+    pub numa_nodes: usize,    // numa节点数
+    pub caches: Vec<CacheInfo>,
+    pub pci_devices: Vec<PciDevice>,
+    pub usb_devices: Vec<UsbDevice>,
+    pub dmi: Vec<(String, String)>,    // (字段, 值)
+}
+
+pub struct CacheInfo {
+    pub level: u8,
+    pub cache_type: String,    // Data、Instruction、Unified
+    pub size: String,    // 每个实例的大小，如 32K
+    pub instances: usize,    // 实例数量
+}
+
+pub struct PciDevice {
+    pub address: String,    // 如 0000:00:02.0
+    pub vendor_id: String,
+    pub device_id: String,
+    pub class: String,    // 设备类别名
+    pub name: String,    // 厂商+设备名，找不到pci.ids时为空
+}
+
+pub struct UsbDevice {
+    pub bus: String,
+    pub device: String,
+    pub vendor_id: String,
+    pub product_id: String,
+    pub name: String,    // 厂商+产品名
+}
+
+const CPU_PATH: &str = "/sys/devices/system/cpu";
+const NODE_PATH: &str = "/sys/devices/system/node";
+const PCI_PATH: &str = "/sys/bus/pci/devices";
+const USB_PATH: &str = "/sys/bus/usb/devices";
+const DMI_PATH: &str = "/sys/class/dmi/id";
+const PCI_IDS_PATHS: [&str; 3] = ["/usr/share/hwdata/pci.ids", "/usr/share/misc/pci.ids", "/usr/share/pci.ids"];
+
+impl Inventory {
+    /*
+     * @概述        读取sysfs生成硬件清单，读取失败的部分留空
+     */
+    pub fn collect() -> Self {
+        let (sockets, cores, threads) = Self::read_topology();
+        let pci_ids = PciIds::load();
+        Self {
+            sockets,
+            cores,
+            threads,
+            numa_nodes: Self::count_numbered_entries(NODE_PATH, "node"),
+            caches: Self::read_caches(),
+            pci_devices: Self::read_pci_devices(&pci_ids),
+            usb_devices: Self::read_usb_devices(),
+            dmi: Self::read_dmi(),
+        }
+    }
+
+    /*
+     * @概述        生成文本形式的硬件清单
+     * @返回值      String
+     */
+    pub fn to_text(&self) -> String {
+        let mut str = String::new();
+
+        str.push_str("[CPU]\n");
+        str.push_str(&format!("sockets: {}\ncores: {}\nthreads: {}\nnuma nodes: {}\n", self.sockets, self.cores, self.threads, self.numa_nodes));
+
+        str.push_str("\n[Cache]\n");
+        for cache in &self.caches {
+            str.push_str(&format!("L{} {}: {} x {}\n", cache.level, cache.cache_type, cache.size, cache.instances));
+        }
+
+        str.push_str("\n[PCI]\n");
+        for device in &self.pci_devices {
+            str.push_str(&format!("{} [{}:{}] {}", device.address, device.vendor_id, device.device_id, device.class));
+            if !device.name.is_empty() {
+                str.push_str(&format!(": {}", device.name));
+            }
+            str.push('\n');
+        }
+
+        str.push_str("\n[USB]\n");
+        for device in &self.usb_devices {
+            str.push_str(&format!("bus {} device {} [{}:{}] {}\n", device.bus, device.device, device.vendor_id, device.product_id, device.name));
+        }
+
+        str.push_str("\n[DMI]\n");
+        for (key, value) in &self.dmi {
+            str.push_str(&format!("{}: {}\n", key, value));
+        }
+        str
+    }
+
+    /*
+     * @概述        统计物理cpu、物理核心和逻辑cpu的数量
+     * @返回值      (usize, usize, usize)
+     */
+    fn read_topology() -> (usize, usize, usize) {
+        let mut packages: BTreeSet<String> = BTreeSet::new();
+        let mut cores: BTreeSet<(String, String)> = BTreeSet::new();
+        let mut threads = 0;
+
+        for cpu in Self::numbered_entries(CPU_PATH, "cpu") {
+            let topology = Path::new(CPU_PATH).join(&cpu).join("topology");
+            let package = read_trim(&topology.join("physical_package_id")).unwrap_or_default();
+            let core = read_trim(&topology.join("core_id")).unwrap_or_else(|| cpu.clone());
+            packages.insert(package.clone());
+            cores.insert((package, core));
+            threads += 1;
+        }
+        (packages.len(), cores.len(), threads)
+    }
+
+    /*
+     * @概述        读取每个cpu的缓存，按shared_cpu_list去重后统计实例数量
+     * @返回值      Vec<CacheInfo>
+     */
+    fn read_caches() -> Vec<CacheInfo> {
+        // (level, type, size) -> 共享该缓存的cpu列表集合
+        let mut caches: BTreeMap<(u8, String, String), BTreeSet<String>> = BTreeMap::new();
+
+        for cpu in Self::numbered_entries(CPU_PATH, "cpu") {
+            let cache_path = Path::new(CPU_PATH).join(&cpu).join("cache");
+            for index in Self::numbered_entries(&cache_path.to_string_lossy(), "index") {
+                let index_path = cache_path.join(&index);
+                let level = match read_trim(&index_path.join("level")).and_then(|str| str.parse::<u8>().ok()) {
+                    Some(level) => level,
+                    None => continue,
+                };
+                let cache_type = read_trim(&index_path.join("type")).unwrap_or_default();
+                let size = read_trim(&index_path.join("size")).unwrap_or_default();
+                let shared = read_trim(&index_path.join("shared_cpu_list")).unwrap_or_else(|| cpu.clone());
+                caches.entry((level, cache_type, size)).or_default().insert(shared);
+            }
+        }
+
+        caches
+            .into_iter()
+            .map(|((level, cache_type, size), shared)| CacheInfo { level, cache_type, size, instances: shared.len() })
+            .collect()
+    }
+
+    fn read_pci_devices(pci_ids: &PciIds) -> Vec<PciDevice> {
+        let mut devices = Vec::new();
+        for address in Self::sorted_entries(PCI_PATH) {
+            let path = Path::new(PCI_PATH).join(&address);
+            let vendor_id = read_hex_id(&path.join("vendor"));
+            let device_id = read_hex_id(&path.join("device"));
+            // class形如0x030000，前两位是大类，中间两位是子类
+            let class_code = read_hex_id(&path.join("class"));
+            let class = match pci_ids.class_name(&class_code) {
+                Some(name) => name,
+                None => format!("class {}", class_code),
+            };
+            devices.push(PciDevice {
+                address,
+                name: pci_ids.device_name(&vendor_id, &device_id),
+                vendor_id,
+                device_id,
+                class,
+            });
+        }
+        devices
+    }
+
+    fn read_usb_devices() -> Vec<UsbDevice> {
+        let mut devices = Vec::new();
+        for entry in Self::sorted_entries(USB_PATH) {
+            let path = Path::new(USB_PATH).join(&entry);
+            // 接口目录没有idVendor
+            let vendor_id = match read_trim(&path.join("idVendor")) {
+                Some(id) => id,
+                None => continue,
+            };
+            let name = [read_trim(&path.join("manufacturer")), read_trim(&path.join("product"))]
+                .into_iter()
+                .flatten()
+                .collect::<Vec<String>>()
+                .join(" ");
+            devices.push(UsbDevice {
+                bus: read_trim(&path.join("busnum")).unwrap_or_default(),
+                device: read_trim(&path.join("devnum")).unwrap_or_default(),
+                vendor_id,
+                product_id: read_trim(&path.join("idProduct")).unwrap_or_default(),
+                name,
+            });
+        }
+        devices
+    }
+
+    fn read_dmi() -> Vec<(String, String)> {
+        let keys = [
+            "sys_vendor", "product_name", "product_version",
+            "board_vendor", "board_name", "board_version",
+            "bios_vendor", "bios_version", "bios_date",
+        ];
+        let mut dmi = Vec::new();
+        for key in keys {
+            if let Some(value) = read_trim(&Path::new(DMI_PATH).join(key)) {
+                dmi.push((key.to_string(), value));
+            }
+        }
+        dmi
+    }
+
+    /*
+     * @概述        列出目录下形如 <prefix><数字> 的条目，按数字排序
+     * @参数1       &str，目录
+     * @参数2       &str，前缀
+     * @返回值      Vec<String>
+     */
+    fn numbered_entries(path: &str, prefix: &str) -> Vec<String> {
+        let mut entries: Vec<(usize, String)> = Vec::new();
+        if let Ok(dir) = fs::read_dir(path) {
+            for entry in dir.flatten() {
+                let name = entry.file_name().to_string_lossy().to_string();
+                if let Some(number) = name.strip_prefix(prefix)
+                    && let Ok(number) = number.parse::<usize>() {
+                    entries.push((number, name));
+                }
+            }
+        }
+        entries.sort();
+        entries.into_iter().map(|(_, name)| name).collect()
+    }
+
+    fn count_numbered_entries(path: &str, prefix: &str) -> usize {
+        Self::numbered_entries(path, prefix).len()
+    }
+
+    fn sorted_entries(path: &str) -> Vec<String> {
+        let mut entries: Vec<String> = match fs::read_dir(path) {
+            Ok(dir) => dir.flatten().map(|entry| entry.file_name().to_string_lossy().to_string()).collect(),
+            Err(_) => Vec::new(),
+        };
+        entries.sort();
+        entries
+    }
+}
+
+/*
+ * @概述        pci.ids数据库中用到的部分：厂商、设备和设备类别的名称
+ */
+struct PciIds {
+    vendors: HashMap<String, String>,
+    devices: HashMap<(String, String), String>,
+    classes: HashMap<String, String>,    // 大类如 03，大类+子类如 0300
+}
+
+impl PciIds {
+    /*
+     * @概述        读取系统中的pci.ids，找不到时返回空表
+     *              格式：厂商行无缩进，设备行缩进一个tab，C开头的是类别，子类缩进一个tab
+     */
+    fn load() -> Self {
+        let mut ids = Self { vendors: HashMap::new(), devices: HashMap::new(), classes: HashMap::new() };
+        let content = match PCI_IDS_PATHS.iter().find_map(|path| fs::read(path).ok()) {
+            Some(bytes) => String::from_utf8_lossy(&bytes).to_string(),
+            None => return ids,
+        };
+
+        let mut vendor = String::new();
+        let mut class = String::new();
+        let mut in_class = false;
+        for line in content.lines() {
+            if line.starts_with('#') || line.trim().is_empty() {
+                continue;
+            }
+            if let Some(rest) = line.strip_prefix("C ") {
+                if let Some((id, name)) = rest.split_once("  ") {
+                    class = id.to_lowercase();
+                    ids.classes.insert(class.clone(), name.to_string());
+                }
+                in_class = true;
+            } else if !line.starts_with('\t') {
+                if let Some((id, name)) = line.split_once("  ") {
+                    vendor = id.to_lowercase();
+                    ids.vendors.insert(vendor.clone(), name.to_string());
+                }
+                in_class = false;
+            } else if !line.starts_with("\t\t")
+                && let Some((id, name)) = line.trim_start().split_once("  ") {
+                if in_class {
+                    ids.classes.insert(format!("{}{}", class, id.to_lowercase()), name.to_string());
+                } else {
+                    ids.devices.insert((vendor.clone(), id.to_lowercase()), name.to_string());
+                }
+            }
+        }
+        ids
+    }
+
+    fn device_name(&self, vendor_id: &str, device_id: &str) -> String {
+        let vendor = self.vendors.get(vendor_id).cloned().unwrap_or_default();
+        match self.devices.get(&(vendor_id.to_string(), device_id.to_string())) {
+            Some(device) => format!("{} {}", vendor, device),
+            None => vendor,
+        }
+    }
+
+    /*
+     * @概述        按类别代码查找名称，先找子类再找大类
+     * @参数1       &str，如 030000
+     * @返回值      Option<String>
+     */
+    fn class_name(&self, class_code: &str) -> Option<String> {
+        let sub_class = class_code.get(0..4)?;
+        self.classes
+            .get(sub_class)
+            .or_else(|| self.classes.get(&class_code[0..2]))
+            .cloned()
+    }
+}
+
+/*
+ * @概述        读取sysfs中的单个值
+ * @参数1       &Path
+ * @返回值      Option<String>，文件不存在或为空时返回None
+ */
+fn read_trim(path: &Path) -> Option<String> {
+    let str = fs::read_to_string(path).ok()?;
+    let str = str.trim();
+    if str.is_empty() {
+        return None;
+    }
+    Some(str.to_string())
+}
+
+/*
+ * @概述        读取形如0x8086的id，去掉0x前缀并转成小写
+ */
+fn read_hex_id(path: &Path) -> String {
+    match read_trim(path) {
+        Some(str) => str.trim_start_matches("0x").to_lowercase(),
+        None => String::new(),
+    }
+}
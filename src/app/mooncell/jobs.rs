@@ -0,0 +1,342 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::Ordering;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use super::fileops::{self, ConflictPlan, Control, OperateReport, Progress};
+
+// 在后台线程中执行的操作
+pub type JobTask = Box<dyn FnOnce(&Mutex<Progress>, &Control) -> OperateReport + Send>;
+
+/*
+ * @概述        后台文件操作队列
+ *              每个Job在单独的线程里执行fileops中的操作，界面通过JobSnapshot读取进度
+ *              结束的Job保留在列表中作为历史记录
+ */
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobKind {
+    Copy,
+    Move,
+    Trash,
+    Delete,
+    Rename,
+    Undo,
+    Redo,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobState {
+    Running,
+    Paused,
+    Done,
+    Failed,    // 完成但有错误
+    Cancelled,
+}
+
+pub struct Job {
+    id: usize,
+    kind: JobKind,
+    sources: Vec<PathBuf>,
+    target: Option<PathBuf>,    // 复制/移动的目标文件夹
+    progress: Arc<Mutex<Progress>>,
+    control: Arc<Control>,
+    handle: Option<JoinHandle<OperateReport>>,    // 结束后被取走
+    report: Option<OperateReport>,    // 结束后的结果
+    start_time: Instant,
+    end_time: Option<Instant>,
+}
+
+/*
+ * @概述        供界面显示的Job状态
+ */
+pub struct JobSnapshot {
+    pub id: usize,
+    pub kind: JobKind,
+    pub state: JobState,
+    pub target: Option<PathBuf>,
+    pub source_count: usize,
+    pub done: usize,    // 成功处理的条目数，结束后才有效
+    pub skipped: usize,    // 因为冲突跳过的条目数，结束后才有效
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    pub items_done: u64,
+    pub items_total: u64,
+    pub current: PathBuf,
+    pub speed: f64,    // 字节/秒
+    pub eta: Option<Duration>,    // 预计剩余时间
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+/*
+ * @概述        刚结束的Job，交给日志记录
+ */
+pub struct FinishedJob {
+    pub snapshot: JobSnapshot,
+    pub sources: Vec<PathBuf>,
+    pub report: OperateReport,
+}
+
+pub struct JobManager {
+    jobs: Vec<Job>,
+    next_id: usize,
+}
+
+impl JobKind {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            JobKind::Copy => "copy",
+            JobKind::Move => "move",
+            JobKind::Trash => "trash",
+            JobKind::Delete => "delete",
+            JobKind::Rename => "rename",
+            JobKind::Undo => "undo",
+            JobKind::Redo => "redo",
+        }
+    }
+}
+
+impl JobState {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            JobState::Running => "running",
+            JobState::Paused => "paused",
+            JobState::Done => "done",
+            JobState::Failed => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobState::Done | JobState::Failed | JobState::Cancelled)
+    }
+}
+
+impl Job {
+    fn state(&self) -> JobState {
+        match &self.report {
+            Some(report) if report.cancelled => JobState::Cancelled,
+            Some(report) if !report.errors.is_empty() => JobState::Failed,
+            Some(_) => JobState::Done,
+            None if self.control.paused.load(Ordering::Relaxed) => JobState::Paused,
+            None => JobState::Running,
+        }
+    }
+
+    fn snapshot(&self) -> JobSnapshot {
+        let (bytes_done, bytes_total, items_done, items_total, current, paused_time) = match self.progress.lock() {
+            Ok(progress) => (
+                progress.bytes_done,
+                progress.bytes_total,
+                progress.items_done,
+                progress.items_total,
+                progress.current.clone(),
+                progress.paused_time,
+            ),
+            Err(_) => (0, 0, 0, 0, PathBuf::new(), Duration::ZERO),
+        };
+
+        let elapsed = self.end_time.unwrap_or_else(Instant::now).duration_since(self.start_time);
+        let active_secs = elapsed.saturating_sub(paused_time).as_secs_f64();
+        let speed = if active_secs > 0.0 { bytes_done as f64 / active_secs } else { 0.0 };
+        let eta = if self.report.is_none() && speed > 0.0 {
+            Some(Duration::from_secs_f64(bytes_total.saturating_sub(bytes_done) as f64 / speed))
+        } else {
+            None
+        };
+
+        JobSnapshot {
+            id: self.id,
+            kind: self.kind,
+            state: self.state(),
+            target: self.target.clone(),
+            source_count: self.sources.len(),
+            done: match &self.report {
+                Some(report) => report.done,
+                None => 0,
+            },
+            skipped: match &self.report {
+                Some(report) => report.skipped,
+                None => 0,
+            },
+            bytes_done,
+            bytes_total,
+            items_done,
+            items_total,
+            current,
+            speed,
+            eta,
+            errors: match &self.report {
+                Some(report) => report.errors.clone(),
+                None => Vec::new(),
+            },
+        }
+    }
+
+    /*
+     * @概述        等待线程结束并保存结果
+     * @返回值      Option<FinishedJob>，已经取过结果时返回None
+     */
+    fn join(&mut self) -> Option<FinishedJob> {
+        let handle = self.handle.take()?;
+        self.report = Some(match handle.join() {
+            Ok(report) => report,
+            Err(_) => {
+                let mut report = OperateReport::new();
+                report.errors.push((PathBuf::new(), String::from("worker thread panicked")));
+                report
+            }
+        });
+        self.end_time = Some(Instant::now());
+        Some(FinishedJob {
+            snapshot: self.snapshot(),
+            sources: self.sources.clone(),
+            report: self.report.clone().unwrap_or_else(OperateReport::new),
+        })
+    }
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self { jobs: Vec::new(), next_id: 1 }
+    }
+
+    /*
+     * @概述        新建一个Job并立即在后台线程开始执行
+     * @参数1       JobKind
+     * @参数2       Vec<PathBuf>，需要操作的文件
+     * @参数3       Option<PathBuf>，复制/移动的目标文件夹
+     * @参数4       ConflictPlan，目标已经存在时的处理方式
+     * @返回值      usize，job id
+     */
+    pub fn push(&mut self, kind: JobKind, sources: Vec<PathBuf>, target: Option<PathBuf>, plan: ConflictPlan) -> usize {
+        let task_sources = sources.clone();
+        let task_target = target.clone();
+        let task: JobTask = Box::new(move |progress, control| {
+            fileops::measure(&task_sources, progress);
+            match (kind, task_target) {
+                (JobKind::Copy, Some(target)) => fileops::copy_into(&task_sources, &target, &plan, progress, control),
+                (JobKind::Move, Some(target)) => fileops::move_into(&task_sources, &target, &plan, progress, control),
+                (JobKind::Trash, _) => fileops::trash(&task_sources, progress, control),
+                (JobKind::Delete, _) => fileops::delete(&task_sources, progress, control),
+                _ => OperateReport::new(),
+            }
+        });
+        self.push_task(kind, sources, target, task)
+    }
+
+    /*
+     * @概述        新建一个执行任意操作的Job，撤销和重做使用
+     * @参数1       JobKind
+     * @参数2       Vec<PathBuf>，涉及的文件，用于显示
+     * @参数3       Option<PathBuf>，目标文件夹，用于显示
+     * @参数4       JobTask
+     * @返回值      usize，job id
+     */
+    pub fn push_task(&mut self, kind: JobKind, sources: Vec<PathBuf>, target: Option<PathBuf>, task: JobTask) -> usize {
+        let progress = Arc::new(Mutex::new(Progress::new()));
+        let control = Arc::new(Control::new());
+
+        let handle = {
+            let progress = Arc::clone(&progress);
+            let control = Arc::clone(&control);
+            thread::spawn(move || task(&progress, &control))
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id,
+            kind,
+            sources,
+            target,
+            progress,
+            control,
+            handle: Some(handle),
+            report: None,
+            start_time: Instant::now(),
+            end_time: None,
+        });
+        id
+    }
+
+    /*
+     * @概述        检查已经结束的线程，取回结果
+     * @返回值      Vec<FinishedJob>，本次新结束的Job
+     */
+    pub fn poll(&mut self) -> Vec<FinishedJob> {
+        let mut finished = Vec::new();
+        for job in self.jobs.iter_mut() {
+            let is_finished = match &job.handle {
+                Some(handle) => handle.is_finished(),
+                None => false,
+            };
+            if !is_finished {
+                continue;
+            }
+            finished.extend(job.join());
+        }
+        finished
+    }
+
+    /*
+     * @概述        暂停/继续
+     * @参数1       usize，job id
+     */
+    pub fn switch_pause(&mut self, id: usize) {
+        if let Some(job) = self.jobs.iter().find(|job| job.id == id && job.report.is_none()) {
+            let paused = job.control.paused.load(Ordering::Relaxed);
+            job.control.paused.store(!paused, Ordering::Relaxed);
+        }
+    }
+
+    pub fn cancel(&mut self, id: usize) {
+        if let Some(job) = self.jobs.iter().find(|job| job.id == id && job.report.is_none()) {
+            job.control.cancelled.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /*
+     * @概述        清除已经结束的Job
+     */
+    pub fn clear_history(&mut self) {
+        self.jobs.retain(|job| job.report.is_none());
+    }
+
+    /*
+     * @概述        所有Job的状态，进行中的排在前面
+     * @返回值      Vec<JobSnapshot>
+     */
+    pub fn snapshots(&self) -> Vec<JobSnapshot> {
+        let mut running: Vec<JobSnapshot> = Vec::new();
+        let mut history: Vec<JobSnapshot> = Vec::new();
+        for job in &self.jobs {
+            if job.report.is_none() {
+                running.push(job.snapshot());
+            } else {
+                history.push(job.snapshot());
+            }
+        }
+        // 历史记录最新的在前
+        history.reverse();
+        running.append(&mut history);
+        running
+    }
+
+    pub fn running_count(&self) -> usize {
+        self.jobs.iter().filter(|job| job.report.is_none()).count()
+    }
+
+    /*
+     * @概述        退出时取消所有进行中的Job，并等待线程结束
+     * @返回值      Vec<FinishedJob>，和poll一样，取消前已经完成的部分需要记录到操作日志
+     */
+    pub fn cancel_all(&mut self) -> Vec<FinishedJob> {
+        for job in &self.jobs {
+            job.control.paused.store(false, Ordering::Relaxed);
+            job.control.cancelled.store(true, Ordering::Relaxed);
+        }
+        self.jobs.iter_mut().filter_map(Job::join).collect()
+    }
+}
@@ -0,0 +1,576 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{Local, NaiveDateTime};
+
+use super::TopError;
+use super::create::{self, CreateKind};
+use super::fileops::{self, ConflictPlan, OperateReport, Resolution};
+use super::jobs::{JobKind, JobTask};
+use super::permission::{self, PermissionPreview};
+use super::trash::{self, TrashItem};
+
+/*
+ * @概述        文件操作日志，用于撤销和重做
+ *              每个结束的文件操作按照OperateReport中实际创建的路径记录成一个Entry
+ *              新建不经过Job，直接记录新建的路径；修改权限记录每个文件修改前后的权限和所有者
+ *              撤销执行相反的操作，重做按照记录的路径再执行一次原来的操作
+ *              撤销和重做也在后台Job中执行，同一时间只能有一个
+ *              日志保存在$XDG_STATE_HOME/mooncell/journal，重启后仍然可以撤销
+ */
+
+#[derive(Clone)]
+pub enum Action {
+    Copy {
+        pairs: Vec<(PathBuf, PathBuf)>,    // (源路径, 复制出的路径)
+        replaced: Vec<TrashItem>,    // 被覆盖后放进回收站的目标
+    },
+    Move {
+        pairs: Vec<(PathBuf, PathBuf)>,    // (原路径, 新路径)
+        replaced: Vec<TrashItem>,
+    },
+    Trash {
+        items: Vec<TrashItem>,
+    },
+    Delete {
+        paths: Vec<PathBuf>,    // 永久删除，无法撤销
+    },
+    Rename {
+        pairs: Vec<(PathBuf, PathBuf)>,    // (原路径, 新路径)
+    },
+    Create {
+        paths: Vec<PathBuf>,    // 自动创建的父文件夹在前，最后一个是新建的条目
+        kind: CreateKind,
+    },
+    Permission {
+        previews: Vec<PermissionPreview>,    // 成功修改的条目，父文件夹在子条目前面
+    },
+}
+
+#[derive(Clone)]
+pub struct Entry {
+    pub time: NaiveDateTime,
+    pub action: Action,
+}
+
+// 正在执行的撤销/重做
+enum Running {
+    Undo(Entry),    // 成功后放进重做列表
+    Redo(Action),    // 结束后按照结果重新记录
+}
+
+pub struct Journal {
+    undo_list: Vec<Entry>,
+    redo_list: Vec<Entry>,
+    running: Option<(usize, Running)>,    // (job id, 撤销/重做)
+    file: Option<PathBuf>,    // 保存日志的文件
+}
+
+const JOURNAL_LIMIT: usize = 100;
+const TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/*
+ * @概述        保存状态的文件夹，$XDG_STATE_HOME/mooncell，默认~/.local/state/mooncell
+ * @返回值      Option<PathBuf>，没有$HOME时返回None
+ */
+pub fn state_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_STATE_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("mooncell")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state/mooncell")),
+    }
+}
+
+impl Action {
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Copy { pairs, .. } => format!("copy of {} item(s)", pairs.len()),
+            Action::Move { pairs, .. } => format!("move of {} item(s)", pairs.len()),
+            Action::Trash { items } => format!("trash of {} item(s)", items.len()),
+            Action::Delete { paths } => format!("permanent delete of {} item(s)", paths.len()),
+            Action::Rename { pairs } => format!("rename of {} item(s)", pairs.len()),
+            Action::Create { paths, kind } => format!(
+                "new {} {}",
+                kind.to_str(),
+                paths.last().and_then(|path| path.file_name()).map(|name| name.to_string_lossy()).unwrap_or_default(),
+            ),
+            Action::Permission { previews } => format!("permission change of {} item(s)", previews.len()),
+        }
+    }
+
+    /*
+     * @概述        重做时对应的Job类型，直接完成的新建和修改权限没有Job，返回None
+     */
+    fn job_kind(&self) -> Option<JobKind> {
+        match self {
+            Action::Copy { .. } => Some(JobKind::Copy),
+            Action::Move { .. } => Some(JobKind::Move),
+            Action::Trash { .. } => Some(JobKind::Trash),
+            Action::Delete { .. } => Some(JobKind::Delete),
+            Action::Rename { .. } => Some(JobKind::Rename),
+            Action::Create { .. } | Action::Permission { .. } => None,
+        }
+    }
+
+    fn to_str(&self) -> &'static str {
+        match self {
+            Action::Create { .. } => "create",
+            Action::Permission { .. } => "permission",
+            action => action.job_kind().map(JobKind::to_str).unwrap_or_default(),
+        }
+    }
+
+    /*
+     * @概述        根据结束的Job生成记录，没有实际改变任何文件时返回None
+     * @参数1       JobKind
+     * @参数2       &[PathBuf]，Job的源文件
+     * @参数3       &OperateReport
+     * @返回值      Option<Action>
+     */
+    fn from_report(kind: JobKind, sources: &[PathBuf], report: &OperateReport) -> Option<Action> {
+        let action = match kind {
+            JobKind::Copy => Action::Copy { pairs: report.pairs.clone(), replaced: report.replaced.clone() },
+            JobKind::Move => Action::Move { pairs: report.pairs.clone(), replaced: report.replaced.clone() },
+            JobKind::Trash => Action::Trash { items: report.trashed.clone() },
+            JobKind::Delete if report.done > 0 => Action::Delete { paths: sources.to_vec() },
+            JobKind::Rename => Action::Rename { pairs: report.pairs.clone() },
+            _ => return None,
+        };
+        let is_empty = match &action {
+            Action::Copy { pairs, replaced } | Action::Move { pairs, replaced } => pairs.is_empty() && replaced.is_empty(),
+            Action::Trash { items } => items.is_empty(),
+            Action::Rename { pairs } => pairs.is_empty(),
+            Action::Delete { .. } | Action::Create { .. } | Action::Permission { .. } => false,
+        };
+        if is_empty { None } else { Some(action) }
+    }
+
+    /*
+     * @概述        生成撤销的操作
+     * @返回值      (Vec<PathBuf>, JobTask)，(涉及的文件, 操作)
+     */
+    fn undo_task(&self) -> (Vec<PathBuf>, JobTask) {
+        match self.clone() {
+            Action::Copy { pairs, replaced } => {
+                // 复制出的文件放进回收站，再还原被覆盖的文件
+                let targets: Vec<PathBuf> = pairs.into_iter().map(|(_, target)| target).collect();
+                let task_targets = targets.clone();
+                (targets, Box::new(move |progress, control| {
+                    fileops::measure(&task_targets, progress);
+                    let mut report = fileops::trash(&task_targets, progress, control);
+                    report.append(fileops::restore(&replaced, progress, control));
+                    report
+                }))
+            }
+            Action::Move { pairs, replaced } => {
+                // 倒序移回原来的位置，再还原被覆盖的文件
+                let back: Vec<(PathBuf, PathBuf)> = pairs.into_iter().rev().map(|(source, target)| (target, source)).collect();
+                let targets: Vec<PathBuf> = back.iter().map(|(source, _)| source.clone()).collect();
+                (targets.clone(), Box::new(move |progress, control| {
+                    fileops::measure(&targets, progress);
+                    let mut report = fileops::move_pairs(&back, &ConflictPlan::new(), progress, control);
+                    report.append(fileops::restore(&replaced, progress, control));
+                    report
+                }))
+            }
+            Action::Trash { items } => {
+                let paths: Vec<PathBuf> = items.iter().map(|item| item.original_path.clone()).collect();
+                (paths, Box::new(move |progress, control| fileops::restore(&items, progress, control)))
+            }
+            Action::Rename { pairs } => {
+                let back: Vec<(PathBuf, PathBuf)> = pairs.into_iter().map(|(source, target)| (target, source)).collect();
+                let targets: Vec<PathBuf> = back.iter().map(|(source, _)| source.clone()).collect();
+                (targets, Box::new(move |_, _| fileops::rename_pairs(&back)))
+            }
+            Action::Create { paths, .. } => {
+                // 放进回收站，父文件夹已经包含的条目不用单独处理
+                let tops: Vec<PathBuf> = paths
+                    .iter()
+                    .filter(|path| !paths.iter().any(|other| other != *path && path.starts_with(other)))
+                    .cloned()
+                    .collect();
+                let task_tops = tops.clone();
+                (tops, Box::new(move |progress, control| {
+                    fileops::measure(&task_tops, progress);
+                    fileops::trash(&task_tops, progress, control)
+                }))
+            }
+            Action::Permission { previews } => {
+                // apply从后往前执行，倒序后父文件夹先恢复，避免子条目无法访问
+                let back: Vec<PermissionPreview> = previews.iter().rev().map(PermissionPreview::reversed).collect();
+                let paths: Vec<PathBuf> = previews.into_iter().map(|preview| preview.path).collect();
+                (paths, Box::new(move |_, _| permission_report(&back)))
+            }
+            Action::Delete { paths } => (paths, Box::new(|_, _| OperateReport::new())),
+        }
+    }
+
+    /*
+     * @概述        生成重做的操作，被覆盖过的目标再次覆盖
+     * @返回值      (Vec<PathBuf>, JobTask)，(涉及的文件, 操作)
+     */
+    fn redo_task(&self) -> (Vec<PathBuf>, JobTask) {
+        match self.clone() {
+            Action::Copy { pairs, replaced } | Action::Move { pairs, replaced } => {
+                let is_copy = matches!(self, Action::Copy { .. });
+                let plan: ConflictPlan = replaced.into_iter().map(|item| (item.original_path, Resolution::Overwrite)).collect();
+                let sources: Vec<PathBuf> = pairs.iter().map(|(source, _)| source.clone()).collect();
+                (sources.clone(), Box::new(move |progress, control| {
+                    fileops::measure(&sources, progress);
+                    if is_copy {
+                        fileops::copy_pairs(&pairs, &plan, progress, control)
+                    } else {
+                        fileops::move_pairs(&pairs, &plan, progress, control)
+                    }
+                }))
+            }
+            Action::Trash { items } => {
+                let paths: Vec<PathBuf> = items.into_iter().map(|item| item.original_path).collect();
+                let task_paths = paths.clone();
+                (paths, Box::new(move |progress, control| {
+                    fileops::measure(&task_paths, progress);
+                    fileops::trash(&task_paths, progress, control)
+                }))
+            }
+            Action::Rename { pairs } => {
+                let sources: Vec<PathBuf> = pairs.iter().map(|(source, _)| source.clone()).collect();
+                (sources, Box::new(move |_, _| fileops::rename_pairs(&pairs)))
+            }
+            Action::Create { paths, kind } => {
+                let task_paths = paths.clone();
+                (paths, Box::new(move |_, _| {
+                    let mut report = OperateReport::new();
+                    if let Some((path, parents)) = task_paths.split_last() {
+                        for parent in parents {
+                            if let Err(e) = fs::create_dir(parent)
+                                && !parent.is_dir() {
+                                report.errors.push((parent.clone(), e.to_string()));
+                                return report;
+                            }
+                        }
+                        match create::create_entry(&kind, path) {
+                            Ok(_) => report.done += 1,
+                            Err(e) => report.errors.push((path.clone(), e.to_string())),
+                        }
+                    }
+                    report
+                }))
+            }
+            Action::Permission { previews } => {
+                let paths: Vec<PathBuf> = previews.iter().map(|preview| preview.path.clone()).collect();
+                (paths, Box::new(move |_, _| permission_report(&previews)))
+            }
+            Action::Delete { paths } => (paths, Box::new(|_, _| OperateReport::new())),
+        }
+    }
+}
+
+fn permission_report(previews: &[PermissionPreview]) -> OperateReport {
+    let mut report = OperateReport::new();
+    (report.done, report.errors) = permission::apply(previews);
+    report
+}
+
+impl Journal {
+    /*
+     * @概述        读取保存的日志
+     */
+    pub fn load() -> Self {
+        let mut journal = Self {
+            undo_list: Vec::new(),
+            redo_list: Vec::new(),
+            running: None,
+            file: state_dir().map(|dir| dir.join("journal")),
+        };
+        if let Some(file) = &journal.file
+            && let Ok(content) = fs::read_to_string(file) {
+            for line in content.lines() {
+                match Self::parse_line(line) {
+                    Some((true, entry)) => journal.undo_list.push(entry),
+                    Some((false, entry)) => journal.redo_list.push(entry),
+                    None => {}
+                }
+            }
+        }
+        journal
+    }
+
+    /*
+     * @概述        最近一次可以撤销的操作
+     */
+    pub fn last_undo(&self) -> Option<&Entry> {
+        self.undo_list.last()
+    }
+
+    /*
+     * @概述        处理结束的Job：撤销/重做的Job更新列表，其他的记录成新的操作
+     * @参数1       usize，job id
+     * @参数2       JobKind
+     * @参数3       &[PathBuf]，Job的源文件
+     * @参数4       &OperateReport
+     */
+    pub fn finish(&mut self, id: usize, kind: JobKind, sources: &[PathBuf], report: &OperateReport) {
+        match self.running.take() {
+            Some((running_id, Running::Undo(entry))) if running_id == id => {
+                // 只撤销了一部分时无法可靠地重做，丢弃这条记录
+                if report.errors.is_empty() && !report.cancelled {
+                    self.redo_list.push(entry);
+                }
+            }
+            Some((running_id, Running::Redo(action))) if running_id == id => {
+                // 没有Job类型的操作没有可以参考的结果，全部成功时按原样记录
+                let redone = match action.job_kind() {
+                    Some(kind) => Action::from_report(kind, sources, report),
+                    None => (report.errors.is_empty() && !report.cancelled).then_some(action),
+                };
+                if let Some(action) = redone {
+                    self.push_undo(Entry { time: Local::now().naive_local(), action });
+                }
+            }
+            running => {
+                self.running = running;
+                if let Some(action) = Action::from_report(kind, sources, report) {
+                    self.push_undo(Entry { time: Local::now().naive_local(), action });
+                    self.redo_list.clear();
+                }
+            }
+        }
+        self.save();
+    }
+
+    /*
+     * @概述        记录不经过Job、直接完成的操作，例如重命名
+     * @参数1       Action
+     */
+    pub fn record(&mut self, action: Action) {
+        self.push_undo(Entry { time: Local::now().naive_local(), action });
+        self.redo_list.clear();
+        self.save();
+    }
+
+    /*
+     * @概述        取出最近的操作并生成撤销的Job，无法撤销的操作会被移出列表
+     * @返回值      Result<(Entry, Vec<PathBuf>, JobTask), TopError>
+     */
+    pub fn start_undo(&mut self) -> Result<(Entry, Vec<PathBuf>, JobTask), TopError> {
+        if self.running.is_some() {
+            return Err(TopError::ErrorInformation(String::from("wait for the running undo/redo to finish")));
+        }
+        let entry = self.undo_list.pop().ok_or(TopError::ErrorInformation(String::from("nothing to undo")))?;
+        if let Action::Delete { .. } = entry.action {
+            self.save();
+            return Err(TopError::ErrorInformation(format!("can`t undo {}, it is gone", entry.action.describe())));
+        }
+        let (paths, task) = entry.action.undo_task();
+        Ok((entry, paths, task))
+    }
+
+    /*
+     * @概述        取出最近撤销的操作并生成重做的Job
+     * @返回值      Result<(Entry, Vec<PathBuf>, JobTask), TopError>
+     */
+    pub fn start_redo(&mut self) -> Result<(Entry, Vec<PathBuf>, JobTask), TopError> {
+        if self.running.is_some() {
+            return Err(TopError::ErrorInformation(String::from("wait for the running undo/redo to finish")));
+        }
+        let entry = self.redo_list.pop().ok_or(TopError::ErrorInformation(String::from("nothing to redo")))?;
+        let (paths, task) = entry.action.redo_task();
+        Ok((entry, paths, task))
+    }
+
+    /*
+     * @概述        记录已经开始的撤销/重做Job
+     * @参数1       usize，job id
+     * @参数2       Entry
+     * @参数3       bool，true为撤销
+     */
+    pub fn set_running(&mut self, id: usize, entry: Entry, is_undo: bool) {
+        self.running = Some(match is_undo {
+            true => (id, Running::Undo(entry)),
+            false => (id, Running::Redo(entry.action)),
+        });
+        self.save();
+    }
+
+    fn push_undo(&mut self, entry: Entry) {
+        self.undo_list.push(entry);
+        if self.undo_list.len() > JOURNAL_LIMIT {
+            self.undo_list.remove(0);
+        }
+    }
+
+    /*
+     * @概述        保存到文件，每行一条记录，字段用tab分隔，路径按URL规则转义
+     *              第一个字段为undo/redo，然后是时间和操作类型
+     *              之后是带标签的字段：p 源路径 目标路径、t 原路径 回收站 文件名、f 路径、k 新建的类型 链接指向的路径或模板、
+     *              m 路径 类型 原权限 新权限 原uid 新uid 原gid 新gid，类型为d、l、-，权限为八进制
+     */
+    fn save(&self) {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return,
+        };
+        let mut content = String::new();
+        for (list_name, list) in [("undo", &self.undo_list), ("redo", &self.redo_list)] {
+            for entry in list {
+                content.push_str(&Self::format_line(list_name, entry));
+                content.push('\n');
+            }
+        }
+        if let Some(dir) = file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(file, content);
+    }
+
+    fn format_line(list_name: &str, entry: &Entry) -> String {
+        let mut fields: Vec<String> = vec![
+            list_name.to_string(),
+            entry.time.format(TIME_FORMAT).to_string(),
+            entry.action.to_str().to_string(),
+        ];
+        let push_item = |fields: &mut Vec<String>, tag: &str, item: &TrashItem| {
+            fields.push(tag.to_string());
+            fields.push(trash::encode_path(&item.original_path));
+            fields.push(trash::encode_path(&item.trash_dir));
+            fields.push(trash::encode_path(Path::new(&item.name)));
+        };
+        match &entry.action {
+            Action::Rename { pairs } => {
+                for (source, target) in pairs {
+                    fields.push(String::from("p"));
+                    fields.push(trash::encode_path(source));
+                    fields.push(trash::encode_path(target));
+                }
+            }
+            Action::Copy { pairs, replaced } | Action::Move { pairs, replaced } => {
+                for (source, target) in pairs {
+                    fields.push(String::from("p"));
+                    fields.push(trash::encode_path(source));
+                    fields.push(trash::encode_path(target));
+                }
+                for item in replaced {
+                    push_item(&mut fields, "t", item);
+                }
+            }
+            Action::Trash { items } => {
+                for item in items {
+                    push_item(&mut fields, "t", item);
+                }
+            }
+            Action::Delete { paths } => {
+                for path in paths {
+                    fields.push(String::from("f"));
+                    fields.push(trash::encode_path(path));
+                }
+            }
+            Action::Create { paths, kind } => {
+                fields.push(String::from("k"));
+                fields.push(kind.to_str().to_string());
+                fields.push(kind.get_target().map(trash::encode_path).unwrap_or_default());
+                for path in paths {
+                    fields.push(String::from("f"));
+                    fields.push(trash::encode_path(path));
+                }
+            }
+            Action::Permission { previews } => {
+                for preview in previews {
+                    let kind = match (preview.is_symlink, preview.is_dir) {
+                        (true, _) => "l",
+                        (false, true) => "d",
+                        (false, false) => "-",
+                    };
+                    fields.push(String::from("m"));
+                    fields.push(trash::encode_path(&preview.path));
+                    fields.push(kind.to_string());
+                    fields.push(format!("{:o}", preview.old_mode));
+                    fields.push(format!("{:o}", preview.new_mode));
+                    fields.extend([preview.old_uid, preview.new_uid, preview.old_gid, preview.new_gid].map(|id| id.to_string()));
+                }
+            }
+        }
+        fields.join("\t")
+    }
+
+    /*
+     * @概述        解析一行记录
+     * @返回值      Option<(bool, Entry)>，(是否属于撤销列表, 记录)
+     */
+    fn parse_line(line: &str) -> Option<(bool, Entry)> {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 3 {
+            return None;
+        }
+        let is_undo = match fields[0] {
+            "undo" => true,
+            "redo" => false,
+            _ => return None,
+        };
+        let time = NaiveDateTime::parse_from_str(fields[1], TIME_FORMAT).ok()?;
+
+        let mut pairs: Vec<(PathBuf, PathBuf)> = Vec::new();
+        let mut items: Vec<TrashItem> = Vec::new();
+        let mut paths: Vec<PathBuf> = Vec::new();
+        let mut kind: Option<CreateKind> = None;
+        let mut previews: Vec<PermissionPreview> = Vec::new();
+        let mut i = 3;
+        while i < fields.len() {
+            match fields[i] {
+                "p" if i + 2 < fields.len() => {
+                    pairs.push((trash::decode_path(fields[i + 1]), trash::decode_path(fields[i + 2])));
+                    i += 3;
+                }
+                "t" if i + 3 < fields.len() => {
+                    let name = trash::decode_path(fields[i + 3]).to_string_lossy().to_string();
+                    let trash_dir = trash::decode_path(fields[i + 2]);
+                    let is_dir = trash_dir.join("files").join(&name).is_dir();
+                    items.push(TrashItem {
+                        name,
+                        trash_dir,
+                        original_path: trash::decode_path(fields[i + 1]),
+                        deletion_date: None,
+                        is_dir,
+                    });
+                    i += 4;
+                }
+                "f" if i + 1 < fields.len() => {
+                    paths.push(trash::decode_path(fields[i + 1]));
+                    i += 2;
+                }
+                "k" if i + 2 < fields.len() => {
+                    kind = CreateKind::from_str(fields[i + 1], trash::decode_path(fields[i + 2]));
+                    i += 3;
+                }
+                "m" if i + 8 < fields.len() => {
+                    let parse_mode = |field: &str| u32::from_str_radix(field, 8).ok();
+                    let parse_id = |field: &str| field.parse::<u32>().ok();
+                    previews.push(PermissionPreview {
+                        path: trash::decode_path(fields[i + 1]),
+                        is_dir: fields[i + 2] == "d",
+                        is_symlink: fields[i + 2] == "l",
+                        old_mode: parse_mode(fields[i + 3])?,
+                        new_mode: parse_mode(fields[i + 4])?,
+                        old_uid: parse_id(fields[i + 5])?,
+                        new_uid: parse_id(fields[i + 6])?,
+                        old_gid: parse_id(fields[i + 7])?,
+                        new_gid: parse_id(fields[i + 8])?,
+                    });
+                    i += 9;
+                }
+                _ => return None,
+            }
+        }
+
+        let action = match fields[2] {
+            "copy" => Action::Copy { pairs, replaced: items },
+            "move" => Action::Move { pairs, replaced: items },
+            "trash" => Action::Trash { items },
+            "delete" => Action::Delete { paths },
+            "rename" => Action::Rename { pairs },
+            "create" if !paths.is_empty() => Action::Create { paths, kind: kind? },
+            "permission" => Action::Permission { previews },
+            _ => return None,
+        };
+        Some((is_undo, Entry { time, action }))
+    }
+}
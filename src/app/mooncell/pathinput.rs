@@ -0,0 +1,126 @@
+use std::env;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use super::TopError;
+
+/*
+ * @概述        路径栏输入的路径，开头的~展开为$HOME，$VAR和${VAR}展开为环境变量
+ *              相对路径相对于当前文件夹，Tab补全文件夹名
+ */
+
+/*
+ * @概述        展开~和环境变量，相对路径接到cwd后面
+ * @参数1       &str，输入的路径
+ * @参数2       &Path，当前文件夹
+ * @返回值      Result<PathBuf, TopError>，环境变量不存在时返回Err
+ */
+pub fn expand(input: &str, cwd: &Path) -> Result<PathBuf, TopError> {
+    let input = input.trim();
+    let mut expanded = String::new();
+    let rest = match input.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => {
+            expanded.push_str(&env_var("HOME")?);
+            rest
+        }
+        _ => input,
+    };
+
+    let mut chars = rest.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch != '$' {
+            expanded.push(ch);
+            continue;
+        }
+        let mut name = String::new();
+        if chars.peek() == Some(&'{') {
+            chars.next();
+            for ch in chars.by_ref() {
+                if ch == '}' {
+                    break;
+                }
+                name.push(ch);
+            }
+        } else {
+            while let Some(ch) = chars.peek()
+                && (ch.is_ascii_alphanumeric() || *ch == '_') {
+                name.push(*ch);
+                chars.next();
+            }
+        }
+        // 单独的$保持原样
+        if name.is_empty() {
+            expanded.push('$');
+        } else {
+            expanded.push_str(&env_var(&name)?);
+        }
+    }
+    Ok(normalize(&cwd.join(expanded)))
+}
+
+/*
+ * @概述        去掉路径中的.和..，不解析符号链接
+ */
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => { normalized.pop(); }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+fn env_var(name: &str) -> Result<String, TopError> {
+    env::var(name).map_err(|_| TopError::ErrorInformation(format!("${} is not set", name)))
+}
+
+/*
+ * @概述        补全最后一段文件夹名，只有一个候选时补全并加上/，多个时补全共同的前缀
+ * @参数1       &str，输入的路径
+ * @参数2       &Path，当前文件夹
+ * @返回值      (String, Vec<String>)，(补全后的输入, 所有候选的名字)
+ */
+pub fn complete(input: &str, cwd: &Path) -> (String, Vec<String>) {
+    let (dir_part, prefix) = match input.rfind('/') {
+        Some(pos) => input.split_at(pos + 1),
+        None => ("", input),
+    };
+    let dir = match expand(dir_part, cwd) {
+        Ok(dir) => dir,
+        Err(_) => return (input.to_string(), Vec::new()),
+    };
+    let entries = match fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(_) => return (input.to_string(), Vec::new()),
+    };
+    let mut candidates: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .collect();
+    candidates.sort();
+
+    let completed = match candidates.as_slice() {
+        [] => input.to_string(),
+        [only] => format!("{}{}/", dir_part, only),
+        [first, others @ ..] => {
+            let mut common = first.as_str();
+            for other in others {
+                let len = common
+                    .char_indices()
+                    .zip(other.chars())
+                    .take_while(|((_, a), b)| a == b)
+                    .last()
+                    .map(|((i, a), _)| i + a.len_utf8())
+                    .unwrap_or(0);
+                common = &common[..len];
+            }
+            format!("{}{}", dir_part, common)
+        }
+    };
+    (completed, candidates)
+}
@@ -0,0 +1,250 @@
+use std::collections::HashMap;
+use std::fs;
+use std::os::unix::fs::{lchown, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use super::TopError;
+
+/*
+ * @概述        修改权限和所有者
+ *              先按照PermissionChange生成每个文件的预览，确认后再执行
+ *              递归时文件和文件夹使用不同的权限，符号链接不修改权限，也不会进入链接指向的文件夹
+ *              用户名和组名从/etc/passwd、/etc/group读取，也可以直接使用数字id
+ */
+
+/*
+ * @概述        一类条目的权限怎么修改，勾选只修改改过的位，每个文件保留其他位原来的值
+ */
+#[derive(Clone, Copy, PartialEq)]
+pub enum ModeChange {
+    Keep,
+    Mask { set: u32, clear: u32 },    // 新权限为 (原权限 & !clear) | set
+    Absolute(u32),    // 输入的八进制权限，所有条目都改成这个权限
+}
+
+impl ModeChange {
+    pub fn apply(self, old_mode: u32) -> u32 {
+        match self {
+            ModeChange::Keep => old_mode,
+            ModeChange::Mask { set, clear } => (old_mode & !clear) | set,
+            ModeChange::Absolute(mode) => mode,
+        }
+    }
+}
+
+pub struct PermissionChange {
+    pub file_mode: ModeChange,    // 文件的权限
+    pub dir_mode: ModeChange,    // 文件夹的权限
+    pub owner: Option<u32>,
+    pub group: Option<u32>,
+    pub recursive: bool,
+}
+
+/*
+ * @概述        一个文件修改前后的权限和所有者
+ */
+#[derive(Clone)]
+pub struct PermissionPreview {
+    pub path: PathBuf,
+    pub is_dir: bool,
+    pub is_symlink: bool,
+    pub old_mode: u32,
+    pub new_mode: u32,
+    pub old_uid: u32,
+    pub new_uid: u32,
+    pub old_gid: u32,
+    pub new_gid: u32,
+}
+
+const MODE_MASK: u32 = 0o7777;
+
+impl PermissionPreview {
+    pub fn is_changed(&self) -> bool {
+        self.old_mode != self.new_mode || self.old_uid != self.new_uid || self.old_gid != self.new_gid
+    }
+
+    /*
+     * @概述        交换修改前后的值，用于撤销
+     */
+    pub fn reversed(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            is_dir: self.is_dir,
+            is_symlink: self.is_symlink,
+            old_mode: self.new_mode,
+            new_mode: self.old_mode,
+            old_uid: self.new_uid,
+            new_uid: self.old_uid,
+            old_gid: self.new_gid,
+            new_gid: self.old_gid,
+        }
+    }
+}
+
+/*
+ * @概述        生成每个文件修改前后的对比，递归时包括文件夹里的所有条目
+ * @参数1       &[PathBuf]，选中的文件
+ * @参数2       &PermissionChange
+ * @返回值      Vec<PermissionPreview>，父文件夹在子条目前面
+ */
+pub fn preview(paths: &[PathBuf], change: &PermissionChange) -> Vec<PermissionPreview> {
+    let mut previews = Vec::new();
+    for path in paths {
+        preview_path(path, change, &mut previews);
+    }
+    previews
+}
+
+fn preview_path(path: &Path, change: &PermissionChange, previews: &mut Vec<PermissionPreview>) {
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(_) => return,
+    };
+    let is_dir = metadata.is_dir();
+    let is_symlink = metadata.file_type().is_symlink();
+    let old_mode = metadata.mode() & MODE_MASK;
+    let new_mode = match (is_symlink, is_dir) {
+        (true, _) => old_mode,
+        (false, true) => change.dir_mode.apply(old_mode),
+        (false, false) => change.file_mode.apply(old_mode),
+    };
+    previews.push(PermissionPreview {
+        path: path.to_path_buf(),
+        is_dir,
+        is_symlink,
+        old_mode,
+        new_mode,
+        old_uid: metadata.uid(),
+        new_uid: change.owner.unwrap_or(metadata.uid()),
+        old_gid: metadata.gid(),
+        new_gid: change.group.unwrap_or(metadata.gid()),
+    });
+
+    if change.recursive && is_dir
+        && let Ok(entries) = fs::read_dir(path) {
+        let mut children: Vec<PathBuf> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+        children.sort();
+        for child in children {
+            preview_path(&child, change, previews);
+        }
+    }
+}
+
+/*
+ * @概述        执行预览中有变化的条目，子条目先于父文件夹修改，避免先去掉了文件夹的权限导致进不去
+ *              先修改所有者再修改权限，因为chown会清除setuid/setgid
+ * @参数1       &[PermissionPreview]
+ * @返回值      (usize, Vec<(PathBuf, String)>)，(成功的数量, 失败的文件和原因)
+ */
+pub fn apply(previews: &[PermissionPreview]) -> (usize, Vec<(PathBuf, String)>) {
+    let mut done = 0;
+    let mut errors = Vec::new();
+    for preview in previews.iter().rev().filter(|preview| preview.is_changed()) {
+        let owner_changed = preview.old_uid != preview.new_uid || preview.old_gid != preview.new_gid;
+        let result = (|| {
+            if owner_changed {
+                let owner = (preview.old_uid != preview.new_uid).then_some(preview.new_uid);
+                let group = (preview.old_gid != preview.new_gid).then_some(preview.new_gid);
+                lchown(&preview.path, owner, group)?;
+            }
+            // chown之后setuid/setgid可能已经被清除，需要重新设置
+            if !preview.is_symlink && (preview.old_mode != preview.new_mode || (owner_changed && preview.new_mode & 0o6000 != 0)) {
+                fs::set_permissions(&preview.path, fs::Permissions::from_mode(preview.new_mode))?;
+            }
+            Ok::<(), std::io::Error>(())
+        })();
+        match result {
+            Ok(_) => done += 1,
+            Err(e) => errors.push((preview.path.clone(), e.to_string())),
+        }
+    }
+    (done, errors)
+}
+
+/*
+ * @概述        把权限转换为ls -l的格式，例如drwxr-xr-x
+ * @参数1       u32，权限
+ * @参数2       char，类型，d、l、-
+ * @返回值      String
+ */
+pub fn mode_to_string(mode: u32, kind: char) -> String {
+    let mut str = String::from(kind);
+    let specials = [(0o4000, 's'), (0o2000, 's'), (0o1000, 't')];
+    for (i, (special, special_ch)) in specials.iter().enumerate() {
+        let shift = 6 - i * 3;
+        str.push(if mode & (0o4 << shift) != 0 { 'r' } else { '-' });
+        str.push(if mode & (0o2 << shift) != 0 { 'w' } else { '-' });
+        let exec = mode & (0o1 << shift) != 0;
+        str.push(match (mode & special != 0, exec) {
+            (true, true) => *special_ch,
+            (true, false) => special_ch.to_ascii_uppercase(),
+            (false, true) => 'x',
+            (false, false) => '-',
+        });
+    }
+    str
+}
+
+/*
+ * @概述        所有用户的uid和名字
+ * @返回值      HashMap<u32, String>
+ */
+pub fn user_names() -> HashMap<u32, String> {
+    read_ids("/etc/passwd").into_iter().map(|(name, id)| (id, name)).collect()
+}
+
+/*
+ * @概述        所有组的gid和名字
+ * @返回值      HashMap<u32, String>
+ */
+pub fn group_names() -> HashMap<u32, String> {
+    read_ids("/etc/group").into_iter().map(|(name, id)| (id, name)).collect()
+}
+
+/*
+ * @概述        把用户名或者uid转换为uid
+ * @参数1       &str
+ * @返回值      Result<u32, TopError>
+ */
+pub fn parse_user(user: &str) -> Result<u32, TopError> {
+    user.parse::<u32>()
+        .ok()
+        .or_else(|| find_id("/etc/passwd", user))
+        .ok_or_else(|| TopError::ErrorInformation(format!("unknown user \"{}\"", user)))
+}
+
+/*
+ * @概述        把组名或者gid转换为gid
+ * @参数1       &str
+ * @返回值      Result<u32, TopError>
+ */
+pub fn parse_group(group: &str) -> Result<u32, TopError> {
+    group.parse::<u32>()
+        .ok()
+        .or_else(|| find_id("/etc/group", group))
+        .ok_or_else(|| TopError::ErrorInformation(format!("unknown group \"{}\"", group)))
+}
+
+/*
+ * @概述        读取/etc/passwd或者/etc/group，每行为 名字:密码:id:...
+ * @返回值      Vec<(String, u32)>，(名字, id)
+ */
+fn read_ids(file: &str) -> Vec<(String, u32)> {
+    let content = match fs::read_to_string(file) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    content
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split(':');
+            let name = fields.next()?;
+            let id = fields.nth(1)?.parse::<u32>().ok()?;
+            Some((name.to_string(), id))
+        })
+        .collect()
+}
+
+fn find_id(file: &str, name: &str) -> Option<u32> {
+    read_ids(file).into_iter().find(|(entry_name, _)| entry_name == name).map(|(_, id)| id)
+}
@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::journal::state_dir;
+use super::trash::{decode_path, encode_path};
+
+/*
+ * @概述        书签和访问过的文件夹
+ *              书签为一个字符对应一个文件夹，m<字符>设置，'<字符>跳转
+ *              访问过的文件夹和zoxide一样按frecency排序：访问次数乘以按上次访问时间计算的权重
+ *              保存在$XDG_STATE_HOME/mooncell/places，每行为 b 字符 路径 或者 v 次数 上次访问的时间戳 路径，用tab分隔
+ */
+
+const MAX_RANK: f64 = 1000.0;    // 访问次数的总和超过时全部按比例减少，太小的删除
+const HOUR: i64 = 3600;
+const DAY: i64 = 24 * HOUR;
+const WEEK: i64 = 7 * DAY;
+
+struct Visit {
+    rank: f64,    // 访问次数，会随着老化减少
+    last: i64,    // 上次访问的时间戳
+}
+
+pub struct Places {
+    bookmarks: BTreeMap<char, PathBuf>,
+    visits: HashMap<PathBuf, Visit>,
+    file: Option<PathBuf>,
+}
+
+impl Places {
+    /*
+     * @概述        读取保存的书签和访问记录，文件不存在时为空
+     */
+    pub fn load() -> Self {
+        let file = state_dir().map(|dir| dir.join("places"));
+        let mut places = Self { bookmarks: BTreeMap::new(), visits: HashMap::new(), file };
+        let content = match places.file.as_ref().map(fs::read_to_string) {
+            Some(Ok(content)) => content,
+            _ => return places,
+        };
+        for line in content.lines() {
+            let fields: Vec<&str> = line.split('\t').collect();
+            match fields[..] {
+                ["b", key, path] => {
+                    if let Some(key) = key.chars().next() {
+                        places.bookmarks.insert(key, decode_path(path));
+                    }
+                }
+                ["v", rank, last, path] => {
+                    if let (Ok(rank), Ok(last)) = (rank.parse(), last.parse()) {
+                        places.visits.insert(decode_path(path), Visit { rank, last });
+                    }
+                }
+                _ => {}
+            }
+        }
+        places
+    }
+
+    pub fn set_bookmark(&mut self, key: char, path: &Path) {
+        self.bookmarks.insert(key, path.to_path_buf());
+        self.save();
+    }
+
+    pub fn get_bookmark(&self, key: char) -> Option<&Path> {
+        self.bookmarks.get(&key).map(PathBuf::as_path)
+    }
+
+    /*
+     * @返回值      Vec<(char, PathBuf)>，按字符排序
+     */
+    pub fn get_bookmarks(&self) -> Vec<(char, PathBuf)> {
+        self.bookmarks.iter().map(|(key, path)| (*key, path.clone())).collect()
+    }
+
+    pub fn remove_bookmark(&mut self, key: char) {
+        if self.bookmarks.remove(&key).is_some() {
+            self.save();
+        }
+    }
+
+    /*
+     * @概述        记录一次访问，次数总和太大时老化
+     * @参数1       &Path
+     */
+    pub fn visit(&mut self, path: &Path) {
+        let now = chrono::Local::now().timestamp();
+        let visit = self.visits.entry(path.to_path_buf()).or_insert(Visit { rank: 0.0, last: now });
+        visit.rank += 1.0;
+        visit.last = now;
+
+        let total: f64 = self.visits.values().map(|visit| visit.rank).sum();
+        if total > MAX_RANK {
+            let factor = 0.9 * MAX_RANK / total;
+            self.visits.retain(|_, visit| {
+                visit.rank *= factor;
+                visit.rank >= 1.0
+            });
+        }
+        self.save();
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        if self.visits.remove(path).is_some() {
+            self.save();
+        }
+    }
+
+    /*
+     * @概述        按frecency排序的文件夹，已经不存在的不返回
+     *              query按空格分成几段，路径中需要按顺序包含每一段，不区分大小写
+     * @参数1       &str
+     * @参数2       usize，最多返回的数量
+     * @返回值      Vec<PathBuf>
+     */
+    pub fn recent(&self, query: &str, limit: usize) -> Vec<PathBuf> {
+        let now = chrono::Local::now().timestamp();
+        let terms: Vec<String> = query.split_whitespace().map(|term| term.to_lowercase()).collect();
+        let mut found: Vec<(f64, &PathBuf)> = self.visits
+            .iter()
+            .filter(|(path, _)| Self::is_match(path, &terms))
+            .map(|(path, visit)| (Self::frecency(visit, now), path))
+            .collect();
+        found.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+        found
+            .into_iter()
+            .map(|(_, path)| path)
+            .filter(|path| path.is_dir())
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    fn frecency(visit: &Visit, now: i64) -> f64 {
+        let age = now - visit.last;
+        let weight = if age < HOUR {
+            4.0
+        } else if age < DAY {
+            2.0
+        } else if age < WEEK {
+            0.5
+        } else {
+            0.25
+        };
+        visit.rank * weight
+    }
+
+    fn is_match(path: &Path, terms: &[String]) -> bool {
+        let path_str = path.to_string_lossy().to_lowercase();
+        let mut from = 0;
+        for term in terms {
+            match path_str[from..].find(term.as_str()) {
+                Some(pos) => from += pos + term.len(),
+                None => return false,
+            }
+        }
+        true
+    }
+
+    fn save(&self) {
+        let file = match &self.file {
+            Some(file) => file,
+            None => return,
+        };
+        let mut content = String::new();
+        for (key, path) in &self.bookmarks {
+            content.push_str(&format!("b\t{}\t{}\n", key, encode_path(path)));
+        }
+        for (path, visit) in &self.visits {
+            content.push_str(&format!("v\t{}\t{}\t{}\n", visit.rank, visit.last, encode_path(path)));
+        }
+        if let Some(dir) = file.parent() {
+            let _ = fs::create_dir_all(dir);
+        }
+        let _ = fs::write(file, content);
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::collections::HashMap;
+
+use super::TopError;
+
+/*
+ * @概述        通过/proc读取进程相关的信息
+ *              包括枚举进程、读取进程名、扫描进程打开的fd以及发送信号
+ */
+
+/*
+ * @概述        列出/proc下所有的进程号
+ * @返回值      Vec<u32>
+ */
+pub fn list_pids() -> Vec<u32> {
+    let mut pids: Vec<u32> = Vec::new();
+    if let Ok(entries) = fs::read_dir("/proc") {
+        for entry in entries.flatten() {
+            if let Some(name) = entry.file_name().to_str()
+                && let Ok(pid) = name.parse::<u32>() {
+                pids.push(pid);
+            }
+        }
+    }
+    pids
+}
+
+/*
+ * @概述        读取/proc/<pid>/comm获取进程名
+ * @参数1       u32，pid
+ * @返回值      String，失败时返回"?"
+ */
+pub fn process_name(pid: u32) -> String {
+    match fs::read_to_string(format!("/proc/{}/comm", pid)) {
+        Ok(str) => str.trim_end().to_string(),
+        Err(_) => String::from("?"),
+    }
+}
+
+/*
+ * @概述        读取进程的工作目录
+ * @参数1       u32，pid
+ * @返回值      Option<PathBuf>
+ */
+pub fn process_cwd(pid: u32) -> Option<PathBuf> {
+    fs::read_link(format!("/proc/{}/cwd", pid)).ok()
+}
+
+/*
+ * @概述        读取进程的可执行文件路径，去掉文件被删除时内核附加的后缀
+ * @参数1       u32，pid
+ * @返回值      Option<PathBuf>
+ */
+pub fn process_exe(pid: u32) -> Option<PathBuf> {
+    let exe = fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+    match exe.to_str().and_then(|str| str.strip_suffix(" (deleted)")) {
+        Some(str) => Some(PathBuf::from(str)),
+        None => Some(exe),
+    }
+}
+
+/*
+ * @概述        读取进程所有fd指向的目标
+ * @参数1       u32，pid
+ * @返回值      Vec<(String, PathBuf)>，(fd编号, 指向的目标)，没有权限时为空
+ */
+pub fn fd_targets(pid: u32) -> Vec<(String, PathBuf)> {
+    let mut targets = Vec::new();
+    if let Ok(entries) = fs::read_dir(format!("/proc/{}/fd", pid)) {
+        for entry in entries.flatten() {
+            if let Ok(target) = fs::read_link(entry.path()) {
+                targets.push((entry.file_name().to_string_lossy().to_string(), target));
+            }
+        }
+    }
+    targets
+}
+
+/*
+ * @概述        扫描所有进程的fd，建立socket inode到pid的对应关系
+ * @返回值      HashMap<u64, u32>，inode -> pid
+ */
+pub fn socket_inode_map() -> HashMap<u64, u32> {
+    let mut map: HashMap<u64, u32> = HashMap::new();
+    for pid in list_pids() {
+        for (_, target) in fd_targets(pid) {
+            // 形如 socket:[12345]
+            if let Some(str) = target.to_str()
+                && let Some(inode) = str.strip_prefix("socket:[").and_then(|s| s.strip_suffix(']'))
+                && let Ok(inode) = inode.parse::<u64>() {
+                map.entry(inode).or_insert(pid);
+            }
+        }
+    }
+    map
+}
+
+/*
+ * @概述        占用了某个文件/文件夹的进程
+ */
+#[derive(Clone)]
+pub struct FileHolder {
+    pub pid: u32,
+    pub name: String,    // 进程名
+    pub usage: String,    // 占用方式，如 fd 3、cwd
+    pub target: PathBuf,    // 实际占用的路径
+}
+
+/*
+ * @概述        扫描/proc/<pid>/fd和/proc/<pid>/cwd，找出所有打开了path(或其下内容)的进程
+ * @参数1       &Path
+ * @返回值      Vec<FileHolder>
+ */
+pub fn find_holders(path: &Path) -> Vec<FileHolder> {
+    let path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut holders: Vec<FileHolder> = Vec::new();
+
+    for pid in list_pids() {
+        let mut usages: Vec<(String, PathBuf)> = Vec::new();
+        if let Some(cwd) = process_cwd(pid) {
+            usages.push((String::from("cwd"), cwd));
+        }
+        for (fd, target) in fd_targets(pid) {
+            usages.push((format!("fd {}", fd), target));
+        }
+
+        for (usage, target) in usages {
+            if target.starts_with(&path) {
+                holders.push(FileHolder {
+                    pid,
+                    name: process_name(pid),
+                    usage,
+                    target,
+                });
+            }
+        }
+    }
+    holders
+}
+
+/*
+ * @概述        向进程发送信号
+ * @参数1       u32，pid
+ * @参数2       &str，信号名，TERM或者KILL
+ * @返回值      Result<(), TopError>
+ */
+pub fn send_signal(pid: u32, signal: &str) -> Result<(), TopError> {
+    let signal = match signal {
+        "TERM" => libc::SIGTERM,
+        "KILL" => libc::SIGKILL,
+        _ => return Err(TopError::ErrorInformation(format!("unsupported signal {}", signal))),
+    };
+    // pid为0或者负数时会发给整个进程组，不允许
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Err(TopError::ErrorInformation(format!("invalid pid {}", pid))),
+    };
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        Ok(())
+    } else {
+        Err(TopError::ErrorInformation(std::io::Error::last_os_error().to_string()))
+    }
+}
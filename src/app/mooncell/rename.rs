@@ -0,0 +1,333 @@
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Local};
+use regex::{NoExpand, Regex};
+
+use super::TopError;
+
+/*
+ * @概述        批量重命名的规则和预览
+ *              find为空时replace作为整个新名字的模板，否则把名字中匹配find的部分替换为replace
+ *              replace中可以使用的模板：
+ *                  {name} 原名字(不含后缀)  {ext} 后缀  {date} 今天的日期  {mtime} 文件的修改日期
+ *                  {n} 序号  {n:3} 补零到3位  {n:3:10} 从10开始
+ *              正则模式下还可以用$1、${name}引用捕获组
+ */
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CaseMode {
+    Keep,
+    Lower,
+    Upper,
+    Title,    // 每个单词首字母大写
+}
+
+pub struct RenameRule {
+    pub find: String,
+    pub replace: String,
+    pub use_regex: bool,
+    pub case: CaseMode,
+}
+
+/*
+ * @概述        一个文件重命名前后的对比
+ */
+pub struct RenamePreview {
+    pub from: PathBuf,
+    pub to: PathBuf,
+    pub problem: Option<String>,    // 冲突或者名字不合法，有问题时不能执行
+}
+
+impl CaseMode {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            CaseMode::Keep => "keep",
+            CaseMode::Lower => "lower",
+            CaseMode::Upper => "upper",
+            CaseMode::Title => "title",
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            CaseMode::Keep => CaseMode::Lower,
+            CaseMode::Lower => CaseMode::Upper,
+            CaseMode::Upper => CaseMode::Title,
+            CaseMode::Title => CaseMode::Keep,
+        }
+    }
+}
+
+impl RenamePreview {
+    pub fn is_changed(&self) -> bool {
+        self.from != self.to
+    }
+}
+
+/*
+ * @概述        按照规则生成每个文件的新名字
+ * @参数1       &[PathBuf]，需要重命名的文件，序号按照这个顺序
+ * @参数2       &RenameRule
+ * @返回值      Result<Vec<RenamePreview>, TopError>，正则表达式错误时返回Err
+ */
+pub fn preview(paths: &[PathBuf], rule: &RenameRule) -> Result<Vec<RenamePreview>, TopError> {
+    let pattern = if rule.use_regex { rule.find.clone() } else { regex::escape(&rule.find) };
+    let re = Regex::new(&pattern).map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+    let today = Local::now().format("%Y-%m-%d").to_string();
+
+    let mut previews: Vec<RenamePreview> = Vec::new();
+    for (index, path) in paths.iter().enumerate() {
+        let name = file_name(path);
+        let is_dir = path.is_dir();
+        let (stem, ext) = split_name(&name, is_dir);
+        let expanded = expand_template(&rule.replace, index, path, &stem, &ext, &today);
+
+        let new_name = if rule.find.is_empty() {
+            if rule.replace.is_empty() { name.clone() } else { expanded }
+        } else if rule.use_regex {
+            re.replace_all(&name, expanded.as_str()).to_string()
+        } else {
+            re.replace_all(&name, NoExpand(&expanded)).to_string()
+        };
+        let new_name = apply_case(&new_name, rule.case, is_dir);
+        previews.push(make_preview(path, &new_name));
+    }
+    check_problems(&mut previews);
+    Ok(previews)
+}
+
+/*
+ * @概述        检查新名字是否合法、是否互相重复、是否和已有的文件冲突
+ * @参数1       &mut [RenamePreview]
+ */
+pub fn check_problems(previews: &mut [RenamePreview]) {
+    let sources: HashSet<PathBuf> = previews.iter().map(|preview| preview.from.clone()).collect();
+    let mut counts: HashMap<PathBuf, usize> = HashMap::new();
+    for preview in previews.iter() {
+        *counts.entry(preview.to.clone()).or_insert(0) += 1;
+    }
+
+    for preview in previews.iter_mut() {
+        // 生成预览时已经发现的问题保留
+        if preview.problem.is_some() {
+            continue;
+        }
+        let new_name = preview.to.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        preview.problem = if new_name.is_empty() || new_name == "." || new_name == ".." || preview.to.parent() != preview.from.parent() {
+            Some(String::from("invalid name"))
+        } else if counts.get(&preview.to).copied().unwrap_or(0) > 1 {
+            Some(String::from("duplicate name"))
+        } else if preview.is_changed() && fs::symlink_metadata(&preview.to).is_ok() && !sources.contains(&preview.to) {
+            Some(String::from("already exists"))
+        } else {
+            None
+        };
+    }
+}
+
+/*
+ * @概述        生成给编辑器修改的文本，每行为 序号<tab>文件名
+ * @参数1       &[PathBuf]
+ * @返回值      String
+ */
+pub fn to_editor_text(paths: &[PathBuf]) -> String {
+    let mut text = String::new();
+    for (index, path) in paths.iter().enumerate() {
+        text.push_str(&format!("{}\t{}\n", index + 1, file_name(path)));
+    }
+    text
+}
+
+/*
+ * @概述        把to_editor_text的内容写进新建的临时文件，只有自己可以读写
+ *              放在$XDG_RUNTIME_DIR，没有时放在临时文件夹，独占创建，不会跟随别人预先放好的符号链接
+ * @参数1       &[PathBuf]
+ * @返回值      std::io::Result<PathBuf>，临时文件的路径，用完后需要删除
+ */
+pub fn write_editor_file(paths: &[PathBuf]) -> std::io::Result<PathBuf> {
+    let dir = match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) if !dir.is_empty() => PathBuf::from(dir),
+        _ => env::temp_dir(),
+    };
+    let text = to_editor_text(paths);
+    let mut attempt = 0;
+    loop {
+        let nanos = Local::now().timestamp_subsec_nanos();
+        let file = dir.join(format!("mooncell-rename-{}-{}-{}.txt", std::process::id(), nanos, attempt));
+        match OpenOptions::new().write(true).create_new(true).mode(0o600).open(&file) {
+            Ok(mut opened) => {
+                if let Err(e) = opened.write_all(text.as_bytes()) {
+                    let _ = fs::remove_file(&file);
+                    return Err(e);
+                }
+                return Ok(file);
+            }
+            // 名字被占用时换一个名字重试
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && attempt < 100 => attempt += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/*
+ * @概述        读取编辑器修改后的文本，删除的行保持原名，不会删除文件
+ * @参数1       &str，修改后的文本
+ * @参数2       &[PathBuf]，to_editor_text使用的文件列表
+ * @返回值      Result<Vec<RenamePreview>, TopError>，序号不正确时返回Err
+ */
+pub fn from_editor_text(text: &str, paths: &[PathBuf]) -> Result<Vec<RenamePreview>, TopError> {
+    let mut new_names: Vec<Option<String>> = vec![None; paths.len()];
+    for (line_number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let (index, name) = line
+            .split_once('\t')
+            .and_then(|(index, name)| Some((index.trim().parse::<usize>().ok()?, name)))
+            .ok_or_else(|| TopError::ErrorInformation(format!("line {}: expected <number><tab><name>", line_number + 1)))?;
+        match new_names.get_mut(index.wrapping_sub(1)) {
+            Some(slot) if slot.is_none() => *slot = Some(name.to_string()),
+            Some(_) => return Err(TopError::ErrorInformation(format!("line {}: number {} is used twice", line_number + 1, index))),
+            None => return Err(TopError::ErrorInformation(format!("line {}: unknown number {}", line_number + 1, index))),
+        }
+    }
+
+    let mut previews: Vec<RenamePreview> = paths
+        .iter()
+        .zip(new_names)
+        .map(|(path, name)| match name {
+            Some(name) => make_preview(path, &name),
+            None => RenamePreview { from: path.clone(), to: path.clone(), problem: None },
+        })
+        .collect();
+    check_problems(&mut previews);
+    Ok(previews)
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/*
+ * @概述        生成一个文件的预览
+ *              原名字不是UTF-8时只能按替换过的名字处理，没有变化的保持原名，有变化的标记问题，不会改成含有替换字符的名字
+ * @参数1       &Path，原路径
+ * @参数2       &str，新名字
+ * @返回值      RenamePreview
+ */
+fn make_preview(path: &Path, new_name: &str) -> RenamePreview {
+    let is_utf8 = path.file_name().is_none_or(|name| name.to_str().is_some());
+    if is_utf8 {
+        RenamePreview { from: path.to_path_buf(), to: path.with_file_name(new_name), problem: None }
+    } else if new_name == file_name(path) {
+        RenamePreview { from: path.to_path_buf(), to: path.to_path_buf(), problem: None }
+    } else {
+        RenamePreview { from: path.to_path_buf(), to: path.to_path_buf(), problem: Some(String::from("name is not UTF-8")) }
+    }
+}
+
+/*
+ * @概述        拆分名字和后缀，文件夹和以.开头没有其他.的文件不拆分
+ * @返回值      (String, String)，(名字, 后缀)
+ */
+fn split_name(name: &str, is_dir: bool) -> (String, String) {
+    if is_dir {
+        return (name.to_string(), String::new());
+    }
+    match name.rfind('.') {
+        Some(pos) if pos > 0 => (name[..pos].to_string(), name[pos + 1..].to_string()),
+        _ => (name.to_string(), String::new()),
+    }
+}
+
+/*
+ * @概述        展开replace中的模板，不认识的{}原样保留
+ * @参数1       &str，模板
+ * @参数2       usize，文件的序号，从0开始
+ * @参数3       &Path，文件路径，用于读取修改时间
+ * @参数4       &str，原名字
+ * @参数5       &str，原后缀
+ * @参数6       &str，今天的日期
+ * @返回值      String
+ */
+fn expand_template(template: &str, index: usize, path: &Path, stem: &str, ext: &str, today: &str) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        // ${name}是正则的捕获组，不处理
+        let is_capture = rest[..start].ends_with('$');
+        out.push_str(&rest[..start]);
+        let end = match rest[start..].find('}') {
+            Some(end) => start + end,
+            None => break,
+        };
+        let token = &rest[start + 1..end];
+        let value = if is_capture { None } else { expand_token(token, index, path, stem, ext, today) };
+        match value {
+            Some(value) => out.push_str(&value),
+            None => out.push_str(&rest[start..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+fn expand_token(token: &str, index: usize, path: &Path, stem: &str, ext: &str, today: &str) -> Option<String> {
+    match token {
+        "name" => return Some(stem.to_string()),
+        "ext" => return Some(ext.to_string()),
+        "date" => return Some(today.to_string()),
+        "mtime" => {
+            let modified = fs::symlink_metadata(path).and_then(|metadata| metadata.modified()).ok()?;
+            return Some(DateTime::<Local>::from(modified).format("%Y-%m-%d").to_string());
+        }
+        _ => {}
+    }
+
+    // {n}、{n:宽度}、{n:宽度:起始值}
+    let mut parts = token.split(':');
+    if parts.next() != Some("n") {
+        return None;
+    }
+    let width: usize = match parts.next() {
+        Some(width) => width.parse().ok()?,
+        None => 1,
+    };
+    let start: usize = match parts.next() {
+        Some(start) => start.parse().ok()?,
+        None => 1,
+    };
+    Some(format!("{:0width$}", start + index, width = width))
+}
+
+/*
+ * @概述        转换大小写，不改变后缀
+ */
+fn apply_case(name: &str, case: CaseMode, is_dir: bool) -> String {
+    let (stem, ext) = split_name(name, is_dir);
+    let stem = match case {
+        CaseMode::Keep => return name.to_string(),
+        CaseMode::Lower => stem.to_lowercase(),
+        CaseMode::Upper => stem.to_uppercase(),
+        CaseMode::Title => {
+            let mut out = String::new();
+            let mut word_start = true;
+            for ch in stem.chars() {
+                if word_start {
+                    out.extend(ch.to_uppercase());
+                } else {
+                    out.extend(ch.to_lowercase());
+                }
+                word_start = !ch.is_alphanumeric();
+            }
+            out
+        }
+    };
+    if ext.is_empty() { stem } else { format!("{}.{}", stem, ext) }
+}
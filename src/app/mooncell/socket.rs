@@ -0,0 +1,285 @@
+use std::fs;
+use std::net::{Ipv4Addr, Ipv6Addr};
+
+use super::TopError;
+use super::process;
+
+/*
+ * @概述        仿照ss，解析/proc/net/{tcp,tcp6,udp,udp6,unix}
+ *              每一行处理成一个SocketUnit，再通过/proc/<pid>/fd的inode找到所属进程
+ */
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SocketProtocol {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+    Unix,
+}
+
+#[derive(Clone)]
+pub struct SocketUnit {
+    pub protocol: SocketProtocol,
+    pub state: String,    // 连接状态，与ss的写法保持一致
+    pub local: String,    // 本地地址
+    pub remote: String,    // 远端地址
+    pub recv_q: u64,    // 接收队列
+    pub send_q: u64,    // 发送队列
+    pub inode: u64,
+    pub pid: Option<u32>,    // 所属进程，找不到（多半是没有权限）时为None
+    pub process: String,    // 所属进程名
+}
+
+pub struct SocketInfo {
+    pub list: Vec<SocketUnit>,
+}
+
+impl SocketProtocol {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            SocketProtocol::Tcp => "tcp",
+            SocketProtocol::Tcp6 => "tcp6",
+            SocketProtocol::Udp => "udp",
+            SocketProtocol::Udp6 => "udp6",
+            SocketProtocol::Unix => "unix",
+        }
+    }
+
+    fn proc_path(&self) -> &'static str {
+        match self {
+            SocketProtocol::Tcp => "/proc/net/tcp",
+            SocketProtocol::Tcp6 => "/proc/net/tcp6",
+            SocketProtocol::Udp => "/proc/net/udp",
+            SocketProtocol::Udp6 => "/proc/net/udp6",
+            SocketProtocol::Unix => "/proc/net/unix",
+        }
+    }
+
+    pub fn is_tcp(&self) -> bool {
+        matches!(self, SocketProtocol::Tcp | SocketProtocol::Tcp6)
+    }
+
+    pub fn is_udp(&self) -> bool {
+        matches!(self, SocketProtocol::Udp | SocketProtocol::Udp6)
+    }
+}
+
+impl SocketUnit {
+    /*
+     * @概述        是否处于监听或已建立连接的状态
+     * @返回值      bool
+     */
+    pub fn is_listen_or_established(&self) -> bool {
+        matches!(self.state.as_str(), "LISTEN" | "ESTAB" | "UNCONN")
+    }
+
+    /*
+     * @概述        按过滤字符串匹配，纯数字时匹配端口或pid，否则匹配进程名和地址
+     * @参数1       &str，过滤字符串
+     * @返回值      bool
+     */
+    pub fn is_match(&self, filter: &str) -> bool {
+        let filter = filter.trim();
+        if filter.is_empty() {
+            return true;
+        }
+
+        if let Ok(number) = filter.parse::<u32>() {
+            if self.pid == Some(number) {
+                return true;
+            }
+            let port = number.to_string();
+            return Self::port_of(&self.local) == Some(port.as_str())
+                || Self::port_of(&self.remote) == Some(port.as_str());
+        }
+
+        let filter = filter.to_lowercase();
+        self.process.to_lowercase().contains(&filter)
+            || self.local.to_lowercase().contains(&filter)
+            || self.remote.to_lowercase().contains(&filter)
+    }
+
+    fn port_of(addr: &str) -> Option<&str> {
+        addr.rsplit_once(':').map(|(_, port)| port)
+    }
+}
+
+impl SocketInfo {
+    pub fn new() -> Self {
+        Self { list: Vec::new() }
+    }
+
+    /*
+     * @概述        重新读取全部socket并关联到进程
+     */
+    pub fn refresh(&mut self) {
+        let mut list: Vec<SocketUnit> = Vec::new();
+        for protocol in [SocketProtocol::Tcp, SocketProtocol::Tcp6, SocketProtocol::Udp, SocketProtocol::Udp6, SocketProtocol::Unix] {
+            if let Ok(mut units) = Self::read_table(protocol) {
+                list.append(&mut units);
+            }
+        }
+
+        let inode_map = process::socket_inode_map();
+        for unit in list.iter_mut() {
+            if let Some(pid) = inode_map.get(&unit.inode) {
+                unit.pid = Some(*pid);
+                unit.process = process::process_name(*pid);
+            }
+        }
+        self.list = list;
+    }
+
+    /*
+     * @概述        读取并解析一张/proc/net下的表
+     * @参数1       SocketProtocol
+     * @返回值      Result<Vec<SocketUnit>, TopError>
+     */
+    fn read_table(protocol: SocketProtocol) -> Result<Vec<SocketUnit>, TopError> {
+        let content = fs::read_to_string(protocol.proc_path()).map_err(|_| TopError::ReadError)?;
+        let mut units = Vec::new();
+        // 第一行是表头
+        for line in content.lines().skip(1) {
+            let unit = match protocol {
+                SocketProtocol::Unix => Self::parse_unix_line(line),
+                _ => Self::parse_inet_line(protocol, line),
+            };
+            if let Some(unit) = unit {
+                units.push(unit);
+            }
+        }
+        Ok(units)
+    }
+
+    /*
+     * @概述        解析tcp/udp表中的一行
+     *              sl local_address rem_address st tx_queue:rx_queue tr:tm->when retrnsmt uid timeout inode
+     */
+    fn parse_inet_line(protocol: SocketProtocol, line: &str) -> Option<SocketUnit> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            return None;
+        }
+
+        let state_code = u8::from_str_radix(fields[3], 16).ok()?;
+        let (send_q, recv_q) = fields[4].split_once(':')?;
+        Some(SocketUnit {
+            protocol,
+            state: Self::inet_state(protocol, state_code),
+            local: Self::parse_inet_addr(fields[1])?,
+            remote: Self::parse_inet_addr(fields[2])?,
+            recv_q: u64::from_str_radix(recv_q, 16).ok()?,
+            send_q: u64::from_str_radix(send_q, 16).ok()?,
+            inode: fields[9].parse().ok()?,
+            pid: None,
+            process: String::from("-"),
+        })
+    }
+
+    /*
+     * @概述        解析unix表中的一行
+     *              Num RefCount Protocol Flags Type St Inode Path
+     */
+    fn parse_unix_line(line: &str) -> Option<SocketUnit> {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 7 {
+            return None;
+        }
+
+        let flags = u32::from_str_radix(fields[3], 16).ok()?;
+        let state_code = u8::from_str_radix(fields[5], 16).ok()?;
+        // __SO_ACCEPTCON，表示正在监听
+        let state = if flags & 0x10000 != 0 {
+            String::from("LISTEN")
+        } else {
+            match state_code {
+                1 => String::from("UNCONN"),
+                2 => String::from("SYN-SENT"),
+                3 => String::from("ESTAB"),
+                4 => String::from("CLOSING"),
+                _ => String::from("UNKNOWN"),
+            }
+        };
+        let path = match fields.get(7) {
+            Some(path) => path.to_string(),
+            None => String::from("*"),
+        };
+
+        Some(SocketUnit {
+            protocol: SocketProtocol::Unix,
+            state,
+            local: path,
+            remote: String::from("*"),
+            recv_q: 0,
+            send_q: 0,
+            inode: fields[6].parse().ok()?,
+            pid: None,
+            process: String::from("-"),
+        })
+    }
+
+    /*
+     * @概述        将内核的状态码转换成ss风格的字符串
+     */
+    fn inet_state(protocol: SocketProtocol, code: u8) -> String {
+        // udp没有连接的概念，7表示未连接
+        if protocol.is_udp() {
+            return match code {
+                1 => String::from("ESTAB"),
+                _ => String::from("UNCONN"),
+            };
+        }
+        let str = match code {
+            0x01 => "ESTAB",
+            0x02 => "SYN-SENT",
+            0x03 => "SYN-RECV",
+            0x04 => "FIN-WAIT-1",
+            0x05 => "FIN-WAIT-2",
+            0x06 => "TIME-WAIT",
+            0x07 => "CLOSE",
+            0x08 => "CLOSE-WAIT",
+            0x09 => "LAST-ACK",
+            0x0A => "LISTEN",
+            0x0B => "CLOSING",
+            _ => "UNKNOWN",
+        };
+        String::from(str)
+    }
+
+    /*
+     * @概述        解析形如 0100007F:1F90 的地址
+     *              地址部分是按本机字节序打印的32位整数，ipv6为4个这样的整数
+     * @返回值      Option<String>，如 127.0.0.1:8080、[::1]:22、*:*
+     */
+    fn parse_inet_addr(str: &str) -> Option<String> {
+        let (addr_hex, port_hex) = str.split_once(':')?;
+        let port = u16::from_str_radix(port_hex, 16).ok()?;
+        let port_str = if port == 0 { String::from("*") } else { port.to_string() };
+
+        let addr_str = match addr_hex.len() {
+            8 => {
+                let addr = Ipv4Addr::from(u32::from_str_radix(addr_hex, 16).ok()?.to_ne_bytes());
+                if addr.is_unspecified() { String::from("*") } else { addr.to_string() }
+            }
+            32 => {
+                let mut bytes = [0u8; 16];
+                for i in 0..4 {
+                    let word = u32::from_str_radix(&addr_hex[i * 8..i * 8 + 8], 16).ok()?;
+                    bytes[i * 4..i * 4 + 4].copy_from_slice(&word.to_ne_bytes());
+                }
+                let addr = Ipv6Addr::from(bytes);
+                if addr.is_unspecified() {
+                    String::from("*")
+                } else {
+                    match addr.to_ipv4_mapped() {
+                        Some(v4) => v4.to_string(),
+                        None => format!("[{}]", addr),
+                    }
+                }
+            }
+            _ => return None,
+        };
+        Some(format!("{}:{}", addr_str, port_str))
+    }
+}
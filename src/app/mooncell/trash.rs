@@ -0,0 +1,369 @@
+use std::env;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::{DirBuilderExt, MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+use std::ffi::OsStr;
+
+use chrono::{Local, NaiveDateTime};
+
+use super::TopError;
+use super::info::Info;
+
+/*
+ * @概述        按照freedesktop Trash规范实现的回收站
+ *              和家目录在同一个文件系统上的文件放进$XDG_DATA_HOME/Trash
+ *              其他文件系统上的文件放进挂载点下的.Trash/$uid或.Trash-$uid
+ *              每个回收站包含files和info两个文件夹，info中的.trashinfo记录原路径和删除时间
+ */
+
+/*
+ * @概述        回收站里的一个条目
+ */
+#[derive(Clone)]
+pub struct TrashItem {
+    pub name: String,    // files下的文件名
+    pub trash_dir: PathBuf,    // 所在的回收站
+    pub original_path: PathBuf,
+    pub deletion_date: Option<NaiveDateTime>,
+    pub is_dir: bool,
+}
+
+const DATE_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+impl TrashItem {
+    pub fn files_path(&self) -> PathBuf {
+        self.trash_dir.join("files").join(&self.name)
+    }
+
+    pub fn info_path(&self) -> PathBuf {
+        self.trash_dir.join("info").join(format!("{}.trashinfo", self.name))
+    }
+}
+
+/*
+ * @概述        家目录的回收站，$XDG_DATA_HOME/Trash，默认~/.local/share/Trash
+ * @返回值      Option<PathBuf>，没有$HOME时返回None
+ */
+fn home_trash() -> Option<PathBuf> {
+    match env::var_os("XDG_DATA_HOME") {
+        Some(dir) if !dir.is_empty() => Some(PathBuf::from(dir).join("Trash")),
+        _ => env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share/Trash")),
+    }
+}
+
+fn get_uid() -> u32 {
+    unsafe { libc::getuid() }
+}
+
+/*
+ * @概述        找到路径所在文件系统的挂载点，向上查找直到设备号改变
+ * @参数1       &Path，必须是存在的路径
+ * @返回值      PathBuf
+ */
+fn find_top_dir(path: &Path) -> PathBuf {
+    let dev = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata.dev(),
+        Err(_) => return PathBuf::from("/"),
+    };
+    let mut top = path.to_path_buf();
+    while let Some(parent) = top.parent() {
+        match fs::metadata(parent) {
+            Ok(metadata) if metadata.dev() == dev => top = parent.to_path_buf(),
+            _ => break,
+        }
+    }
+    top
+}
+
+/*
+ * @概述        挂载点下的回收站
+ *              $top/.Trash必须是设置了sticky位的文件夹且不是符号链接，才能使用$top/.Trash/$uid
+ *              否则使用$top/.Trash-$uid
+ * @参数1       &Path，挂载点
+ * @参数2       bool，不存在时是否创建
+ * @返回值      Option<PathBuf>
+ */
+fn top_dir_trash(top: &Path, create: bool) -> Option<PathBuf> {
+    let uid = get_uid();
+    let shared = top.join(".Trash");
+    if let Ok(metadata) = fs::symlink_metadata(&shared)
+        && metadata.is_dir()
+        && metadata.permissions().mode() & libc::S_ISVTX != 0 {
+        let trash = shared.join(uid.to_string());
+        if trash.is_dir() || (create && fs::DirBuilder::new().mode(0o700).create(&trash).is_ok()) {
+            return Some(trash);
+        }
+    }
+
+    let trash = top.join(format!(".Trash-{}", uid));
+    if trash.is_dir() || (create && fs::DirBuilder::new().mode(0o700).create(&trash).is_ok()) {
+        return Some(trash);
+    }
+    None
+}
+
+/*
+ * @概述        选择存放某个文件的回收站
+ * @参数1       &Path，需要删除的文件
+ * @返回值      Result<(PathBuf, Option<PathBuf>), TopError>，(回收站, 挂载点)，家目录回收站没有挂载点
+ */
+fn choose_trash(path: &Path) -> Result<(PathBuf, Option<PathBuf>), TopError> {
+    let metadata = fs::symlink_metadata(path).map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+    if let Some(home_trash) = home_trash() {
+        // 回收站可能还不存在，和它最近的存在的上级比较
+        let home_dev = home_trash.ancestors().find_map(|dir| fs::metadata(dir).ok()).map(|metadata| metadata.dev());
+        if home_dev == Some(metadata.dev()) {
+            return Ok((home_trash, None));
+        }
+    }
+    let top = find_top_dir(path);
+    match top_dir_trash(&top, true) {
+        Some(trash) => Ok((trash, Some(top))),
+        None => Err(TopError::ErrorInformation(format!("no usable trash on {}", top.to_string_lossy()))),
+    }
+}
+
+/*
+ * @概述        将文件放进回收站
+ * @参数1       &Path，需要删除的文件/文件夹
+ * @返回值      Result<TrashItem, TopError>，放进回收站后的条目
+ */
+pub fn trash_path(path: &Path) -> Result<TrashItem, TopError> {
+    let path = match path.parent() {
+        // 只规范化上级，避免跟随文件本身是符号链接的情况
+        Some(parent) => match (fs::canonicalize(parent), path.file_name()) {
+            (Ok(parent), Some(name)) => parent.join(name),
+            _ => path.to_path_buf(),
+        },
+        None => path.to_path_buf(),
+    };
+    let (trash, top) = choose_trash(&path)?;
+    let files_dir = trash.join("files");
+    let info_dir = trash.join("info");
+    for dir in [&files_dir, &info_dir] {
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o700)
+            .create(dir)
+            .map_err(|e| TopError::ErrorInformation(format!("can`t create {}: {}", dir.to_string_lossy(), e)))?;
+    }
+
+    // 挂载点下的回收站记录相对路径
+    let record_path = match &top {
+        Some(top) => path.strip_prefix(top).unwrap_or(&path).to_path_buf(),
+        None => path.clone(),
+    };
+    let now = Local::now().naive_local();
+    let info_content = format!(
+        "[Trash Info]\nPath={}\nDeletionDate={}\n",
+        encode_path(&record_path),
+        now.format(DATE_FORMAT),
+    );
+
+    // 先用O_EXCL创建.trashinfo占住名字，再移动文件
+    let file_name = path.file_name().ok_or(TopError::ParseError)?.to_string_lossy().to_string();
+    let mut index = 1;
+    loop {
+        let name = if index == 1 { file_name.clone() } else { format!("{}.{}", file_name, index) };
+        let info_path = info_dir.join(format!("{}.trashinfo", name));
+        match fs::OpenOptions::new().write(true).create_new(true).open(&info_path) {
+            Ok(mut info_file) => {
+                let files_path = files_dir.join(&name);
+                if fs::symlink_metadata(&files_path).is_ok() {
+                    // 孤立的文件，换一个名字
+                    let _ = fs::remove_file(&info_path);
+                    index += 1;
+                    continue;
+                }
+                let result = info_file
+                    .write_all(info_content.as_bytes())
+                    .and_then(|_| fs::rename(&path, &files_path));
+                if let Err(e) = result {
+                    let _ = fs::remove_file(&info_path);
+                    return Err(TopError::ErrorInformation(e.to_string()));
+                }
+                let is_dir = fs::symlink_metadata(&files_path).is_ok_and(|metadata| metadata.is_dir());
+                return Ok(TrashItem {
+                    name,
+                    trash_dir: trash.clone(),
+                    original_path: path.clone(),
+                    deletion_date: Some(now),
+                    is_dir,
+                });
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => index += 1,
+            Err(e) => return Err(TopError::ErrorInformation(format!("can`t write {}: {}", info_path.to_string_lossy(), e))),
+        }
+    }
+}
+
+/*
+ * @概述        所有存在的回收站，家目录的和各个挂载点下的
+ * @返回值      Vec<(PathBuf, Option<PathBuf>)>，(回收站, 挂载点)
+ */
+fn trash_dirs() -> Vec<(PathBuf, Option<PathBuf>)> {
+    let mut dirs: Vec<(PathBuf, Option<PathBuf>)> = Vec::new();
+    if let Some(home_trash) = home_trash()
+        && home_trash.is_dir() {
+        dirs.push((home_trash, None));
+    }
+    let mut mount_points: Vec<PathBuf> = Info::read_mount_options().into_keys().collect();
+    mount_points.sort();
+    for mount_point in mount_points {
+        if let Some(trash) = top_dir_trash(&mount_point, false)
+            && !dirs.iter().any(|(dir, _)| *dir == trash) {
+            dirs.push((trash, Some(mount_point)));
+        }
+    }
+    dirs
+}
+
+/*
+ * @概述        列出回收站里的所有条目，最近删除的在前
+ * @返回值      Vec<TrashItem>
+ */
+pub fn list_items() -> Vec<TrashItem> {
+    let mut items: Vec<TrashItem> = Vec::new();
+    for (trash, top) in trash_dirs() {
+        let entries = match fs::read_dir(trash.join("info")) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = match file_name.strip_suffix(".trashinfo") {
+                Some(name) => name.to_string(),
+                None => continue,
+            };
+            let content = match fs::read_to_string(entry.path()) {
+                Ok(content) => content,
+                Err(_) => continue,
+            };
+            let (path, deletion_date) = parse_trash_info(&content);
+            let path = match path {
+                Some(path) => path,
+                None => continue,
+            };
+            // 挂载点下的回收站中记录的是相对路径
+            let original_path = match &top {
+                Some(top) if path.is_relative() => top.join(path),
+                _ => path,
+            };
+            let is_dir = fs::symlink_metadata(trash.join("files").join(&name)).is_ok_and(|metadata| metadata.is_dir());
+            items.push(TrashItem { name, trash_dir: trash.clone(), original_path, deletion_date, is_dir });
+        }
+    }
+    items.sort_by_key(|item| std::cmp::Reverse(item.deletion_date));
+    items
+}
+
+/*
+ * @概述        解析.trashinfo
+ * @参数1       &str，文件内容
+ * @返回值      (Option<PathBuf>, Option<NaiveDateTime>)，(原路径, 删除时间)
+ */
+fn parse_trash_info(content: &str) -> (Option<PathBuf>, Option<NaiveDateTime>) {
+    let mut path = None;
+    let mut deletion_date = None;
+    let mut in_group = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            in_group = line == "[Trash Info]";
+            continue;
+        }
+        if !in_group {
+            continue;
+        }
+        if let Some(value) = line.strip_prefix("Path=") {
+            path = Some(decode_path(value));
+        } else if let Some(value) = line.strip_prefix("DeletionDate=") {
+            deletion_date = NaiveDateTime::parse_from_str(value, DATE_FORMAT).ok();
+        }
+    }
+    (path, deletion_date)
+}
+
+/*
+ * @概述        还原到原来的位置，原位置已经有文件时失败
+ * @参数1       &TrashItem
+ * @返回值      Result<(), TopError>
+ */
+pub fn restore(item: &TrashItem) -> Result<(), TopError> {
+    if fs::symlink_metadata(&item.original_path).is_ok() {
+        return Err(TopError::ErrorInformation(format!("{} already exists", item.original_path.to_string_lossy())));
+    }
+    if let Some(parent) = item.original_path.parent() {
+        fs::create_dir_all(parent).map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+    }
+    fs::rename(item.files_path(), &item.original_path).map_err(|e| TopError::ErrorInformation(e.to_string()))?;
+    let _ = fs::remove_file(item.info_path());
+    Ok(())
+}
+
+/*
+ * @概述        永久删除，先删除文件再删除.trashinfo，失败时条目仍然留在回收站里
+ * @参数1       &TrashItem
+ * @返回值      Result<(), TopError>
+ */
+pub fn remove(item: &TrashItem) -> Result<(), TopError> {
+    let files_path = item.files_path();
+    let result = match fs::symlink_metadata(&files_path) {
+        Ok(metadata) if metadata.is_dir() => fs::remove_dir_all(&files_path),
+        Ok(_) => fs::remove_file(&files_path),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e),
+    };
+    result.map_err(|e| TopError::ErrorInformation(format!("{}: {}", item.name, e)))?;
+    fs::remove_file(item.info_path()).map_err(|e| TopError::ErrorInformation(format!("{}: {}", item.name, e)))
+}
+
+/*
+ * @概述        清空所有回收站
+ * @返回值      (usize, Vec<String>)，(删除的条目数, 错误信息)
+ */
+pub fn empty() -> (usize, Vec<String>) {
+    let mut count = 0;
+    let mut errors: Vec<String> = Vec::new();
+    for item in list_items() {
+        match remove(&item) {
+            Ok(_) => count += 1,
+            Err(e) => errors.push(e.to_string()),
+        }
+    }
+    (count, errors)
+}
+
+/*
+ * @概述        按照URL的规则转义路径，保留/和不需要转义的字符
+ */
+pub fn encode_path(path: &Path) -> String {
+    let mut str = String::new();
+    for byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => str.push(*byte as char),
+            _ => str.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    str
+}
+
+pub fn decode_path(str: &str) -> PathBuf {
+    let bytes = str.as_bytes();
+    let mut out: Vec<u8> = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(hex) = str.get(i + 1..i + 3)
+            && let Ok(byte) = u8::from_str_radix(hex, 16) {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    PathBuf::from(OsStr::from_bytes(&out))
+}
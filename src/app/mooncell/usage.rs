@@ -0,0 +1,287 @@
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use super::filemanage::FileType;
+use super::filetype::TypeTable;
+
+/*
+ * @概述        磁盘占用分析，和ncdu一样
+ *              后台线程递归统计文件夹里每个条目占用的空间(st_blocks)，完成后得到一棵UsageTree
+ *              默认不进入其他文件系统的挂载点，有多个硬链接的文件只计算一次，不跟随符号链接
+ */
+
+// 没有计入大小的原因
+#[derive(Clone, Copy, PartialEq)]
+pub enum UsageFlag {
+    Normal,
+    Hardlink,    // 硬链接已经计算过
+    OtherFs,    // 其他文件系统，没有进入
+    Unreadable,    // 文件夹读取失败，大小不完整
+}
+
+struct UsageNode {
+    name: OsString,    // 保留原始的名字，不是UTF-8的名字也能还原路径
+    is_dir: bool,
+    file_type: FileType,
+    disk_usage: u64,    // 包括所有子条目
+    items: u64,    // 包括自身和所有子条目
+    flag: UsageFlag,
+    parent: Option<usize>,
+    children: Vec<usize>,
+}
+
+/*
+ * @概述        统计结果，节点保存在nodes里，0是开始统计的文件夹
+ */
+pub struct UsageTree {
+    root: PathBuf,
+    nodes: Vec<UsageNode>,
+}
+
+/*
+ * @概述        供界面显示的条目
+ */
+pub struct UsageEntry {
+    pub id: usize,
+    pub name: String,    // 显示用，不是UTF-8的部分被替换
+    pub is_dir: bool,
+    pub file_type: FileType,
+    pub disk_usage: u64,
+    pub items: u64,
+    pub flag: UsageFlag,
+}
+
+#[derive(Clone)]
+pub struct UsageProgress {
+    pub items: u64,
+    pub disk_usage: u64,
+    pub current: PathBuf,
+}
+
+// 统计时共用的参数和状态
+struct ScanContext<'a> {
+    root_dev: u64,    // 开始统计的文件夹所在的设备
+    cross_fs: bool,
+    seen: HashSet<(u64, u64)>,    // 计算过的硬链接(dev, inode)
+    file_types: &'a TypeTable,
+    progress: &'a Mutex<UsageProgress>,
+    cancel: &'a AtomicBool,
+}
+
+/*
+ * @概述        进行中的统计
+ */
+pub struct UsageScan {
+    progress: Arc<Mutex<UsageProgress>>,
+    result: Arc<Mutex<Option<UsageTree>>>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl UsageFlag {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            UsageFlag::Normal => "",
+            UsageFlag::Hardlink => "hardlink",
+            UsageFlag::OtherFs => "other fs",
+            UsageFlag::Unreadable => "unreadable",
+        }
+    }
+}
+
+impl UsageScan {
+    /*
+     * @概述        开始在后台统计root
+     * @参数1       &Path
+     * @参数2       bool，是否进入其他文件系统
+     * @参数3       Arc<TypeTable>，按名字和元数据判断文件类型，不读取内容
+     */
+    pub fn start(root: &Path, cross_fs: bool, file_types: Arc<TypeTable>) -> Self {
+        let progress = Arc::new(Mutex::new(UsageProgress { items: 0, disk_usage: 0, current: root.to_path_buf() }));
+        let result = Arc::new(Mutex::new(None));
+        let cancel = Arc::new(AtomicBool::new(false));
+        {
+            let root = root.to_path_buf();
+            let progress = Arc::clone(&progress);
+            let result = Arc::clone(&result);
+            let cancel = Arc::clone(&cancel);
+            thread::spawn(move || {
+                let tree = UsageTree::scan(&root, cross_fs, &file_types, &progress, &cancel);
+                if !cancel.load(Ordering::Relaxed)
+                    && let Ok(mut result) = result.lock() {
+                    *result = Some(tree);
+                }
+            });
+        }
+        Self { progress, result, cancel }
+    }
+
+    pub fn get_progress(&self) -> Option<UsageProgress> {
+        self.progress.lock().ok().map(|progress| progress.clone())
+    }
+
+    /*
+     * @概述        取出完成的结果
+     * @返回值      Option<UsageTree>，还没有完成时返回None
+     */
+    pub fn take_result(&self) -> Option<UsageTree> {
+        self.result.lock().ok()?.take()
+    }
+}
+
+impl Drop for UsageScan {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl UsageTree {
+    fn scan(root: &Path, cross_fs: bool, file_types: &TypeTable, progress: &Mutex<UsageProgress>, cancel: &AtomicBool) -> Self {
+        let mut tree = Self { root: root.to_path_buf(), nodes: Vec::new() };
+        let name = root.as_os_str().to_os_string();
+        match fs::symlink_metadata(root) {
+            Ok(metadata) => {
+                let mut context = ScanContext { root_dev: metadata.dev(), cross_fs, seen: HashSet::new(), file_types, progress, cancel };
+                tree.scan_entry(root, name, &metadata, None, &mut context);
+            }
+            Err(_) => tree.nodes.push(UsageNode {
+                name,
+                is_dir: true,
+                file_type: FileType::Folder,
+                disk_usage: 0,
+                items: 1,
+                flag: UsageFlag::Unreadable,
+                parent: None,
+                children: Vec::new(),
+            }),
+        }
+        tree
+    }
+
+    /*
+     * @概述        统计一个条目，文件夹递归统计子条目
+     * @返回值      usize，节点的下标
+     */
+    fn scan_entry(&mut self, path: &Path, name: OsString, metadata: &fs::Metadata, parent: Option<usize>, context: &mut ScanContext) -> usize {
+        let is_dir = metadata.is_dir();
+        let mut flag = UsageFlag::Normal;
+        let mut disk_usage = metadata.blocks() * 512;
+        if !is_dir && metadata.nlink() > 1 && !context.seen.insert((metadata.dev(), metadata.ino())) {
+            flag = UsageFlag::Hardlink;
+            disk_usage = 0;
+        }
+        if is_dir && !context.cross_fs && metadata.dev() != context.root_dev {
+            flag = UsageFlag::OtherFs;
+            disk_usage = 0;
+        }
+
+        let id = self.nodes.len();
+        let file_type = context.file_types.detect(path, &name.to_string_lossy(), metadata, false);
+        self.nodes.push(UsageNode { name, is_dir, file_type, disk_usage, items: 1, flag, parent, children: Vec::new() });
+        if let Ok(mut progress) = context.progress.lock() {
+            progress.items += 1;
+            progress.disk_usage += disk_usage;
+            if is_dir {
+                progress.current = path.to_path_buf();
+            }
+        }
+        if !is_dir || flag == UsageFlag::OtherFs || context.cancel.load(Ordering::Relaxed) {
+            return id;
+        }
+
+        let entries = match fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(_) => {
+                self.nodes[id].flag = UsageFlag::Unreadable;
+                return id;
+            }
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            let child_path = entry.path();
+            let metadata = match fs::symlink_metadata(&child_path) {
+                Ok(metadata) => metadata,
+                Err(_) => continue,
+            };
+            let child = self.scan_entry(&child_path, entry.file_name(), &metadata, Some(id), context);
+            let (child_usage, child_items) = (self.nodes[child].disk_usage, self.nodes[child].items);
+            let node = &mut self.nodes[id];
+            node.disk_usage += child_usage;
+            node.items += child_items;
+            node.children.push(child);
+        }
+        id
+    }
+
+    /*
+     * @概述        节点的完整路径
+     * @参数1       usize
+     * @返回值      PathBuf
+     */
+    pub fn get_path(&self, id: usize) -> PathBuf {
+        let mut names = Vec::new();
+        let mut now = id;
+        while let Some(parent) = self.nodes[now].parent {
+            names.push(self.nodes[now].name.as_os_str());
+            now = parent;
+        }
+        let mut path = self.root.clone();
+        path.extend(names.iter().rev());
+        path
+    }
+
+    pub fn get_parent(&self, id: usize) -> Option<usize> {
+        self.nodes.get(id)?.parent
+    }
+
+    pub fn get_entry(&self, id: usize) -> Option<UsageEntry> {
+        let node = self.nodes.get(id)?;
+        Some(UsageEntry {
+            id,
+            name: node.name.to_string_lossy().to_string(),
+            is_dir: node.is_dir,
+            file_type: node.file_type,
+            disk_usage: node.disk_usage,
+            items: node.items,
+            flag: node.flag,
+        })
+    }
+
+    /*
+     * @概述        子条目，按占用空间从大到小排序
+     * @参数1       usize
+     * @返回值      Vec<UsageEntry>
+     */
+    pub fn get_children(&self, id: usize) -> Vec<UsageEntry> {
+        let mut children: Vec<UsageEntry> = match self.nodes.get(id) {
+            Some(node) => node.children.iter().filter_map(|child| self.get_entry(*child)).collect(),
+            None => Vec::new(),
+        };
+        children.sort_by(|a, b| b.disk_usage.cmp(&a.disk_usage).then_with(|| a.name.cmp(&b.name)));
+        children
+    }
+
+    /*
+     * @概述        删除后从树里去掉这个节点，上层文件夹减去它的大小
+     * @参数1       usize，不能是0
+     */
+    pub fn remove(&mut self, id: usize) {
+        let parent = match self.get_parent(id) {
+            Some(parent) => parent,
+            None => return,
+        };
+        let (disk_usage, items) = (self.nodes[id].disk_usage, self.nodes[id].items);
+        self.nodes[parent].children.retain(|child| *child != id);
+        let mut now = Some(parent);
+        while let Some(ancestor) = now {
+            let node = &mut self.nodes[ancestor];
+            node.disk_usage = node.disk_usage.saturating_sub(disk_usage);
+            node.items = node.items.saturating_sub(items);
+            now = node.parent;
+        }
+    }
+}
@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+use super::mooncell::{FileUnit, ModeChange, Mooncell, PermissionChange, PermissionPreview};
+
+// 正在编辑的部分
+#[derive(Clone, Copy, PartialEq)]
+enum PermissionFocus {
+    Grid,    // rwx勾选
+    Octal,    // 八进制
+    Owner,
+    Group,
+}
+
+// 勾选表格的每一格对应的权限位，最后一行为setuid、setgid、sticky
+const MODE_BITS: [[u32; 3]; 4] = [
+    [0o400, 0o200, 0o100],
+    [0o040, 0o020, 0o010],
+    [0o004, 0o002, 0o001],
+    [0o4000, 0o2000, 0o1000],
+];
+const ROW_NAMES: [&str; 4] = ["owner", "group", "other", "special"];
+const COL_NAMES: [[&str; 3]; 4] = [
+    ["r", "w", "x"],
+    ["r", "w", "x"],
+    ["r", "w", "x"],
+    ["setuid", "setgid", "sticky"],
+];
+
+/*
+ * @概述        权限和所有者编辑窗口，勾选表格和八进制输入同步
+ *              文件和文件夹的权限分开编辑，没有修改过的一方保持原来的权限
+ *              勾选只改变勾选过的位，其他位每个文件保持原样；输入八进制时所有条目都改成这个权限
+ *              每次修改后重新生成预览，确认后执行
+ */
+pub struct Permissionview {
+    paths: Vec<PathBuf>,
+    file_mode: ModeChange,
+    dir_mode: ModeChange,
+    base_file_mode: u32,    // 勾选表格按这个权限显示，取第一个文件的权限
+    base_dir_mode: u32,
+    edit_dirs: bool,    // 正在编辑文件夹的权限
+    recursive: bool,
+    focus: PermissionFocus,
+    cursor: (usize, usize),    // 勾选表格中的(行, 列)
+    octal: LineInput,
+    owner: LineInput,
+    group: LineInput,
+    users: HashMap<u32, String>,
+    groups: HashMap<u32, String>,
+    previews: Vec<PermissionPreview>,
+    error: String,
+    table_state: TableState,
+}
+
+impl Permissionview {
+    /*
+     * @参数1       Vec<FileUnit>，需要修改的文件
+     * @参数2       &Mooncell，读取用户和组
+     */
+    pub fn new(files: Vec<FileUnit>, mooncell: &Mooncell) -> Self {
+        let base_file_mode = files.iter().find(|file| !file.path.is_dir()).map_or(0o644, |file| file.mode);
+        let base_dir_mode = files.iter().find(|file| file.path.is_dir()).map_or(0o755, |file| file.mode);
+        let edit_dirs = files.iter().all(|file| file.path.is_dir());
+        let mut view = Self {
+            paths: files.into_iter().map(|file| file.path).collect(),
+            file_mode: ModeChange::Keep,
+            dir_mode: ModeChange::Keep,
+            base_file_mode,
+            base_dir_mode,
+            edit_dirs,
+            recursive: false,
+            focus: PermissionFocus::Grid,
+            cursor: (0, 0),
+            octal: LineInput::new(""),
+            owner: LineInput::new(""),
+            group: LineInput::new(""),
+            users: mooncell.get_user_names(),
+            groups: mooncell.get_group_names(),
+            previews: Vec::new(),
+            error: String::new(),
+            table_state: TableState::default(),
+        };
+        view.sync_octal();
+        view.refresh(mooncell);
+        view
+    }
+
+    pub fn get_previews(&self) -> &[PermissionPreview] {
+        &self.previews
+    }
+
+    pub fn has_error(&self) -> bool {
+        !self.error.is_empty()
+    }
+
+    pub fn changed_count(&self) -> usize {
+        self.previews.iter().filter(|preview| preview.is_changed()).count()
+    }
+
+    /*
+     * @概述        处理编辑按键，修改后重新生成预览
+     * @参数1       KeyEvent
+     * @参数2       &Mooncell
+     */
+    pub fn handle_key(&mut self, key_event: KeyEvent, mooncell: &Mooncell) {
+        match key_event.code {
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    PermissionFocus::Grid => PermissionFocus::Octal,
+                    PermissionFocus::Octal => PermissionFocus::Owner,
+                    PermissionFocus::Owner => PermissionFocus::Group,
+                    PermissionFocus::Group => PermissionFocus::Grid,
+                };
+                return;
+            }
+            KeyCode::PageUp => self.table_state.scroll_up_by(10),
+            KeyCode::PageDown => self.table_state.scroll_down_by(10),
+            KeyCode::F(2) => {
+                self.edit_dirs = !self.edit_dirs;
+                self.sync_octal();
+            }
+            KeyCode::F(3) => self.recursive = !self.recursive,
+            KeyCode::F(4) => {
+                // 当前这一方恢复为不修改
+                *self.mode_mut() = ModeChange::Keep;
+                self.sync_octal();
+            }
+            _ => match self.focus {
+                PermissionFocus::Grid => {
+                    let (row, col) = self.cursor;
+                    match key_event.code {
+                        KeyCode::Up => self.cursor.0 = row.saturating_sub(1),
+                        KeyCode::Down => self.cursor.0 = (row + 1).min(MODE_BITS.len() - 1),
+                        KeyCode::Left => self.cursor.1 = col.saturating_sub(1),
+                        KeyCode::Right => self.cursor.1 = (col + 1).min(2),
+                        KeyCode::Char(' ') => {
+                            let bit = MODE_BITS[row][col];
+                            let is_on = self.current_mode() & bit != 0;
+                            let change = self.mode_mut();
+                            *change = match *change {
+                                ModeChange::Absolute(mode) => ModeChange::Absolute(mode ^ bit),
+                                ModeChange::Keep if is_on => ModeChange::Mask { set: 0, clear: bit },
+                                ModeChange::Keep => ModeChange::Mask { set: bit, clear: 0 },
+                                ModeChange::Mask { set, clear } if is_on => ModeChange::Mask { set: set & !bit, clear: clear | bit },
+                                ModeChange::Mask { set, clear } => ModeChange::Mask { set: set | bit, clear: clear & !bit },
+                            };
+                            if *change == (ModeChange::Mask { set: 0, clear: 0 }) {
+                                *change = ModeChange::Keep;
+                            }
+                            self.sync_octal();
+                        }
+                        _ => return,
+                    }
+                }
+                PermissionFocus::Octal => {
+                    if !self.octal.handle_key(key_event) {
+                        return;
+                    }
+                    let text = self.octal.get_text().to_string();
+                    match u32::from_str_radix(&text, 8) {
+                        Ok(mode) if (3..=4).contains(&text.len()) && mode <= 0o7777 => *self.mode_mut() = ModeChange::Absolute(mode),
+                        _ => {
+                            self.error = format!("\"{}\" is not a valid octal mode", text);
+                            return;
+                        }
+                    }
+                }
+                PermissionFocus::Owner => {
+                    if !self.owner.handle_key(key_event) {
+                        return;
+                    }
+                }
+                PermissionFocus::Group => {
+                    if !self.group.handle_key(key_event) {
+                        return;
+                    }
+                }
+            },
+        }
+        self.refresh(mooncell);
+    }
+
+    /*
+     * @概述        按照当前的设置重新生成预览，用户或组不存在时提示错误
+     * @参数1       &Mooncell
+     */
+    fn refresh(&mut self, mooncell: &Mooncell) {
+        let parse = |text: &str, parser: &dyn Fn(&str) -> Result<u32, super::TopError>| {
+            let text = text.trim();
+            if text.is_empty() { Ok(None) } else { parser(text).map(Some) }
+        };
+        let owner = parse(self.owner.get_text(), &|text| mooncell.parse_user(text));
+        let group = parse(self.group.get_text(), &|text| mooncell.parse_group(text));
+        let (owner, group) = match (owner, group) {
+            (Ok(owner), Ok(group)) => (owner, group),
+            (Err(e), _) | (_, Err(e)) => {
+                self.error = e.to_string();
+                return;
+            }
+        };
+        self.error.clear();
+        let change = PermissionChange {
+            file_mode: self.file_mode,
+            dir_mode: self.dir_mode,
+            owner,
+            group,
+            recursive: self.recursive,
+        };
+        self.previews = mooncell.fm_permission_preview(&self.paths, &change);
+    }
+
+    /*
+     * @概述        勾选表格和八进制显示的权限
+     */
+    fn current_mode(&self) -> u32 {
+        match self.edit_dirs {
+            true => self.dir_mode.apply(self.base_dir_mode),
+            false => self.file_mode.apply(self.base_file_mode),
+        }
+    }
+
+    fn mode_mut(&mut self) -> &mut ModeChange {
+        match self.edit_dirs {
+            true => &mut self.dir_mode,
+            false => &mut self.file_mode,
+        }
+    }
+
+    fn sync_octal(&mut self) {
+        let mode = self.current_mode();
+        self.octal = LineInput::new(&format!("{:04o}", mode));
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let layout_permission = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(7),    // 编辑
+                Constraint::Fill(1),    // 预览
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+        let layout_edit = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(vec![
+                Constraint::Length(40),    // 勾选表格
+                Constraint::Fill(1),    // 八进制、所有者
+            ])
+            .split(layout_permission[0]);
+
+        frame.render_widget(Clear, area);
+
+        // 勾选表格，勾选过的位加粗
+        let mode = self.current_mode();
+        let edited = match self.edit_dirs {
+            true => self.dir_mode,
+            false => self.file_mode,
+        };
+        let edited_bits = match edited {
+            ModeChange::Keep => 0,
+            ModeChange::Mask { set, clear } => set | clear,
+            ModeChange::Absolute(_) => 0o7777,
+        };
+        let mut grid_lines: Vec<Line> = Vec::new();
+        for (row, bits) in MODE_BITS.iter().enumerate() {
+            let mut spans = vec![Span::raw(format!("{:<8}", ROW_NAMES[row]))];
+            for (col, bit) in bits.iter().enumerate() {
+                let check = if mode & bit != 0 { "[x]" } else { "[ ]" };
+                let mut style = Style::default();
+                if edited_bits & bit != 0 {
+                    style = style.add_modifier(Modifier::BOLD);
+                }
+                if self.focus == PermissionFocus::Grid && self.cursor == (row, col) {
+                    style = style.add_modifier(Modifier::REVERSED);
+                }
+                spans.push(Span::styled(format!("{} {}", check, COL_NAMES[row][col]), style));
+                spans.push(Span::raw("  "));
+            }
+            grid_lines.push(Line::from(spans));
+        }
+        let grid_title = format!(
+            "{} mode{}",
+            if self.edit_dirs { "folder" } else { "file" },
+            match edited {
+                ModeChange::Keep => " (unchanged)",
+                ModeChange::Mask { .. } => " (toggled bits)",
+                ModeChange::Absolute(_) => " (set to octal)",
+            },
+        );
+        let grid_p = Paragraph::new(grid_lines)
+            .block(Block::bordered().title(grid_title));
+        frame.render_widget(grid_p, layout_edit[0]);
+
+        // 八进制、所有者、组
+        let field = |focus: PermissionFocus, input: &LineInput, prompt: &str| {
+            if self.focus == focus {
+                input.to_line(prompt).style(Style::default().fg(Color::Yellow))
+            } else {
+                Line::from(format!("{}{}", prompt, input.get_text()))
+            }
+        };
+        let field_lines = vec![
+            field(PermissionFocus::Octal, &self.octal, "octal: "),
+            field(PermissionFocus::Owner, &self.owner, "owner: "),
+            field(PermissionFocus::Group, &self.group, "group: "),
+            Line::from(format!("recursive: {}", if self.recursive { "on" } else { "off" })),
+        ];
+        let field_p = Paragraph::new(field_lines)
+            .block(Block::bordered().title(format!("{} item(s), empty owner/group keeps them", self.paths.len())));
+        frame.render_widget(field_p, layout_edit[1]);
+
+        // 预览
+        let rows: Vec<Row> = self.previews
+            .iter()
+            .map(|preview| {
+                let kind = if preview.is_symlink { 'l' } else if preview.is_dir { 'd' } else { '-' };
+                let mode_str = if preview.old_mode == preview.new_mode {
+                    Mooncell::mode_to_string(preview.old_mode, kind)
+                } else {
+                    format!("{} -> {}", Mooncell::mode_to_string(preview.old_mode, kind), Mooncell::mode_to_string(preview.new_mode, kind))
+                };
+                let user = |uid: u32| self.users.get(&uid).cloned().unwrap_or_else(|| uid.to_string());
+                let group = |gid: u32| self.groups.get(&gid).cloned().unwrap_or_else(|| gid.to_string());
+                let owner_str = if preview.old_uid == preview.new_uid && preview.old_gid == preview.new_gid {
+                    format!("{}:{}", user(preview.old_uid), group(preview.old_gid))
+                } else {
+                    format!("{}:{} -> {}:{}", user(preview.old_uid), group(preview.old_gid), user(preview.new_uid), group(preview.new_gid))
+                };
+                let style = if preview.is_changed() { Style::default().fg(Color::Green) } else { Style::default().fg(Color::DarkGray) };
+                Row::new(vec![preview.path.to_string_lossy().to_string(), mode_str, owner_str]).style(style)
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Length(24),
+            Constraint::Length(30),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Path", "Mode", "Owner"])
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .block(Block::bordered().title(format!("preview: {} of {} changed", self.changed_count(), self.previews.len())));
+        frame.render_stateful_widget(table, layout_permission[1], &mut self.table_state);
+
+        // tips
+        let (tips_str, tips_style) = if self.error.is_empty() {
+            (
+                String::from("next field[tab]  toggle[space]  files/folders[F2]  recursive[F3]  keep mode[F4]  apply[enter]  cancel[esc]"),
+                Style::default().bg(Color::DarkGray),
+            )
+        } else {
+            (self.error.clone(), Style::default().bg(Color::DarkGray).fg(Color::Red))
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(tips_style);
+        frame.render_widget(tips_p, layout_permission[2]);
+    }
+}
@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Clear, List, ListState, Paragraph},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+
+/*
+ * @概述        快速跳转窗口，没有输入时先显示书签，下面是按frecency排序的访问过的文件夹
+ *              输入空格分隔的关键字过滤访问过的文件夹
+ */
+pub struct Placesview {
+    input: LineInput,
+    entries: Vec<(Option<char>, PathBuf)>,    // (书签的字符, 路径)，访问过的文件夹没有字符
+    list_state: ListState,
+}
+
+pub const PLACES_LIMIT: usize = 200;    // 最多显示的访问过的文件夹
+
+impl Placesview {
+    pub fn new() -> Self {
+        Self {
+            input: LineInput::new(""),
+            entries: Vec::new(),
+            list_state: ListState::default(),
+        }
+    }
+
+    /*
+     * @概述        处理输入
+     * @参数1       KeyEvent
+     * @返回值      bool，输入是否改变，改变后需要重新设置条目
+     */
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        let old = self.input.get_text().to_string();
+        self.input.handle_key(key_event);
+        old != self.input.get_text()
+    }
+
+    pub fn get_query(&self) -> &str {
+        self.input.get_text()
+    }
+
+    /*
+     * @参数1       Vec<(char, PathBuf)>，书签，有输入时不显示
+     * @参数2       Vec<PathBuf>，访问过的文件夹
+     */
+    pub fn set_entries(&mut self, bookmarks: Vec<(char, PathBuf)>, recent: Vec<PathBuf>) {
+        self.entries.clear();
+        if self.input.get_text().trim().is_empty() {
+            self.entries.extend(bookmarks.into_iter().map(|(key, path)| (Some(key), path)));
+        }
+        self.entries.extend(recent.into_iter().map(|path| (None, path)));
+        if self.entries.is_empty() {
+            self.list_state.select(None);
+        } else {
+            let pos = self.list_state.selected().unwrap_or(0).min(self.entries.len() - 1);
+            self.list_state.select(Some(pos));
+        }
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(i) if i + 1 < self.entries.len() => i + 1,
+            _ => 0,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.list_state.selected() {
+            Some(0) | None => self.entries.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.list_state.select(Some(i));
+    }
+
+    pub fn get_selected(&self) -> Option<&(Option<char>, PathBuf)> {
+        self.entries.get(self.list_state.selected()?)
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let layout_places = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(3),    // 输入
+                Constraint::Fill(1),    // 书签和访问过的文件夹
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        let input_p = Paragraph::new(self.input.to_line("> "))
+            .block(Block::bordered().title("jump to"));
+        frame.render_widget(input_p, layout_places[0]);
+
+        let items: Vec<Line> = self.entries
+            .iter()
+            .map(|(key, path)| match key {
+                Some(key) => Line::from(vec![
+                    Span::raw(format!("'{} ", key)).fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                    Span::raw(path.to_string_lossy().to_string()),
+                ]),
+                None => Line::from(format!("   {}", path.to_string_lossy())),
+            })
+            .collect();
+        let list = List::new(items)
+            .block(Block::bordered().title("bookmarks & recent directories"))
+            .highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(">> ");
+        frame.render_stateful_widget(list, layout_places[1], &mut self.list_state);
+
+        let tips_p = Paragraph::new("jump[enter]  forget[delete]  move[up/down]  cancel[esc]")
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(Style::default().bg(Color::DarkGray));
+        frame.render_widget(tips_p, layout_places[2]);
+    }
+}
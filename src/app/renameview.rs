@@ -0,0 +1,232 @@
+use std::path::PathBuf;
+
+use crossterm::event::KeyEvent;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::Line,
+    widgets::{Block, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::lineinput::LineInput;
+use super::TopError;
+use super::mooncell::{CaseMode, RenamePreview, RenameRule};
+
+/*
+ * @概述        批量重命名窗口，上面编辑规则，下面实时显示重命名前后的对比和冲突
+ *              从编辑器返回的结果也在这里确认，此时规则不能修改
+ */
+pub struct Renameview {
+    paths: Vec<PathBuf>,
+    find: LineInput,
+    replace: LineInput,
+    focus_replace: bool,    // 正在编辑replace
+    use_regex: bool,
+    case: CaseMode,
+    from_editor: bool,    // 编辑器模式的结果
+    previews: Vec<RenamePreview>,
+    error: String,    // 规则错误，例如正则表达式写错
+    table_state: TableState,
+}
+
+impl Renameview {
+    pub fn new(paths: Vec<PathBuf>) -> Self {
+        Self {
+            paths,
+            find: LineInput::new(""),
+            replace: LineInput::new(""),
+            focus_replace: false,
+            use_regex: false,
+            case: CaseMode::Keep,
+            from_editor: false,
+            previews: Vec::new(),
+            error: String::new(),
+            table_state: TableState::default(),
+        }
+    }
+
+    /*
+     * @概述        显示编辑器模式修改后的结果
+     * @参数1       Vec<PathBuf>，交给编辑器的文件
+     * @参数2       Vec<RenamePreview>
+     */
+    pub fn from_editor(paths: Vec<PathBuf>, previews: Vec<RenamePreview>) -> Self {
+        let mut view = Self::new(paths);
+        view.from_editor = true;
+        view.previews = previews;
+        view
+    }
+
+    pub fn is_from_editor(&self) -> bool {
+        self.from_editor
+    }
+
+    pub fn get_paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    pub fn get_previews(&self) -> &[RenamePreview] {
+        &self.previews
+    }
+
+    pub fn get_rule(&self) -> RenameRule {
+        RenameRule {
+            find: self.find.get_text().to_string(),
+            replace: self.replace.get_text().to_string(),
+            use_regex: self.use_regex,
+            case: self.case,
+        }
+    }
+
+    /*
+     * @概述        更新预览，规则有错误时保留上一次的预览
+     * @参数1       Result<Vec<RenamePreview>, TopError>
+     */
+    pub fn set_previews(&mut self, previews: Result<Vec<RenamePreview>, TopError>) {
+        match previews {
+            Ok(previews) => {
+                self.previews = previews;
+                self.error.clear();
+            }
+            Err(e) => self.error = e.to_string(),
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = error;
+    }
+
+    pub fn switch_focus(&mut self) {
+        self.focus_replace = !self.focus_replace;
+    }
+
+    pub fn switch_regex(&mut self) {
+        self.use_regex = !self.use_regex;
+    }
+
+    pub fn next_case(&mut self) {
+        self.case = self.case.next();
+    }
+
+    /*
+     * @概述        编辑正在输入的规则
+     * @参数1       KeyEvent
+     * @返回值      bool，按键是否被处理
+     */
+    pub fn handle_key(&mut self, key_event: KeyEvent) -> bool {
+        if self.from_editor {
+            return false;
+        }
+        if self.focus_replace {
+            self.replace.handle_key(key_event)
+        } else {
+            self.find.handle_key(key_event)
+        }
+    }
+
+    pub fn next(&mut self) {
+        let i = match self.table_state.selected() {
+            Some(i) if i + 1 < self.previews.len() => i + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.table_state.selected() {
+            Some(0) | None => self.previews.len().saturating_sub(1),
+            Some(i) => i - 1,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn changed_count(&self) -> usize {
+        self.previews.iter().filter(|preview| preview.is_changed()).count()
+    }
+
+    pub fn problem_count(&self) -> usize {
+        self.previews.iter().filter(|preview| preview.problem.is_some()).count()
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect) {
+        let rule_height = if self.from_editor { 0 } else { 4 };
+        let layout_rename = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(rule_height),    // 规则
+                Constraint::Fill(1),    // 预览
+                Constraint::Length(1),    // 提示
+            ])
+            .split(area);
+
+        frame.render_widget(Clear, area);
+
+        // 规则
+        if !self.from_editor {
+            let focus_style = Style::default().fg(Color::Yellow);
+            let (find_line, replace_line) = if self.focus_replace {
+                (Line::from(format!("find:    {}", self.find.get_text())), self.replace.to_line("replace: ").style(focus_style))
+            } else {
+                (self.find.to_line("find:    ").style(focus_style), Line::from(format!("replace: {}", self.replace.get_text())))
+            };
+            let rule_p = Paragraph::new(vec![find_line, replace_line])
+                .block(Block::bordered().title(format!(
+                    "bulk rename {} item(s)    regex: {}    case: {}",
+                    self.paths.len(),
+                    if self.use_regex { "on" } else { "off" },
+                    self.case.to_str(),
+                )));
+            frame.render_widget(rule_p, layout_rename[0]);
+        }
+
+        // 预览
+        let rows: Vec<Row> = self.previews
+            .iter()
+            .map(|preview| {
+                let name = |path: &PathBuf| path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+                let style = match (&preview.problem, preview.is_changed()) {
+                    (Some(_), _) => Style::default().fg(Color::Red),
+                    (None, true) => Style::default().fg(Color::Green),
+                    (None, false) => Style::default().fg(Color::DarkGray),
+                };
+                Row::new(vec![
+                    name(&preview.from),
+                    name(&preview.to),
+                    preview.problem.clone().unwrap_or_default(),
+                ])
+                .style(style)
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Fill(1),
+            Constraint::Length(16),
+        ];
+        let title = format!("preview: {} changed, {} conflict(s)", self.changed_count(), self.problem_count());
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Before", "After", "Problem"])
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .block(Block::bordered().title(title))
+            .row_highlight_style(Style::default().bg(Color::DarkGray));
+        frame.render_stateful_widget(table, layout_rename[1], &mut self.table_state);
+
+        // tips
+        let (tips_str, tips_style) = if !self.error.is_empty() {
+            (self.error.clone(), Style::default().bg(Color::DarkGray).fg(Color::Red))
+        } else if self.from_editor {
+            (String::from("apply[enter]  cancel[esc]"), Style::default().bg(Color::DarkGray))
+        } else {
+            (
+                String::from("switch field[tab]  regex[F2]  case[F3]  apply[enter]  cancel[esc]    {n} {n:3:1} {name} {ext} {date} {mtime}"),
+                Style::default().bg(Color::DarkGray),
+            )
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(tips_style);
+        frame.render_widget(tips_p, layout_rename[2]);
+    }
+}
@@ -0,0 +1,211 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::mooncell::{SocketProtocol, SocketUnit};
+
+/*
+ * @概述        socket列表的显示状态
+ *              filter为空时显示全部，按端口/pid/进程名过滤
+ */
+pub struct Socketview {
+    table_state: TableState,
+    filter: String,    // 过滤字符串
+    filter_editing: bool,    // 是否正在输入过滤字符串
+    show_all: bool,    // false时只显示监听和已建立的连接
+    protocol_filter: ProtocolFilter,    // 按协议过滤
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProtocolFilter {
+    All,
+    Tcp,
+    Udp,
+    Unix,
+}
+
+impl Socketview {
+    pub fn new() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select_first();
+        Self {
+            table_state,
+            filter: String::new(),
+            filter_editing: false,
+            show_all: false,
+            protocol_filter: ProtocolFilter::All,
+        }
+    }
+
+    pub fn is_filter_editing(&self) -> bool {
+        self.filter_editing
+    }
+
+    pub fn start_filter_edit(&mut self) {
+        self.filter_editing = true;
+    }
+
+    pub fn stop_filter_edit(&mut self) {
+        self.filter_editing = false;
+    }
+
+    pub fn filter_push(&mut self, ch: char) {
+        self.filter.push(ch);
+        self.table_state.select_first();
+    }
+
+    pub fn filter_pop(&mut self) {
+        self.filter.pop();
+        self.table_state.select_first();
+    }
+
+    pub fn filter_clear(&mut self) {
+        self.filter.clear();
+        self.filter_editing = false;
+        self.table_state.select_first();
+    }
+
+    pub fn switch_show_all(&mut self) {
+        self.show_all = !self.show_all;
+    }
+
+    /*
+     * @概述        按 all -> tcp -> udp -> unix 的顺序切换协议过滤
+     */
+    pub fn switch_protocol(&mut self) {
+        self.protocol_filter = match self.protocol_filter {
+            ProtocolFilter::All => ProtocolFilter::Tcp,
+            ProtocolFilter::Tcp => ProtocolFilter::Udp,
+            ProtocolFilter::Udp => ProtocolFilter::Unix,
+            ProtocolFilter::Unix => ProtocolFilter::All,
+        };
+        self.table_state.select_first();
+    }
+
+    pub fn next(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(0) | None => len.saturating_sub(1),
+            Some(i) => (i - 1).min(len.saturating_sub(1)),
+        };
+        self.table_state.select(Some(i));
+    }
+
+    /*
+     * @概述        按当前的过滤条件筛选socket
+     * @参数1       &[SocketUnit]
+     * @返回值      Vec<SocketUnit>
+     */
+    pub fn visible(&self, list: &[SocketUnit]) -> Vec<SocketUnit> {
+        list.iter()
+            .filter(|unit| self.show_all || unit.is_listen_or_established())
+            .filter(|unit| match self.protocol_filter {
+                ProtocolFilter::All => true,
+                ProtocolFilter::Tcp => unit.protocol.is_tcp(),
+                ProtocolFilter::Udp => unit.protocol.is_udp(),
+                ProtocolFilter::Unix => unit.protocol == SocketProtocol::Unix,
+            })
+            .filter(|unit| unit.is_match(&self.filter))
+            .cloned()
+            .collect()
+    }
+
+    /*
+     * @概述        返回当前选中的socket
+     */
+    pub fn get_selected(&self, list: &[SocketUnit]) -> Option<SocketUnit> {
+        let pos = self.table_state.selected()?;
+        self.visible(list).get(pos).cloned()
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, list: &[SocketUnit]) {
+        let layout_socket = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Length(3),    // 过滤
+                Constraint::Fill(1),    // socket列表
+                Constraint::Length(1),    // 提示
+            ])
+            .split(frame.area());
+
+        // 过滤条件
+        let protocol_str = match self.protocol_filter {
+            ProtocolFilter::All => "all",
+            ProtocolFilter::Tcp => "tcp",
+            ProtocolFilter::Udp => "udp",
+            ProtocolFilter::Unix => "unix",
+        };
+        let state_str = if self.show_all { "all states" } else { "listening & established" };
+        let cursor = if self.filter_editing { "_" } else { "" };
+        let filter_p = Paragraph::new(format!("{}{}", self.filter, cursor))
+            .block(Block::bordered().title(format!("filter (port / pid / process)  [{}, {}]", protocol_str, state_str)));
+        frame.render_widget(filter_p, layout_socket[0]);
+
+        // socket列表
+        let visible = self.visible(list);
+        if let Some(pos) = self.table_state.selected()
+            && pos >= visible.len() {
+            self.table_state.select(Some(visible.len().saturating_sub(1)));
+        }
+        let rows: Vec<Row> = visible
+            .iter()
+            .map(|unit| {
+                let process = match unit.pid {
+                    Some(pid) => format!("{}/{}", pid, unit.process),
+                    None => String::from("-"),
+                };
+                Row::new(vec![
+                    unit.protocol.to_str().to_string(),
+                    unit.state.clone(),
+                    unit.recv_q.to_string(),
+                    unit.send_q.to_string(),
+                    unit.local.clone(),
+                    unit.remote.clone(),
+                    process,
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Length(5),
+            Constraint::Length(11),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Fill(1),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Netid", "State", "Recv-Q", "Send-Q", "Local", "Peer", "Process"])
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .block(Block::bordered().title(format!("sockets ({}/{})", visible.len(), list.len())))
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_stateful_widget(table, layout_socket[1], &mut self.table_state);
+
+        // tips
+        let tips_str = if self.filter_editing {
+            String::from("finish[enter]    clear[esc]")
+        } else {
+            String::from("filter[/]    protocol[p]    all states[a]    open cwd[w]    open exe[e]    back to top[backspace]    exit[esc]")
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center);
+        frame.render_widget(tips_p, layout_socket[2]);
+    }
+}
@@ -0,0 +1,158 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::mooncell::TrashItem;
+
+// 等待确认的永久删除
+#[derive(Clone, Copy, PartialEq)]
+pub enum TrashPending {
+    Null,
+    Remove,    // 永久删除选中的条目
+    Empty,    // 清空回收站
+}
+
+/*
+ * @概述        回收站列表，显示原路径和删除时间，可以还原、永久删除和清空
+ */
+pub struct Trashview {
+    table_state: TableState,
+    pending: TrashPending,
+    message: String,    // 操作结果的提示
+}
+
+impl Trashview {
+    pub fn new() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select_first();
+        Self {
+            table_state,
+            pending: TrashPending::Null,
+            message: String::new(),
+        }
+    }
+
+    pub fn next(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(0) | None => len.saturating_sub(1),
+            Some(i) => (i - 1).min(len.saturating_sub(1)),
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn get_selected(&self, items: &[TrashItem]) -> Option<TrashItem> {
+        items.get(self.table_state.selected()?).cloned()
+    }
+
+    /*
+     * @概述        准备永久删除，需要再确认一次
+     */
+    pub fn ready(&mut self, pending: TrashPending) {
+        self.pending = pending;
+    }
+
+    pub fn take_pending(&mut self) -> TrashPending {
+        std::mem::replace(&mut self.pending, TrashPending::Null)
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub fn draw(&mut self, frame: &mut Frame, items: &[TrashItem]) {
+        let layout_trash = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 回收站列表
+                Constraint::Length(4),    // 选中条目的详细信息
+                Constraint::Length(1),    // 提示
+            ])
+            .split(frame.area());
+
+        if let Some(pos) = self.table_state.selected()
+            && pos >= items.len() {
+            self.table_state.select(Some(items.len().saturating_sub(1)));
+        }
+
+        // 回收站列表
+        let rows: Vec<Row> = items
+            .iter()
+            .map(|item| {
+                let name = match item.original_path.file_name() {
+                    Some(name) => name.to_string_lossy().to_string(),
+                    None => item.name.clone(),
+                };
+                Row::new(vec![
+                    if item.is_dir { format!("{}/", name) } else { name },
+                    item.original_path.parent().map(|parent| parent.to_string_lossy().to_string()).unwrap_or_default(),
+                    Self::date_to_string(item),
+                ])
+            })
+            .collect();
+        let widths = [
+            Constraint::Fill(1),
+            Constraint::Fill(2),
+            Constraint::Length(19),
+        ];
+        let table = Table::new(rows, widths)
+            .header(
+                Row::new(vec!["Name", "Original location", "Deleted"])
+                    .style(Style::default().fg(Color::Yellow)),
+            )
+            .block(Block::bordered().title(format!("trash ({})", items.len())))
+            .row_highlight_style(
+                Style::default()
+                    .bg(Color::LightBlue)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            );
+        frame.render_stateful_widget(table, layout_trash[0], &mut self.table_state);
+
+        // 详细信息
+        let detail_str = match self.get_selected(items) {
+            Some(item) => format!(
+                "{}\nin {}",
+                item.original_path.to_string_lossy(),
+                item.trash_dir.to_string_lossy(),
+            ),
+            None => String::from("trash is empty"),
+        };
+        let detail_p = Paragraph::new(detail_str)
+            .block(Block::bordered().title("detail"));
+        frame.render_widget(detail_p, layout_trash[1]);
+
+        // tips
+        let tips_str = match (self.pending, self.get_selected(items)) {
+            (TrashPending::Remove, Some(item)) => format!("delete {} permanently? [y/n]", item.original_path.to_string_lossy()),
+            (TrashPending::Empty, _) => format!("permanently delete all {} item(s)? [y/n]", items.len()),
+            _ if !self.message.is_empty() => self.message.clone(),
+            _ => String::from("restore[r]    delete permanently[d]    empty trash[E]    back to filemanage[backspace]    exit[esc]"),
+        };
+        let tips_style = match self.pending {
+            TrashPending::Null => Style::default(),
+            _ => Style::default().fg(Color::Red),
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(tips_style);
+        frame.render_widget(tips_p, layout_trash[2]);
+    }
+
+    fn date_to_string(item: &TrashItem) -> String {
+        match item.deletion_date {
+            Some(date) => date.format("%Y-%m-%d %H:%M:%S").to_string(),
+            None => String::from("-"),
+        }
+    }
+}
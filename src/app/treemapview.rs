@@ -0,0 +1,202 @@
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use super::mooncell::{FileType, Mooncell, UsageEntry};
+
+/*
+ * @概述        磁盘占用的矩形树图，使用squarified算法让矩形尽量接近正方形
+ *              终端的字符高度约为宽度的两倍，计算时把高度乘2
+ *              颜色表示文件类型，太小画不出来的条目不显示
+ */
+pub struct Treemapview {
+    rects: Vec<(usize, Rect)>,    // 上一次绘制的(条目下标, 位置)，用于方向键移动
+}
+
+const CELL_RATIO: f64 = 2.0;    // 字符的高宽比
+
+impl Treemapview {
+    pub fn new() -> Self {
+        Self { rects: Vec::new() }
+    }
+
+    /*
+     * @参数2       Rect
+     * @参数3       &[UsageEntry]，按大小从大到小排序
+     * @参数4       Option<usize>，选中的条目
+     * @参数5       String，标题
+     * @参数6       &[(FileType, Color)]，每种文件类型的颜色
+     */
+    pub fn draw(&mut self, frame: &mut Frame, area: Rect, entries: &[UsageEntry], selected: Option<usize>, title: String, type_colors: &[(FileType, Color)]) {
+        let block = Block::bordered()
+            .title(title)
+            .title_bottom(Self::legend(entries, type_colors));
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let sizes: Vec<f64> = entries.iter().map(|entry| entry.disk_usage as f64).collect();
+        self.rects = squarify(&sizes, inner);
+        for (i, rect) in &self.rects {
+            let entry = &entries[*i];
+            let mut style = Style::default().bg(Self::type_color(entry.file_type, type_colors)).fg(Color::Black);
+            if selected == Some(*i) {
+                style = style.bg(Color::White).add_modifier(Modifier::BOLD);
+            }
+            let name = if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() };
+            let lines = vec![Line::from(name), Line::from(Mooncell::bytes_to_string(entry.disk_usage))];
+            // 右边和下边画线分开相邻的矩形
+            let mut borders = Borders::NONE;
+            if rect.width > 2 {
+                borders |= Borders::RIGHT;
+            }
+            if rect.height > 2 {
+                borders |= Borders::BOTTOM;
+            }
+            let rect_p = Paragraph::new(lines)
+                .style(style)
+                .block(Block::new().borders(borders).border_style(style.fg(Color::DarkGray)));
+            frame.render_widget(rect_p, *rect);
+        }
+    }
+
+    /*
+     * @概述        按方向键找到相邻的矩形
+     * @参数1       usize，当前选中的条目
+     * @参数2       KeyCode，方向键
+     * @返回值      Option<usize>，那个方向上最近的条目
+     */
+    pub fn neighbor(&self, selected: usize, key: KeyCode) -> Option<usize> {
+        let center = |rect: &Rect| (rect.x as f64 + rect.width as f64 / 2.0, rect.y as f64 + rect.height as f64 / 2.0);
+        // 选中的条目太小没有画出来时从最大的开始
+        let now = match self.rects.iter().find(|(i, _)| *i == selected) {
+            Some((_, now)) => now,
+            None => return self.rects.first().map(|(i, _)| *i),
+        };
+        let (now_x, now_y) = center(now);
+        self.rects
+            .iter()
+            .filter(|(i, _)| *i != selected)
+            .filter_map(|(i, rect)| {
+                let (x, y) = center(rect);
+                // (前进的距离, 偏离的距离)，竖直方向的距离按字符高度换算
+                let (ahead, aside) = match key {
+                    KeyCode::Left => (now_x - x, (y - now_y) * CELL_RATIO),
+                    KeyCode::Right => (x - now_x, (y - now_y) * CELL_RATIO),
+                    KeyCode::Up => ((now_y - y) * CELL_RATIO, x - now_x),
+                    KeyCode::Down => ((y - now_y) * CELL_RATIO, x - now_x),
+                    _ => return None,
+                };
+                if ahead <= 0.0 {
+                    return None;
+                }
+                Some((ahead + aside.abs() * 2.0, *i))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0))
+            .map(|(_, i)| i)
+    }
+
+    // 没有设置颜色的类型用灰色，否则看不出矩形
+    fn type_color(file_type: FileType, type_colors: &[(FileType, Color)]) -> Color {
+        match type_colors.iter().find(|(key, _)| *key == file_type) {
+            Some((_, color)) if *color != Color::Reset => *color,
+            _ => Color::Gray,
+        }
+    }
+
+    // 只显示出现了的类型
+    fn legend(entries: &[UsageEntry], type_colors: &[(FileType, Color)]) -> Line<'static> {
+        Line::from(
+            FileType::ALL
+                .iter()
+                .filter(|file_type| entries.iter().any(|entry| entry.file_type == **file_type))
+                .map(|file_type| {
+                    Span::styled(
+                        format!(" {} ", file_type.to_str()),
+                        Style::default().bg(Self::type_color(*file_type, type_colors)).fg(Color::Black),
+                    )
+                })
+                .collect::<Vec<_>>(),
+        )
+    }
+}
+
+/*
+ * @概述        squarified treemap，按顺序把条目放进一行，加入下一个会让最差的长宽比变大时换行
+ * @参数1       &[f64]，从大到小排序的大小
+ * @参数2       Rect，可以使用的区域
+ * @返回值      Vec<(usize, Rect)>，(下标, 位置)，太小的条目不在里面
+ */
+fn squarify(sizes: &[f64], area: Rect) -> Vec<(usize, Rect)> {
+    let total: f64 = sizes.iter().sum();
+    let mut rects = Vec::new();
+    if total <= 0.0 || area.width == 0 || area.height == 0 {
+        return rects;
+    }
+    // 在高度乘2的空间里计算，面积按比例换算
+    let (mut x, mut y) = (area.x as f64, area.y as f64 * CELL_RATIO);
+    let (mut width, mut height) = (area.width as f64, area.height as f64 * CELL_RATIO);
+    let scale = width * height / total;
+    let items: Vec<(usize, f64)> = sizes.iter().enumerate().filter(|(_, size)| **size > 0.0).map(|(i, size)| (i, size * scale)).collect();
+
+    let worst = |row: &[(usize, f64)], side: f64| -> f64 {
+        let sum: f64 = row.iter().map(|(_, area)| area).sum();
+        let max = row.iter().map(|(_, area)| *area).fold(f64::MIN, f64::max);
+        let min = row.iter().map(|(_, area)| *area).fold(f64::MAX, f64::min);
+        f64::max(side * side * max / (sum * sum), sum * sum / (side * side * min))
+    };
+
+    let mut start = 0;
+    while start < items.len() && width > 0.0 && height > 0.0 {
+        let side = width.min(height);
+        let mut end = start + 1;
+        while end < items.len() && worst(&items[start..=end], side) <= worst(&items[start..end], side) {
+            end += 1;
+        }
+
+        // 沿着短边放一行
+        let row = &items[start..end];
+        let row_sum: f64 = row.iter().map(|(_, area)| area).sum();
+        let thickness = row_sum / side;
+        let mut offset = 0.0;
+        for (i, item_area) in row {
+            let length = item_area / thickness;
+            let (rx, ry, rw, rh) = if width >= height {
+                (x, y + offset, thickness, length)
+            } else {
+                (x + offset, y, length, thickness)
+            };
+            offset += length;
+            if let Some(rect) = to_cells(rx, ry, rw, rh) {
+                rects.push((*i, rect));
+            }
+        }
+        if width >= height {
+            x += thickness;
+            width -= thickness;
+        } else {
+            y += thickness;
+            height -= thickness;
+        }
+        start = end;
+    }
+    rects
+}
+
+/*
+ * @概述        把计算出的位置取整为字符，高度换算回字符
+ */
+fn to_cells(x: f64, y: f64, width: f64, height: f64) -> Option<Rect> {
+    let left = x.round();
+    let right = (x + width).round();
+    let top = (y / CELL_RATIO).round();
+    let bottom = ((y + height) / CELL_RATIO).round();
+    if right <= left || bottom <= top {
+        return None;
+    }
+    Some(Rect::new(left as u16, top as u16, (right - left) as u16, (bottom - top) as u16))
+}
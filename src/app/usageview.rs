@@ -0,0 +1,238 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::KeyCode;
+use ratatui::{
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::mooncell::{FileType, Mooncell, UsageEntry, UsageFlag, UsageProgress};
+use super::treemapview::Treemapview;
+
+// 等待确认的删除
+#[derive(Clone, Copy, PartialEq)]
+pub enum UsagePending {
+    Null,
+    Trash,    // 移到回收站
+    Delete,    // 永久删除
+}
+
+const BAR_WIDTH: usize = 20;
+
+/*
+ * @概述        磁盘占用分析，显示当前文件夹的子条目，按占用空间从大到小排序
+ *              列表的每一行显示占上一层的百分比、大小和包含的条目数，也可以切换为矩形树图
+ */
+pub struct Usageview {
+    table_state: TableState,
+    pending: UsagePending,
+    message: String,    // 操作结果的提示
+    cross_fs: bool,    // 进入其他文件系统
+    show_treemap: bool,
+    treemap: Treemapview,
+}
+
+impl Usageview {
+    pub fn new() -> Self {
+        let mut table_state = TableState::default();
+        table_state.select_first();
+        Self {
+            table_state,
+            pending: UsagePending::Null,
+            message: String::new(),
+            cross_fs: false,
+            show_treemap: false,
+            treemap: Treemapview::new(),
+        }
+    }
+
+    pub fn next(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(i) if i + 1 < len => i + 1,
+            _ => 0,
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn previous(&mut self, len: usize) {
+        let i = match self.table_state.selected() {
+            Some(0) | None => len.saturating_sub(1),
+            Some(i) => (i - 1).min(len.saturating_sub(1)),
+        };
+        self.table_state.select(Some(i));
+    }
+
+    pub fn select(&mut self, pos: usize) {
+        self.table_state.select(Some(pos));
+    }
+
+    pub fn get_selected<'a>(&self, entries: &'a [UsageEntry]) -> Option<&'a UsageEntry> {
+        entries.get(self.table_state.selected()?)
+    }
+
+    /*
+     * @概述        准备删除，需要再确认一次
+     */
+    pub fn ready(&mut self, pending: UsagePending) {
+        self.pending = pending;
+    }
+
+    pub fn take_pending(&mut self) -> UsagePending {
+        std::mem::replace(&mut self.pending, UsagePending::Null)
+    }
+
+    pub fn set_message(&mut self, message: String) {
+        self.message = message;
+    }
+
+    pub fn switch_cross_fs(&mut self) {
+        self.cross_fs = !self.cross_fs;
+    }
+
+    pub fn is_cross_fs(&self) -> bool {
+        self.cross_fs
+    }
+
+    pub fn set_treemap(&mut self, show_treemap: bool) {
+        self.show_treemap = show_treemap;
+    }
+
+    pub fn is_treemap(&self) -> bool {
+        self.show_treemap
+    }
+
+    /*
+     * @概述        在矩形树图中按方向键移动到相邻的矩形
+     * @参数1       KeyCode
+     */
+    pub fn treemap_move(&mut self, key: KeyCode) {
+        let selected = self.table_state.selected().unwrap_or(0);
+        if let Some(i) = self.treemap.neighbor(selected, key) {
+            self.table_state.select(Some(i));
+        }
+    }
+
+    /*
+     * @参数2       &Path，统计的文件夹
+     * @参数3       Option<UsageProgress>，统计中时显示进度
+     * @参数4       Option<(PathBuf, UsageEntry)>，正在查看的文件夹
+     * @参数5       &[UsageEntry]，子条目
+     * @参数6       &[(FileType, Color)]，矩形树图中每种文件类型的颜色
+     */
+    pub fn draw(
+        &mut self,
+        frame: &mut Frame,
+        root: &Path,
+        progress: Option<UsageProgress>,
+        current: Option<(PathBuf, UsageEntry)>,
+        entries: &[UsageEntry],
+        type_colors: &[(FileType, Color)],
+    ) {
+        let layout_usage = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(vec![
+                Constraint::Fill(1),    // 列表
+                Constraint::Length(1),    // 提示
+            ])
+            .split(frame.area());
+
+        if let Some(pos) = self.table_state.selected()
+            && pos >= entries.len() {
+            self.table_state.select(Some(entries.len().saturating_sub(1)));
+        }
+
+        match (progress, current) {
+            (Some(progress), _) => {
+                let progress_p = Paragraph::new(format!(
+                    "scanning {}\n\n{} item(s), {}\n{}",
+                    root.to_string_lossy(),
+                    progress.items,
+                    Mooncell::bytes_to_string(progress.disk_usage),
+                    progress.current.to_string_lossy(),
+                ))
+                .block(Block::bordered().title("disk usage"));
+                frame.render_widget(progress_p, layout_usage[0]);
+            }
+            (None, Some((path, dir))) if self.show_treemap => {
+                let title = format!(
+                    "{}    {} in {} item(s)",
+                    path.to_string_lossy(),
+                    Mooncell::bytes_to_string(dir.disk_usage),
+                    dir.items.saturating_sub(1),
+                );
+                self.treemap.draw(frame, layout_usage[0], entries, self.table_state.selected(), title, type_colors);
+            }
+            (None, Some((path, dir))) => {
+                let rows: Vec<Row> = entries
+                    .iter()
+                    .map(|entry| {
+                        let percent = if dir.disk_usage > 0 { entry.disk_usage as f64 / dir.disk_usage as f64 } else { 0.0 };
+                        let filled = ((percent * BAR_WIDTH as f64).round() as usize).min(BAR_WIDTH);
+                        let name = if entry.is_dir { format!("{}/", entry.name) } else { entry.name.clone() };
+                        let row = Row::new(vec![
+                            format!("{:>5.1}% [{}{}]", percent * 100.0, "#".repeat(filled), " ".repeat(BAR_WIDTH - filled)),
+                            Mooncell::bytes_to_string(entry.disk_usage),
+                            if entry.is_dir { entry.items.saturating_sub(1).to_string() } else { String::new() },
+                            name,
+                            entry.flag.to_str().to_string(),
+                        ]);
+                        match entry.flag {
+                            UsageFlag::Normal => row,
+                            _ => row.style(Style::default().fg(Color::DarkGray)),
+                        }
+                    })
+                    .collect();
+                let widths = [
+                    Constraint::Length(BAR_WIDTH as u16 + 9),
+                    Constraint::Length(10),
+                    Constraint::Length(10),
+                    Constraint::Fill(1),
+                    Constraint::Length(10),
+                ];
+                let title = format!(
+                    "{}    {} in {} item(s){}",
+                    path.to_string_lossy(),
+                    Mooncell::bytes_to_string(dir.disk_usage),
+                    dir.items.saturating_sub(1),
+                    if self.cross_fs { "    crossing filesystems" } else { "" },
+                );
+                let table = Table::new(rows, widths)
+                    .header(
+                        Row::new(vec!["Share", "Size", "Items", "Name", ""])
+                            .style(Style::default().fg(Color::Yellow)),
+                    )
+                    .block(Block::bordered().title(title))
+                    .row_highlight_style(
+                        Style::default()
+                            .bg(Color::LightBlue)
+                            .fg(Color::Black)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                frame.render_stateful_widget(table, layout_usage[0], &mut self.table_state);
+            }
+            (None, None) => {
+                let empty_p = Paragraph::new("no data, rescan[r]")
+                    .block(Block::bordered().title("disk usage"));
+                frame.render_widget(empty_p, layout_usage[0]);
+            }
+        }
+
+        // tips
+        let tips_str = match (self.pending, self.get_selected(entries)) {
+            (UsagePending::Trash, Some(entry)) => format!("move {} to trash? [y/n]", entry.name),
+            (UsagePending::Delete, Some(entry)) => format!("delete {} permanently? [y/n]", entry.name),
+            _ if !self.message.is_empty() => self.message.clone(),
+            _ => String::from("open[enter]    up[backspace]    treemap[t]    trash[d]    delete permanently[D]    show in filemanage[f]    rescan[r]    cross filesystems[x]    exit[esc]"),
+        };
+        let tips_style = match self.pending {
+            UsagePending::Null => Style::default(),
+            _ => Style::default().fg(Color::Red),
+        };
+        let tips_p = Paragraph::new(tips_str)
+            .alignment(ratatui::layout::Alignment::Center)
+            .style(tips_style);
+        frame.render_widget(tips_p, layout_usage[1]);
+    }
+}